@@ -3,10 +3,39 @@
 //! Note: This is illustrative, not exhaustive or necessarily idiomatic for a real project.
 
 // Import necessary items from the standard library
+use std::any::Any; // For runtime type inspection and downcasting
+use std::cell::OnceCell; // Write-once caching
+use std::cell::RefCell; // For interior mutability
+use std::cmp::Ordering; // Result of comparisons
+use std::cmp::Reverse; // Key wrapper that flips an ordering
+use std::collections::hash_map::Entry; // Occupied/Vacant views into a HashMap
 use std::collections::HashMap;
+use std::collections::HashSet; // Membership tests without values
+use std::collections::{BTreeMap, BTreeSet}; // Sorted maps and sets with range queries
+use std::error::Error; // For error chains via `source()`
 use std::fmt; // For implementing Display trait
+use std::fs; // Reading and writing files
+use std::hash::Hash; // Bound for HashMap keys
+use std::io; // `io::Error` for file failures
+use std::iter::Sum; // For `.sum()` on custom types
+use std::mem; // replace/take/swap to move values out from behind `&mut`
+use std::num::NonZeroUsize; // Thread counts from `available_parallelism`
+use std::num::ParseIntError; // Wrapped by field-level errors
+use std::ops::{Add, Sub}; // For operator overloading
+use std::ops::{BitAnd, BitOr, BitOrAssign, Not}; // Bitwise operators for flag sets
+use std::ops::{Deref, DerefMut}; // Smart-pointer access for pool guards
+use std::panic::{self, AssertUnwindSafe}; // Catching panics at a boundary
+use std::rc::Rc; // Single-threaded reference counting
+use std::rc::Weak; // Non-owning counterpart to Rc
+use std::str::ParseBoolError;
+use std::sync::atomic::AtomicU64; // Lock-free progress counter
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering}; // Lock-free shared flags
+use std::sync::Condvar; // Waiting for a pooled item to come back
+use std::sync::mpsc; // Channels between threads
+use std::sync::{Arc, Mutex}; // Thread-safe shared ownership and locking
 use std::thread;
 use std::time::Duration;
+use std::time::Instant; // Monotonic timestamps
 
 // === 1. Basic Syntax: Variables, Data Types, Comments ===
 
@@ -19,16 +48,335 @@ use std::time::Duration;
 
 /// Doc comment for the main function (used by rustdoc)
 fn main() {
-    println!("--- Rust Feature Showcase ---");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        print_usage();
+        return;
+    }
+    // `--verify` checks the reported sections against stored snapshots instead of printing
+    if args.iter().any(|arg| arg == "--verify") {
+        std::process::exit(verify_snapshots());
+    }
+    if args.iter().any(|arg| arg == "--list") {
+        for (number, section) in (1..).zip(registry()) {
+            println!("{:>2}. {:<32} {}", number, section.name, section.description);
+        }
+        return;
+    }
+
+    // Any other arguments name the sections to run (case-insensitive); none means all of them
+    let mut selected = Vec::new();
+    for name in &args {
+        match find_section(name) {
+            Some(section) => selected.push(section.name),
+            None => {
+                eprintln!("Unknown section '{}' (see --list)", name);
+                std::process::exit(2);
+            }
+        }
+    }
+    run_sections(|section| selected.is_empty() || selected.contains(&section.name));
+
+    println!("\n--- End of Showcase ---");
+} // End of main function
+
+fn print_usage() {
+    println!("Usage: rust_showcase [--list | --verify | SECTION...]");
+    println!();
+    println!("  SECTION...  run only the named sections (case-insensitive, see --list)");
+    println!("  --list      list every section with a short description");
+    println!("  --verify    compare each section's output with snapshots/*.txt");
+}
+
+// === Section Registry ===
+
+/// One demo of the showcase, printed under a `--- name ---` heading.
+struct Section {
+    name: &'static str,
+    description: &'static str,
+    output: Output,
+}
+
+/// How a section produces its output.
+enum Output {
+    /// Prints directly to stdout, for sections whose threads print while they run.
+    /// `--verify` skips these and says why, see `UNVERIFIED`.
+    Printed(fn(&mut Showcase)),
+    /// Writes through a `SectionReporter`, so `--verify` can check it without printing.
+    Reported(fn(&mut SectionReporter, &mut Showcase)),
+}
+
+impl Section {
+    // `const` so the registry can be built in a `static`
+    const fn new(name: &'static str, description: &'static str, run: fn(&mut Showcase)) -> Self {
+        Section { name, description, output: Output::Printed(run) }
+    }
+
+    const fn reported(
+        name: &'static str,
+        description: &'static str,
+        report: fn(&mut SectionReporter, &mut Showcase),
+    ) -> Self {
+        Section { name, description, output: Output::Reported(report) }
+    }
+
+    fn run(&self, showcase: &mut Showcase) {
+        match self.output {
+            Output::Printed(run) => run(showcase),
+            Output::Reported(report) => reported(showcase, report),
+        }
+    }
+}
+
+/// What the sections share: the settings, and values that several sections build on.
+struct Showcase {
+    config: config::ShowcaseConfig,
+    data: ShowcaseData,
+}
+
+impl Showcase {
+    /// Reads optional settings from the environment, falling back to defaults on bad input.
+    fn from_env() -> Self {
+        let config = config::ShowcaseConfig::from_env().unwrap_or_else(|e| {
+            eprintln!("Config error: {} (using defaults)", e);
+            config::ShowcaseConfig::default()
+        });
+        Showcase { config, data: showcase_data() }
+    }
+}
+
+/// Every section in showcase order. Adding a demo means writing its function, adding one
+/// entry here, and adding its snapshot (see `SNAPSHOTS`). Sections write through a
+/// `SectionReporter` unless their threads print directly, see `Output`.
+static SECTIONS: &[Section] = &[
+    // === 1. Basic Syntax ===
+    Section::reported("Basic Syntax", "variables, scalar types, tuples, and arrays", basic_syntax_section),
+    // === 2. Control Flow ===
+    Section::reported("Control Flow", "branching and loops", |r, _| control_flow_section(r)),
+    // === 3. Functions ===
+    Section::reported("Functions", "calling plain and recursive functions", |r, _| functions_section(r)),
+    // === 4. Ownership & Borrowing ===
+    Section::reported("Ownership & Borrowing", "moves, copies, and references", |r, _| ownership_section(r)),
+    // === 5. Structs ===
+    Section::reported(
+        "Structs",
+        "named-field, tuple, and unit-like structs",
+        |r, showcase| structs_section(r, &mut showcase.data.user1),
+    ),
+    // === 6. Enums ===
+    Section::reported("Enums", "enum variants, matching, and a message interpreter", |r, _| enums_section(r)),
+    // === 7. Methods ===
+    Section::reported(
+        "Methods",
+        "methods and associated functions on `Rectangle`",
+        |r, showcase| methods_section(r, &showcase.data.rect),
+    ),
+    // === 8. Traits ===
+    Section::reported(
+        "Traits",
+        "shared behavior through traits, static and dynamic dispatch",
+        |r, showcase| {
+            let data = &showcase.data;
+            traits_section(r, &data.tweet, &data.article, &data.rect)
+        },
+    ),
+    // === 9. Generics ===
+    Section::reported("Generics", "generic functions and types", generics_section),
+    // === 10. Error Handling ===
+    Section::reported("Error Handling", "`Option`, `Result`, `?`, and layered errors", error_handling_section),
+    // === 11. Collections ===
+    Section::reported("Collections", "`Vec`, `HashMap`, and a custom stack", collections_section),
+    // === 12. Strings ===
+    Section::reported("Strings", "`&str` and `String`", strings_section),
+    // === 13. Modules ===
+    Section::reported("Modules", "visibility and nested modules", modules_section),
+    // === 14. Macros ===
+    Section::reported("Macros", "declarative macros", macros_section),
+    // === 15. Lifetimes ===
+    Section::reported("Lifetimes", "lifetime annotations and elision", lifetimes_section),
+    // === 16. Closures ===
+    Section::reported("Closures", "closures and what they capture", closures_section),
+    // === 17. Concurrency (Basic Threads) ===
+    Section::new("Concurrency (Basic Threads)", "threads, channels, and shared state", concurrency_section),
+    // === 18. Graphs (BFS) ===
+    Section::reported("Graphs (BFS)", "breadth-first search over a graph", graphs_section),
+    // === 19. Numeral Conversions ===
+    Section::reported("Numeral Conversions", "Roman numerals and other bases", numerals_section),
+    // === 20. Measurements ===
+    Section::reported("Measurements", "operator overloading with units", measurements_section),
+    // === 21. Number Theory ===
+    Section::reported("Number Theory", "number theory with iterators", number_theory_section),
+    // === 22. Grouping ===
+    Section::reported("Grouping", "grouping records with `HashMap`", grouping_section),
+    // === 23. Streaming Parsing ===
+    Section::reported("Streaming Parsing", "parsing a stream line by line", streaming_parsing_section),
+    // === 24. Interior Mutability ===
+    Section::reported("Interior Mutability", "`Cell` vs `RefCell` vs `Mutex`", interior_mutability_section),
+    // === 25. Formatting ===
+    Section::reported("Formatting", "format specifiers and alignment", formatting_section),
+    // === 26. Circuit Breaker ===
+    Section::reported("Circuit Breaker", "a circuit breaker around fallible calls", circuit_breaker_section),
+    // === 27. Observers ===
+    Section::reported("Observers", "observers held through `Weak` references", observers_section),
+    // === 28. Bitflags ===
+    Section::reported("Bitflags", "bit manipulation and bitflags", bitflags_section),
+    // === 29. Memoization ===
+    Section::reported("Memoization", "memoization with a sparse table", memoization_section),
+    // === 30. Trait Object Lifetimes ===
+    Section::reported(
+        "Trait Object Lifetimes",
+        "trait object lifetimes: `+ 'a` vs `+ 'static`",
+        trait_object_lifetimes_section,
+    ),
+    // === 31. Feed Digest ===
+    Section::reported("Feed Digest", "a line-oriented digest format", feed_digest_section),
+    // === 32. Input Validation ===
+    Section::reported("Input Validation", "validating user input", input_validation_section),
+    // === 33. Log Pipeline ===
+    Section::reported("Log Pipeline", "an iterator chain vs threads and channels", log_pipeline_section),
+    // === 34. Dispatch Comparison ===
+    Section::reported("Dispatch Comparison", "dynamic dispatch vs enum dispatch", dispatch_comparison_section),
+    // === 35. Custom Hash Keys ===
+    Section::reported("Custom Hash Keys", "custom hash keys", custom_hash_keys_section),
+    // === 36. Company Directory ===
+    Section::reported("Company Directory", "departments with `HashMap`, `Vec`, and sorting", company_directory_section),
+    // === 37. Vec Manipulation ===
+    Section::reported("Vec Manipulation", "retaining, draining, and splitting vectors", vec_manipulation_section),
+    // === 38. String Interning ===
+    Section::reported("String Interning", "string interning", string_interning_section),
+    // === 39. std::mem ===
+    Section::reported("std::mem", "`mem::replace`, `take`, and `swap`", std_mem_section),
+    // === 40. Percentages and Probabilities ===
+    Section::reported("Percentages and Probabilities", "range-checked newtypes", range_checked_newtypes_section),
+    // === 41. Parallel Map ===
+    Section::reported("Parallel Map", "parallel map with a shared work queue", parallel_map_section),
+    // === 42. Leaderboard ===
+    Section::reported("Leaderboard", "ordered collections: `BTreeMap` and `BTreeSet`", leaderboard_section),
+    // === 43. Custom Iterators ===
+    Section::reported("Custom Iterators", "`DoubleEndedIterator` and `ExactSizeIterator`", custom_iterators_section),
+    // === 44. Templates ===
+    Section::reported("Templates", "string templates with placeholders", templates_section),
+    // === 45. Sorting ===
+    Section::reported("Sorting", "sorting by composite keys", sorting_section),
+    // === 46. Shelf Packing ===
+    Section::reported("Shelf Packing", "shelf packing rectangles into a bin", shelf_packing_section),
+    // === 47. Object Pools ===
+    Section::reported("Object Pools", "object pools with RAII guards", object_pools_section),
+    // === 48. Text Editing ===
+    Section::reported("Text Editing", "a two-stack text buffer", text_editing_section),
+    // === 49. Progress Bars ===
+    Section::new("Progress Bars", "a progress bar over worker threads", progress_bars_section),
+    // === 50. Units with PhantomData ===
+    Section::reported("Units with PhantomData", "units in the type system with `PhantomData`", units_section),
+    // === 51. Unwind Safety ===
+    Section::reported("Unwind Safety", "unwind safety and `catch_unwind`", unwind_safety_section),
+    // === 52. Generational Arenas ===
+    Section::reported("Generational Arenas", "a scene graph in a generational arena", generational_arenas_section),
+];
+
+/// The registry, checked for duplicate names in debug builds (a duplicate would be
+/// unreachable through `find_section`).
+fn registry() -> &'static [Section] {
+    debug_assert!(has_unique_names(SECTIONS), "duplicate section names in SECTIONS");
+    SECTIONS
+}
+
+fn has_unique_names(sections: &[Section]) -> bool {
+    let mut seen = HashSet::new();
+    sections.iter().all(|section| seen.insert(section.name.to_ascii_lowercase()))
+}
+
+/// Looks a section up by name, ignoring ASCII case.
+fn find_section(name: &str) -> Option<&'static Section> {
+    registry().iter().find(|section| section.name.eq_ignore_ascii_case(name))
+}
+
+/// Prints the banner, then runs the sections `filter` accepts in registry order. They
+/// share one `Showcase`, so a skipped section's changes (section 5 edits `user1`'s email,
+/// say) are simply not seen by later ones.
+fn run_sections(filter: impl Fn(&Section) -> bool) {
+    let mut showcase = Showcase::from_env();
+    println!("--- {} ---", showcase.config.name);
+    if showcase.config.verbose {
+        println!("Config: {:?}", showcase.config);
+    }
+    run_section_list(registry(), &mut showcase, &filter);
+}
+
+/// Runs the accepted sections of `sections` in order, returning how many ran.
+fn run_section_list(sections: &[Section], showcase: &mut Showcase, filter: &dyn Fn(&Section) -> bool) -> usize {
+    let mut ran = 0;
+    for section in sections.iter().filter(|section| filter(section)) {
+        section.run(showcase);
+        ran += 1;
+    }
+    ran
+}
+
+/// Adapts a reporter-based section: runs it into a fresh `SectionReporter` and prints the result.
+fn reported(showcase: &mut Showcase, section: fn(&mut SectionReporter, &mut Showcase)) {
+    let mut reporter = SectionReporter::new();
+    section(&mut reporter, showcase);
+    print!("{}", reporter.finish());
+}
+
+// === Showcase Sections ===
+// Sections write through a `SectionReporter` instead of printing directly, see `Output`.
+
+/// The values sections 5-8 operate on; later sections keep using them.
+struct ShowcaseData {
+    user1: User,
+    tweet: Tweet,
+    article: NewsArticle,
+    rect: Rectangle,
+    numbers: Vec<i32>,
+    /// Team scores, built in the Collections style and reused by later sections.
+    scores: HashMap<String, i32>,
+}
+
+fn showcase_data() -> ShowcaseData {
+    // Instantiate a struct
+    let user1 = User {
+        username: String::from("john_doe"),
+        email: String::from("john@example.com"),
+        sign_in_count: 1,
+        active: true,
+    };
+    let rect = Rectangle { width: 30, height: 50 };
+    let tweet = Tweet {
+        username: String::from("horse_ebooks"),
+        content: String::from("of course, as you probably already know"),
+        reply: false,
+        retweet: false,
+    };
+    let article = NewsArticle {
+        headline: String::from("Penguins win the Stanley Cup Championship!"),
+        location: String::from("Pittsburgh, PA, USA"),
+        author: String::from("Iceburgh"),
+        content: String::from("The Pittsburgh Penguins once again are the best hockey team in the NHL."),
+    };
+    let numbers = vec![1, 2, 3, 4, 5];
+    // HashMap<K, V> - key-value store
+    let mut scores = HashMap::new();
+    scores.insert(String::from("Blue"), 10);
+    scores.insert(String::from("Yellow"), 50);
+    // Entry API: update an existing key or insert a new one in a single lookup
+    upsert_score(&mut scores, "Blue", 5);
+    upsert_score(&mut scores, "Red", 30);
+    ShowcaseData { user1, tweet, article, rect, numbers, scores }
+}
 
+/// Section 1: variables, scalar types, tuples, and arrays.
+fn basic_syntax_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Basic Syntax");
     // Immutable variable binding (type inferred)
     let an_integer = 42;
     // Mutable variable binding (type annotated)
     let mut a_float: f64 = 3.14;
 
-    println!("Integer: {}, Mutable Float: {}", an_integer, a_float);
+    r.line(format!("Integer: {}, Mutable Float: {}", an_integer, a_float));
     a_float = 2.71; // Can mutate `a_float`
-    println!("Mutated float: {}", a_float); 
+    r.line(format!("Mutated float: {}", a_float)); 
 
     // Basic types
     let _is_active: bool = true;
@@ -38,212 +386,507 @@ fn main() {
     // Tuples: Grouping fixed number of values of potentially different types
     let tup: (i32, f64, char) = (500, 6.4, '✅');
     let (x, y, _z) = tup; // Destructuring the tuple
-    println!("Tuple elements: x={}, y={}", x, y);
-    println!("Accessing tuple element by index: {}", tup.0);
+    r.line(format!("Tuple elements: x={}, y={}", x, y));
+    r.line(format!("Accessing tuple element by index: {}", tup.0));
 
     // Arrays: Fixed size, same type elements (stack allocated)
     let array: [i32; 3] = [1, 2, 3];
-    println!("First array element: {}", array[0]);
+    r.line(format!("First array element: {}", array[0]));
+}
 
-    // === 2. Control Flow ===
-    println!("\n--- Control Flow ---");
-    control_flow_example(7);
-    loop_examples();
+/// Section 2: branching and loops.
+fn control_flow_section(r: &mut SectionReporter) {
+    r.section("Control Flow");
+    control_flow_example(7, r);
+    loop_examples(r);
+}
 
-    // === 3. Functions ===
-    println!("\n--- Functions ---");
+/// Section 3: calling plain and recursive functions.
+fn functions_section(r: &mut SectionReporter) {
+    r.section("Functions");
     let sum = add_numbers(10, 5);
-    println!("Sum from function: {}", sum);
-    let fact = factorial(5);
-    println!("Factorial of 5 (recursive): {}", fact);
+    r.line(format!("Sum from function: {}", sum));
+    if let Some(fact) = factorial(5) {
+        r.line(format!("Factorial of 5 (recursive): {}", fact));
+    }
+    r.line(format!("Factorial of 20 (iterative): {:?}", factorial_iter(20)));
+    r.line(format!("Factorial of 21: {:?} (the u64 limit is {}!)", factorial(21), MAX_FACTORIAL_INPUT));
+}
 
-    // === 4. Ownership & Borrowing ===
-    println!("\n--- Ownership & Borrowing ---");
-    ownership_demo();
-    borrowing_demo();
+/// Section 4: moves, copies, and references.
+fn ownership_section(r: &mut SectionReporter) {
+    r.section("Ownership & Borrowing");
+    ownership_demo(r);
+    borrowing_demo(r);
+    borrowing_advanced_demo(r);
+    clone_vs_copy_demo(r);
+}
 
-    // === 5. Structs ===
-    println!("\n--- Structs ---");
-    // Instantiate a struct
-    let mut user1 = User {
-        username: String::from("john_doe"),
-        email: String::from("john@example.com"),
-        sign_in_count: 1,
-        active: true,
-    };
-    println!("User: {}, Email: {}", user1.username, user1.email);
+/// Section 5: named-field, tuple, and unit-like structs.
+fn structs_section(r: &mut SectionReporter, user1: &mut User) {
+    r.section("Structs");
+    r.line(format!("User: {}, Email: {}", user1.username, user1.email));
     user1.email = String::from("john.doe@newdomain.com"); // Mutable field
-    println!("User sign-ins: {}, Active: {}", user1.sign_in_count, user1.active);
+    r.line(format!("User sign-ins: {}, Active: {}", user1.sign_in_count, user1.active));
 
     // Tuple struct
     let black = Color(0, 0, 0);
-    println!("Color: ({}, {}, {})", black.0, black.1, black.2);
+    r.line(format!("Color: ({}, {}, {})", black.0, black.1, black.2));
+    // Operator overloading on Color: `+` and `-` saturate, wrapping is opt-in
+    let orange = Color(200, 100, 0);
+    r.line(format!("Saturating add: {:?}", orange + Color(100, 100, 100)));
+    r.line(format!("Wrapping add: {:?}", orange.wrapping_add(Color(100, 100, 100))));
+    r.line(format!("Dimmed: {:?}", orange - Color(50, 150, 10)));
+    // Conversions: `From` never fails, `TryFrom` validates
+    let teal: Color = (0u8, 128u8, 128u8).into();
+    r.line(format!("From tuple: {:?}", teal));
+    match Color::try_from((12i64, 300i64, -4i64)) {
+        Ok(c) => r.line(format!("TryFrom tuple: {:?}", c)),
+        Err(e) => r.line(format!("Error: {}", e)),
+    }
+    // Blending: averaging sRGB bytes directly vs averaging in linear light
+    let (red, green) = (Color(255, 0, 0), Color(0, 255, 0));
+    let naive = Color(red.0 / 2 + green.0 / 2, red.1 / 2 + green.1 / 2, red.2 / 2 + green.2 / 2);
+    let (lr, lg) = (red.to_linear(), green.to_linear());
+    let linear_mix = Color::from_linear([(lr[0] + lg[0]) / 2.0, (lr[1] + lg[1]) / 2.0, (lr[2] + lg[2]) / 2.0]);
+    r.line(format!("Red/green blend: naive {:?}, linear {:?}", naive, linear_mix));
+    r.line(format!("Average of black and white: {:?}", Color::average(&[black, Color(255, 255, 255)])));
+    let overlay_layer = Color(128, 64, 200);
+    for mode in [BlendMode::Normal, BlendMode::Multiply, BlendMode::Screen, BlendMode::Overlay, BlendMode::Add] {
+        r.line(format!("{:?} blend of {:?} over orange: {:?}", mode, overlay_layer, orange.blend(&overlay_layer, mode)));
+    }
+    r.line(format!("Mid-gray with gamma 2.2: {:?}", Color(128, 128, 128).gamma(2.2)));
+    for kelvin in [1900.0, 6500.0, 15000.0] {
+        r.line(format!("Black body at {}K: {:?}", kelvin, Color::from_kelvin(kelvin)));
+    }
+    let palette = [Color(255, 0, 0), Color(250, 4, 2), Color(0, 0, 255), Color(255, 0, 0), Color(0, 3, 250)];
+    r.line(format!("Palette without duplicates: {:?}", dedup_colors(&palette)));
+    r.line(format!("Palette without near-duplicates: {:?}", dedup_similar(&palette, 10.0)));
+    r.line(format!("Palette as hex, two per line:\n{}", hex_dump(&palette, 2)));
+    // Equal RGB steps are not equally visible: the eye is far more sensitive to green
+    let (dark_red, dark_green) = (Color(40, 0, 0), Color(0, 40, 0));
+    r.line(format!(
+        "From black to dark red: RGB {:.1}, ΔE {:.1}; to dark green: RGB {:.1}, ΔE {:.1}",
+        black.distance_rgb(&dark_red),
+        black.distance_lab(&dark_red),
+        black.distance_rgb(&dark_green),
+        black.distance_lab(&dark_green)
+    ));
+
+    // Rendering structs as ASCII art through a shared trait
+    let (tall, square) = (Rectangle { width: 30, height: 50 }, Rectangle::square(25));
+    r.line(render_side_by_side(&[&tall, &square, &orange]));
 
     // Unit-like struct (useful for traits)
     let _marker = AlwaysEqual;
+}
 
-    // === 6. Enums ===
-    println!("\n--- Enums ---");
+/// Section 6: enum variants, matching, and a message interpreter.
+fn enums_section(r: &mut SectionReporter) {
+    r.section("Enums");
     let msg1 = Message::Write(String::from("Hello from enum!"));
     let msg2 = Message::ChangeColor(10, 20, 30);
     let msg3 = Message::Quit;
     let msg4 = Message::Move { x: 50, y: -10 }; // Construct the Move variant
-    process_message(msg1);
-    process_message(msg2);
-    process_message(msg3);
-    process_message(msg4);
+    r.line(format!("Message variants: {}", Message::variant_names().join(", ")));
+    r.line(format!("Sample of each: {:?}", Message::sample_variants()));
+    r.line(format!("msg4 is a {} message", msg4.variant_name()));
+    let from_color = Message::from(Color(1, 2, 3));
+    r.line(format!("Message from Color: {:?}, back to color: {:?}", from_color, from_color.as_color()));
+    r.line(format!("as_color on Quit: {:?}", Message::Quit.as_color()));
+    process_message(msg1, r);
+    process_message(msg2, r);
+    process_message(msg3, r);
+    process_message(msg4, r);
+    // Interpreting messages: a cursor that records and can undo what it applied
+    let mut cursor = Cursor::new();
+    cursor.apply(Message::Move { x: 3, y: 4 });
+    cursor.apply(Message::ChangeColor(255, 0, 0));
+    cursor.apply(Message::Write(String::from("hi")));
+    cursor.apply(Message::Move { x: 10, y: -2 });
+    r.line(format!("Cursor at ({}, {}) after {} messages", cursor.x, cursor.y, cursor.history().len()));
+    r.line(format!("Undid {:?}", cursor.undo()));
+    r.line(format!("Cursor back at ({}, {}), color {:?}, text {:?}", cursor.x, cursor.y, cursor.color, cursor.text));
+    // Saving and resuming: the log is the state, so replaying a saved script restores it
+    let script = cursor.to_script();
+    r.line("Saved script:");
+    for line in script.lines() {
+        r.line(format!("  {}", line));
+    }
+    match Cursor::from_script(&script) {
+        Ok(resumed) => r.line(format!("Resumed at ({}, {}) with {} messages", resumed.x, resumed.y, resumed.history().len())),
+        Err(e) => r.line(format!("Error: {}", e)),
+    }
+    let stream = vec![
+        Message::Move { x: 1, y: 1 },
+        Message::Move { x: 2, y: 3 },
+        Message::ChangeColor(9, 9, 9),
+        Message::ChangeColor(0, 128, 255),
+        Message::Write(String::from("!")),
+        Message::Move { x: 5, y: 8 },
+    ];
+    let stream_len = stream.len();
+    r.line(format!("Coalesced {} streamed messages to {:?}", stream_len, coalesce(stream)));
+    r.line(format!("Bad script: {:?}", Cursor::from_script("0.000000000s move 1\n").map(|c| c.x).map_err(|e| e.to_string())));
+    if let Some(last) = cursor.history().last() {
+        r.line(format!("Last entry {:?} applied {:?} after the first", last.message, last.at));
+    }
+}
 
-    // === 7. Methods ===
-    println!("\n--- Methods ---");
-    let rect = Rectangle { width: 30, height: 50 };
-    println!("Rectangle area: {}", rect.area());
-    println!("Can rect hold another? {}", rect.can_hold(&Rectangle { width: 10, height: 40 }));
+/// Section 7: methods and associated functions on `Rectangle`.
+fn methods_section(r: &mut SectionReporter, rect: &Rectangle) {
+    r.section("Methods");
+    r.line(format!("Rectangle area: {}", rect.area()));
+    r.line(format!("Can rect hold another? {}", rect.can_hold(&Rectangle { width: 10, height: 40 })));
+    let twin = Rectangle { width: rect.width, height: rect.height };
+    r.line(format!("Equal-sized twin: can_hold {}, contains_rect {}", rect.can_hold(&twin), rect.contains_rect(&twin)));
     // Associated function (like static method)
     let square = Rectangle::square(25);
-    println!("Square area: {}", square.area());
+    r.line(format!("Square area: {}", square.area()));
+    r.line(format!("Rectangle perimeter: {}, diagonal: {:.2}", rect.perimeter(), rect.diagonal()));
+    r.line(format!("Inflated by (5, 2): {}, deflated by (20, 20): {}", rect.inflate(5, 2), rect.inflate(-20, -20)));
+    r.line(format!("Is rect a square? {}. Is square? {}", rect.is_square(), square.is_square()));
+    // Grid mapping: split a rectangle into cells and map a point back to its cell
+    let board = Rectangle { width: 40, height: 40 };
+    let probe = Point { x: 25, y: 12 };
+    if let Some((col, row)) = board.cell_containing(&probe, 4, 4) {
+        let cell = board.cell_at(col, row, 4, 4).unwrap();
+        r.line(format!("Point ({}, {}) is in cell ({}, {}) of a 4x4 grid, cell size {}", probe.x, probe.y, col, row, cell));
+    }
+    r.line(format!("Cell (4, 0) of a 4x4 grid: {:?}", board.cell_at(4, 0, 4, 4)));
+    // Dragging: keep a point on the board and find the side it is closest to
+    for drag in [Point { x: 25, y: 12 }, Point { x: -5, y: 60 }, Point { x: 38, y: 20 }] {
+        let kept = board.clamp_point(&drag);
+        r.line(format!(
+            "Drag to ({}, {}) clamps to ({}, {}), nearest edge {:?}",
+            drag.x, drag.y, kept.x, kept.y, board.nearest_edge(&drag)
+        ));
+    }
+}
 
-    // === 8. Traits (Polymorphism) ===
-    println!("\n--- Traits ---");
-    let tweet = Tweet {
-        username: String::from("horse_ebooks"),
-        content: String::from("of course, as you probably already know"),
+/// Section 8: shared behavior through traits, static and dynamic dispatch.
+fn traits_section(r: &mut SectionReporter, tweet: &Tweet, article: &NewsArticle, rect: &Rectangle) {
+    r.section("Traits");
+    r.line(format!("Tweet summary: {}", tweet.summarize()));
+    r.line(format!("Tweet has {} characters to spare", tweet.remaining_chars()));
+    r.line(format!("First words of the article: {:?}", article.words().take(4).collect::<Vec<_>>()));
+    for locale in ["en", "ja", "xx"] {
+        r.line(format!("Author ({}): {}", locale, article.summarize_author_localized(locale)));
+    }
+    match Tweet::new("penguin", &"🐧".repeat(TWEET_MAX_CHARS + 1), false, false) {
+        Ok(t) => r.line(format!("Created tweet: {}", t.summarize())),
+        Err(e) => r.line(format!("Error: {}", e)),
+    }
+    r.line(format_wrapped("Article summary:", &article.summarize()));
+    r.line(format_wrapped("Article content:", &article.content));
+    // Using the trait object for dynamic dispatch
+    r.line(notify_line(tweet));
+    r.line(notify_line(article));
+    // Lazily cached summaries: computed once, recomputed only after invalidation
+    let mut cached = CachedSummary::new(Tweet {
+        username: String::from("cache_bot"),
+        content: String::from("computed once"),
         reply: false,
         retweet: false,
-    };
-    let article = NewsArticle {
-        headline: String::from("Penguins win the Stanley Cup Championship!"),
-        location: String::from("Pittsburgh, PA, USA"),
-        author: String::from("Iceburgh"),
-        content: String::from("The Pittsburgh Penguins once again are the best hockey team in the NHL."),
-    };
-    println!("Tweet summary: {}", tweet.summarize());
-    println!("Article summary: {}", article.summarize());
-    // Using the trait object for dynamic dispatch
-    notify(&tweet);
-    notify(&article);
+    });
+    r.line(format!("Cached summary: {}", cached.summary()));
+    cached.inner_mut().content = String::from("edited after caching");
+    r.line(format!("Cached summary after edit: {}", cached.summary()));
+    // Downcasting with `Any`: a registry of mixed types, filtered by concrete type
+    let mut registry = Registry::new();
+    registry.insert(Rectangle { width: 3, height: 4 });
+    registry.insert(Tweet {
+        username: String::from("ferris"),
+        content: String::from("clack clack"),
+        reply: false,
+        retweet: false,
+    });
+    registry.insert(7u32);
+    registry.insert(Rectangle::square(2));
+    r.line(format!("Rectangles in registry: {:?}", registry.get_all::<Rectangle>()));
+    r.line(format!("Strings in registry: {}", registry.get_all::<String>().len()));
+    if let Some(first_tweet) = registry.take_first::<Tweet>() {
+        r.line(format!("Took tweet out of registry: {}", first_tweet.summarize()));
+    }
+    r.line(format!("Tweets left: {}", registry.get_all::<Tweet>().len()));
     // Using Display trait we implemented for Rectangle
-    println!("Rectangle Display: {}", rect);
+    r.line(format!("Rectangle Display: {}", rect));
+    r.line(format!("Rectangle summary (via Display): {}", rect.summarize()));
+    // Where clauses and multiple bounds
+    r.line(summarize_and_compare(tweet, article));
+    print_all(r, &[Rectangle::square(2), Rectangle { width: 4, height: 1 }]);
+    let feed = [
+        Tweet::new("short", "hi", false, false).unwrap(),
+        Tweet::new("longer", "a somewhat longer tweet", false, false).unwrap(),
+    ];
+    if let Some(loudest) = largest_summary(&feed) {
+        r.line(format!("Longest tweet summary: {}", loudest.summarize()));
+    }
+}
 
-    // === 9. Generics ===
-    println!("\n--- Generics ---");
+/// Section 9: generic functions and types.
+fn generics_section(r: &mut SectionReporter, showcase: &mut Showcase) {
+    let rect = &showcase.data.rect;
+    r.section("Generics");
     let number_list = vec![34, 50, 25, 100, 65];
     let largest_num = largest(&number_list);
-    println!("Largest number: {}", largest_num);
+    r.line(format!("Largest number: {}", largest_num));
 
     let char_list = vec!['y', 'm', 'c', 'a'];
     let largest_char = largest(&char_list);
-    println!("Largest char: {}", largest_char);
+    r.line(format!("Largest char: {}", largest_char));
+    // One generic function, with overflow handled per element type
+    r.line(format!(
+        "Checked area of 20x20 as u8: {:?}, as u16: {:?}",
+        checked_area(20u8, 20u8),
+        checked_area(20u16, 20u16)
+    ));
+    r.line(format!("Checked area of 1.5x2.5 as f64: {:?}", checked_area(1.5, 2.5)));
+    r.line(format!("Checked area of the showcase rect: {:?}", rect.checked_area()));
+    r.line(notify_line(rect)); // A trait bound lets one generic function serve every `Summary` type
+    // A custom comparator generalizes `largest`: reversed, or by any derived key
+    r.line(format!("Smallest number: {:?}", largest_by(&number_list, |a, b| b.cmp(a))));
+    let words = ["pinniped", "auk", "penguin", "tern"];
+    r.line(format!("Longest word: {:?}", largest_by(&words, |a, b| a.len().cmp(&b.len()))));
+    let temperatures = [21.5, 19.0, 23.25, 18.25];
+    r.line(format!("Sum of numbers: {}, of temperatures: {}", sum(&number_list), sum(&temperatures)));
+    r.line(format!("Mean temperature: {:?}, mean of nothing: {:?}", mean(&temperatures), mean(&[])));
 
     let p1: Point<i32> = Point { x: 5, y: 10 };
     let p2: Point<f64> = Point { x: 1.0, y: 4.0 };
-    println!("Generic Point: x = {}, y = {}", p1.x, p1.y()); // Using method on generic struct
-    println!("Generic Point: x = {}, y = {}", p2.x, p2.y());
+    r.line(format!("Generic Point: x = {}, y = {}", p1.x, p1.y())); // Using method on generic struct
+    r.line(format!("Generic Point: x = {}, y = {}", p2.x, p2.y()));
+    // Manual PartialOrd: lexicographic comparison, and sorting floats that contain NaN
+    r.line(format!("(5, 10) < (5, 11)? {}", p1 < Point { x: 5, y: 11 }));
+    let clamped = Point { x: 120, y: -5 }.clamp(Point { x: 0, y: 0 }, Point { x: 100, y: 50 });
+    r.line(format!("(120, -5) clamped to 100x50 box: ({}, {})", clamped.x, clamped.y));
+    if let Some(unit) = (Point { x: 3.0, y: 4.0 }).normalized() {
+        r.line(format!("(3, 4) normalized: ({}, {})", unit.x, unit.y));
+    }
+    let nan_point = Point { x: f64::NAN, y: 0.0 };
+    r.line(format!("NaN point == identical NaN point? {}", nan_point == Point { x: f64::NAN, y: 0.0 }));
+    let mut float_points = vec![
+        Point { x: 2.0, y: 1.0 },
+        Point { x: f64::NAN, y: 0.0 },
+        Point { x: -1.5, y: 3.0 },
+        Point { x: 2.0, y: -4.0 },
+    ];
+    sort_points(&mut float_points);
+    let sorted: Vec<String> = float_points.iter().map(|p| format!("({}, {})", p.x, p.y)).collect();
+    r.line(format!("Sorted points (NaN last): {}", sorted.join(" ")));
+    // Mixed axis types: a timestamp paired with a measurement
+    let reading: Pair<u64, f64> = Pair { x: 1_700_000_000, y: 21.5 };
+    let fahrenheit = reading.clone().map_y(|c| c * 9.0 / 5.0 + 32.0);
+    r.line(format!("Pair: t = {}, celsius = {}, fahrenheit = {}", reading.x(), reading.y(), fahrenheit.y()));
+    r.line(format!("Pair with x mapped to a label: {:?}", fahrenheit.map_x(|t| format!("t+{}", t % 1000))));
+}
 
-    // === 10. Error Handling (Option & Result) ===
-    println!("\n--- Error Handling ---");
-    let numbers = vec![1, 2, 3, 4, 5];
-    match find_item(&numbers, 3) {
-        Some(index) => println!("Found 3 at index: {}", index),
-        None => println!("3 not found in the list."),
+/// Section 10: `Option`, `Result`, `?`, and layered errors.
+fn error_handling_section(r: &mut SectionReporter, showcase: &mut Showcase) {
+    let user1 = &showcase.data.user1;
+    r.section("Error Handling");
+    let numbers = &showcase.data.numbers;
+    match find_item(numbers, 3) {
+        Some(index) => r.line(format!("Found 3 at index: {}", index)),
+        None => r.line("3 not found in the list."),
     }
-    match find_item(&numbers, 6) {
-        Some(index) => println!("Found 6 at index: {}", index),
-        None => println!("6 not found in the list."),
+    match find_item(numbers, 6) {
+        Some(index) => r.line(format!("Found 6 at index: {}", index)),
+        None => r.line("6 not found in the list."),
     }
+    // Lazy search: indices are produced on demand, so `take` stops scanning early
+    let rolls = [3, 6, 1, 6, 6, 2, 6];
+    let first_two: Vec<usize> = matches(&rolls, &6).take(2).collect();
+    r.line(format!("First two 6s at indices: {:?}", first_two));
+    r.line(format!("[6, 6, 2] starts at index: {:?}", find_subslice(&rolls, &[6, 6, 2])));
+    r.line(format!("[1, 1] starts at index: {:?}", find_subslice(&rolls, &[1, 1])));
 
     match divide(10.0, 2.0) {
-        Ok(result) => println!("10.0 / 2.0 = {}", result),
-        Err(e) => println!("Error: {}", e),
+        Ok(result) => r.line(format!("10.0 / 2.0 = {}", result)),
+        Err(e) => r.line(format!("Error: {}", e)),
     }
     match divide(10.0, 0.0) {
-        Ok(result) => println!("10.0 / 0.0 = {}", result), // This won't happen
-        Err(e) => println!("Error: {}", e),
+        Ok(result) => r.line(format!("10.0 / 0.0 = {}", result)), // This won't happen
+        Err(e) => r.line(format!("Error: {}", e)),
     }
     // Using the `?` operator for propagation
-    match process_division(20.0, 5.0) {
-        Ok(res) => println!("Processed division result: {}", res),
-        Err(e) => println!("Processing error: {}", e),
+    match process_division(20.0, 5.0, r) {
+        Ok(res) => r.line(format!("Processed division result: {}", res)),
+        Err(e) => r.line(format!("Processing error: {}", e)),
     }
-     match process_division(20.0, 0.0) {
-        Ok(res) => println!("Processed division result: {}", res),
-        Err(e) => println!("Processing error: {}", e),
+     match process_division(20.0, 0.0, r) {
+        Ok(res) => r.line(format!("Processed division result: {}", res)),
+        Err(e) => r.line(format!("Processing error: {}", e)),
     }
+    // Element-wise division over columns, stopping at the first bad pair
+    match divide_slices(&[10.0, 9.0, 8.0], &[2.0, 3.0, 4.0]) {
+        Ok(quotients) => r.line(format!("Column division: {:?}", quotients)),
+        Err(e) => r.line(format!("Column error: {}", e)),
+    }
+    match divide_slices(&[1.0, 2.0, 3.0], &[1.0, 2.0, 0.0]) {
+        Ok(quotients) => r.line(format!("Column division: {:?}", quotients)),
+        Err(e) => r.line(format!("Column error: {}", e)),
+    }
+    // Two failure modes behind one error type
+    for (a, b) in [(18.0, 2.0), (1.0, 0.0), (-8.0, 2.0)] {
+        match process_sqrt_divide(a, b) {
+            Ok(root) => r.line(format!("sqrt({} / {}) = {}", a, b, root)),
+            Err(e) => r.line(format!("sqrt({} / {}): {}", a, b, e)),
+        }
+    }
+    // Layered errors: each layer wraps its cause, reachable through `source()`
+    if let Err(e) = load_profile("no/such/profile.txt") {
+        print_error_chain(r, &e);
+    }
+    // The same layering without an error enum: `context` notes what was being attempted
+    if let Err(e) = load_users("no/such/users.txt") {
+        print_error_chain(r, e.as_ref());
+    }
+    let users_path = std::env::temp_dir().join("rust_showcase_users.txt");
+    let users_path = users_path.to_string_lossy();
+    let users = "username=pingu\nemail=pingu@example.com\nsign_in_count=3\nactive=true\n\n\
+                 username=pinga\nemail=pinga@example.com\nsign_in_count=many\nactive=true\n";
+    match fs::write(users_path.as_ref(), users) {
+        Ok(()) => {
+            if let Err(e) = load_users(&users_path) {
+                print_error_chain(r, e.as_ref());
+            }
+            let _ = fs::remove_file(users_path.as_ref()); // Best-effort cleanup
+        }
+        Err(e) => r.line(format!("Could not write sample users file: {}", e)),
+    }
+    // The `?` operator on Option
+    r.line(format!(
+        "Last char of first line: {:?} / {:?}",
+        last_char_of_first_line("Hello!\nWorld"),
+        last_char_of_first_line("")
+    ));
+    let sentence = "the quick brown fox";
+    r.line(format!("First word longer than 4: {:?}", first_word_longer_than(sentence, 4)));
+    r.line(format!("First word longer than 5: {:?}", first_word_longer_than(sentence, 5)));
+    let no_at = User {
+        username: String::from("anon"),
+        email: String::from("anon.example.com"),
+        sign_in_count: 0,
+        active: false,
+    };
+    r.line(format!("Email domains: {:?} / {:?}", user_email_domain(user1), user_email_domain(&no_at)));
+    match user1.email_typed() {
+        Ok(email) => r.line(format!("Typed email {}: local {:?}, domain {:?}", email, email.local(), email.domain())),
+        Err(e) => r.line(format!("Error: {}", e)),
+    }
+    match no_at.email_typed() {
+        Ok(email) => r.line(format!("Typed email {}", email)),
+        Err(e) => r.line(format!("Error: {}", e)),
+    }
+    r.line(format!("First line as number: {:?} / {:?}", first_line_number("42\nrest"), first_line_number("")));
+}
 
-
-    // === 11. Collections ===
-    println!("\n--- Collections ---");
+/// Section 11: `Vec`, `HashMap`, and a custom stack.
+fn collections_section(r: &mut SectionReporter, showcase: &mut Showcase) {
+    let config = &showcase.config;
+    r.section("Collections");
     // Vector (Vec<T>) - growable array
     let mut my_vec: Vec<i32> = Vec::new();
     my_vec.push(10);
     my_vec.push(20);
     my_vec.push(30);
-    println!("Vector: {:?}", my_vec); // Debug print format
+    r.line(format!("Vector: {:?}", my_vec)); // Debug print format
     let third = my_vec.get(2); // Returns Option<&i32>
     if let Some(val) = third {
-        println!("Third element: {}", val);
+        r.line(format!("Third element: {}", val));
     }
     // Using vec! macro
-    let v2 = vec![100, 200, 300];
-    for i in &v2 { // Iterate by reference
-        println!("Vec item: {}", i);
+    let mut v2 = vec![100, 200, 300];
+    v2.truncate(config.max_items); // Cap printed items via config
+    for (n, i) in (1..).zip(&v2) { // Iterate by reference
+        r.line(format!("{} vec item: {}", humanize::ordinal(n), i));
     }
 
-    // HashMap<K, V> - key-value store
-    let mut scores = HashMap::new();
-    scores.insert(String::from("Blue"), 10);
-    scores.insert(String::from("Yellow"), 50);
+    let daily_visits = [12.0, 15.0, 9.0, 21.0, 18.0, 30.0];
+    r.line(format!("3-day moving average of {:?}: {:?}", daily_visits, windows_mean(&daily_visits, 3)));
+    // A custom container built on Vec: only push, pop and peek at the top
+    let mut plates = Stack::new();
+    plates.push("bottom");
+    plates.push("middle");
+    plates.push("top");
+    r.line(format!("Stack of {} plates, top: {:?}", plates.len(), plates.peek()));
+    r.line(format!("Popped: {:?}", plates.pop()));
+    r.line(format!("Remaining, in pop order: {:?}", plates.into_iter().collect::<Vec<_>>()));
+    let letters: Stack<char> = "abc".chars().collect(); // Last collected ends up on top
+    r.line(format!("Collected stack: top {:?}, empty: {}", letters.peek(), letters.is_empty()));
+
+    // HashMap<K, V> - key-value store (the team scores are built in `showcase_data`)
+    let scores = &showcase.data.scores;
     let team_name = String::from("Blue");
     let score = scores.get(&team_name); // Returns Option<&V>
     match score {
-        Some(s) => println!("Score for Blue team: {}", s),
-        None => println!("Blue team not found."),
+        Some(s) => r.line(format!("Score for Blue team: {}", s)),
+        None => r.line("Blue team not found."),
     }
-    // Iterate over HashMap
-    for (key, value) in &scores {
-        println!("{}: {}", key, value);
+    // Iterate over HashMap, in name order since HashMap order varies between runs
+    let mut entries: Vec<(&String, &i32)> = scores.iter().collect();
+    entries.sort();
+    for (key, value) in entries.into_iter().take(config.max_items) {
+        r.line(format!("{}: {}", key, value));
     }
+}
 
-    // === 12. Strings ===
-    println!("\n--- Strings ---");
+/// Section 12: `&str` and `String`.
+fn strings_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Strings");
     // &str - string slice (reference to UTF-8 encoded string data)
     let s1: &str = "Hello";
     // String - owned, growable string (heap allocated)
     let mut s2: String = String::from("World");
     s2.push_str("!"); // Append a string slice
     s2.push(' '); // Append a char
-    println!("String slice: {}, Owned String: {}", s1, s2);
+    r.line(format!("String slice: {}, Owned String: {}", s1, s2));
     let s3 = s1.to_string() + " " + &s2; // Concatenation (takes ownership of s1's String conversion)
-    println!("Concatenated: {}", s3);
+    r.line(format!("Concatenated: {}", s3));
     let s4 = format!("{}-{}", s1, s2); // format! macro (doesn't take ownership)
-    println!("Formatted: {}", s4);
+    r.line(format!("Formatted: {}", s4));
     // Slicing strings (be careful with UTF-8 boundaries)
     let hello = &s4[0..5]; // "Hello"
-    println!("Slice of s4: {}", hello);
+    r.line(format!("Slice of s4: {}", hello));
+}
 
-    // === 13. Modules ===
-    println!("\n--- Modules ---");
-    my_module::public_function();
-    // my_module::private_function(); // Error: private_function is private
-    my_module::nested::nested_function();
+/// Section 13: visibility and nested modules.
+fn modules_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Modules");
+    my_module::public_function(r);
+    // my_module::private_function(r); // Error: private_function is private
+    my_module::nested::nested_function(r);
+    // Dispatching by name through the module's registry
+    r.line(format!("my_module offers: {:?}", my_module::registry()));
+    for name in ["nested::nested_function", "private_function"] {
+        if let Err(e) = my_module::call(name, r) {
+            r.line(format!("Error: {}", e));
+        }
+    }
     // Use statement example (see top of file) - HashMap is used directly
+}
 
-    // === 14. Macros ===
-    println!("\n--- Macros ---");
+/// Section 14: declarative macros.
+fn macros_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Macros");
     // We've been using println!, vec!, format!
     // Simple custom declarative macro:
     macro_rules! my_macro {
         () => {
-            println!("My macro was called!");
+            r.line("My macro was called!");
         };
         ($x:expr) => {
-            println!("My macro received expression: {}", $x);
+            r.line(format!("My macro received expression: {}", $x));
         };
     }
     my_macro!();
     my_macro!(1 + 2);
+}
 
-    // === 15. Lifetimes ===
-    println!("\n--- Lifetimes ---");
+/// Section 15: lifetime annotations and elision.
+fn lifetimes_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Lifetimes");
     let string1 = String::from("abcd");
     // let result: &str; // 'result' is no longer assigned to in a way that outlives the block below
 
@@ -252,7 +895,7 @@ fn main() {
 
         // Call 'longest' and use the result *within this scope* where both inputs are valid.
         let inner_result = longest(string1.as_str(), string2.as_str());
-        println!("The longest string inside the inner scope is: {}", inner_result);
+        r.line(format!("The longest string inside the inner scope is: {}", inner_result));
 
         // We CANNOT assign inner_result to an outer variable 'result' here,
         // because inner_result's lifetime is tied to 'string2', which ends at the '}'.
@@ -265,7 +908,7 @@ fn main() {
 
     // We can separately show that a reference to string1 *can* live long:
     let long_lived_ref = string1.as_str();
-    println!("string1 reference still valid here: {}", long_lived_ref);
+    r.line(format!("string1 reference still valid here: {}", long_lived_ref));
 
 
     let _string3 = String::from("Short");
@@ -277,24 +920,28 @@ fn main() {
     //     let result_outer = longest(string4.as_str(), _string3.as_str()); // Compiler Error: `string4` does not live long enough
     // }
     // println!("{}", result_outer);
+}
 
-
-    // === 16. Closures ===
-    println!("\n--- Closures ---");
+/// Section 16: closures and what they capture.
+fn closures_section(r: &mut SectionReporter, showcase: &mut Showcase) {
+    let numbers = &showcase.data.numbers;
+    r.section("Closures");
     let doubler = |x: i32| -> i32 { x * 2 };
-    println!("Doubler closure: 5 * 2 = {}", doubler(5));
+    r.line(format!("Doubler closure: 5 * 2 = {}", doubler(5)));
 
     // Closures can capture their environment
     let factor = 10;
     let multiplier = |x| x * factor; // Captures `factor` by reference (Fn trait)
-    println!("Multiplier closure: 6 * {} = {}", factor, multiplier(6));
+    r.line(format!("Multiplier closure: 6 * {} = {}", factor, multiplier(6)));
 
     // Example using a closure with iterator adapter
     let doubled_numbers: Vec<_> = numbers.iter().map(|&x| x * 2).collect();
-    println!("Doubled numbers using map and closure: {:?}", doubled_numbers);
-
+    r.line(format!("Doubled numbers using map and closure: {:?}", doubled_numbers));
+}
 
-    // === 17. Concurrency ===
+/// Section 17: threads, channels, and shared state.
+fn concurrency_section(showcase: &mut Showcase) {
+    let numbers = &showcase.data.numbers;
     println!("\n--- Concurrency (Basic Threads) ---");
     let handle = thread::spawn(|| {
         for i in 1..=3 {
@@ -309,376 +956,9078 @@ fn main() {
         thread::sleep(Duration::from_millis(1));
     }
 
-    handle.join().unwrap(); // Wait for the spawned thread to finish
-    println!("Spawned thread finished.");
-
+    // Wait for the spawned thread to finish, but don't hang forever if it never does
+    match join_timeout(handle, Duration::from_secs(1)) {
+        Ok(()) => println!("Spawned thread finished."),
+        Err(e) => println!("Error: {}", e),
+    }
 
-    println!("\n--- End of Showcase ---");
-} // End of main function
+    // Deadlines: give up waiting on slow work instead of blocking
+    match run_with_timeout(|| factorial_iter(20).unwrap_or(0), Duration::from_millis(200)) {
+        Ok(v) => println!("Fast computation finished in time: 20! = {}", v),
+        Err(e) => println!("Fast computation {}", e),
+    }
+    let slow = run_with_timeout_cancellable(
+        |cancelled| {
+            let mut ticks = 0u64;
+            while !cancelled.load(AtomicOrdering::Relaxed) && ticks < 1_000 {
+                thread::sleep(Duration::from_millis(1)); // Stand-in for a long computation
+                ticks += 1;
+            }
+            ticks
+        },
+        Duration::from_millis(20),
+    );
+    match slow {
+        Ok(ticks) => println!("Slow computation finished after {} ticks", ticks),
+        Err(e) => println!("Slow computation {} (worker told to stop)", e),
+    }
 
-// === Function Definitions ===
+    // Data parallelism: scoped threads may borrow `numbers` because they are joined before it is dropped
+    let squares = par_map(numbers, 3, |&n| n * n);
+    println!("Squares computed on 3 threads: {:?}", squares);
+    let cubes = par_map(numbers, 0, |&n| n * n * n);
+    println!("Cubes computed with threads = 0 (auto: {} here): {:?}", default_thread_count(), cubes);
 
-/// Adds two i32 numbers.
-fn add_numbers(x: i32, y: i32) -> i32 {
-    x + y // Implicit return (no semicolon)
+    // Shared mutable state: single-threaded (Rc<RefCell>) vs multi-threaded (Arc<Mutex>)
+    shared_cart_demo();
 }
 
-/// Calculates factorial recursively.
-fn factorial(n: u64) -> u64 {
-    if n == 0 {
-        1
-    } else {
-        n * factorial(n - 1)
+/// Section 18: breadth-first search over a graph.
+fn graphs_section(r: &mut SectionReporter, showcase: &mut Showcase) {
+    let user1 = &showcase.data.user1;
+    let tweet = &showcase.data.tweet;
+    let article = &showcase.data.article;
+    r.section("Graphs (BFS)");
+    let mut friends = graph::Graph::new();
+    friends.add_edge(user1.username.as_str(), tweet.username.as_str());
+    friends.add_edge(tweet.username.as_str(), "ferris");
+    friends.add_edge("ferris", article.author.as_str());
+    friends.add_edge("alice", "bob");
+    friends.add_node("hermit");
+    match friends.bfs_shortest_path(&"john_doe", &"Iceburgh") {
+        Some(path) => r.line(format!("Shortest path john_doe -> Iceburgh: {}", path.join(" -> "))),
+        None => r.line("john_doe and Iceburgh are not connected."),
     }
+    r.line(format!("Path john_doe -> alice: {:?}", friends.bfs_shortest_path(&"john_doe", &"alice")));
+    r.line(format!("Neighbors of hermit: {:?}", friends.neighbors(&"hermit")));
+    r.line(format!("Connected components: {}", friends.connected_components().len()));
 }
 
-/// Demonstrates basic control flow.
-fn control_flow_example(number: i32) {
-    if number % 4 == 0 {
-        println!("{} is divisible by 4", number);
-    } else if number % 3 == 0 {
-        println!("{} is divisible by 3", number);
-    } else if number % 2 == 0 {
-        println!("{} is divisible by 2", number);
-    } else {
-        println!("{} is not divisible by 4, 3, or 2", number);
+/// Section 19: Roman numerals and other bases.
+fn numerals_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Numeral Conversions");
+    let numeral_rows: Vec<Vec<String>> = [1u32, 4, 9, 14, 2024, 3999]
+        .iter()
+        .map(|&n| {
+            let base = |b| numerals::to_base(u64::from(n), b).unwrap_or_default();
+            vec![n.to_string(), numerals::to_roman(n).unwrap_or_default(), base(2), base(16), base(36)]
+        })
+        .collect();
+    r.line(format_table(&["n", "roman", "binary", "hex", "b36"], &numeral_rows));
+    r.line(format!("from_roman(\"MCMXCIV\") = {:?}", numerals::from_roman("MCMXCIV")));
+    match numerals::from_roman("IIII") {
+        Ok(n) => r.line(format!("Parsed IIII as {}", n)),
+        Err(e) => r.line(format!("Error: {}", e)),
+    }
+    if let Err(e) = numerals::to_base(255, 37) {
+        r.line(format!("Error: base {}", e));
     }
+}
 
-    // `if` is an expression
-    let condition = true;
-    let value = if condition { 5 } else { 6 };
-    println!("The value from if expression is: {}", value);
+/// Section 20: operator overloading with units.
+fn measurements_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Measurements");
+    let board_length = Millimeters(500) + Meters(1);
+    r.line(format!("500 mm + {} = {}", Meters(1), board_length));
+    r.line(format!("{} + {} = {}", Millimeters(250), Millimeters(40), Millimeters(250) + Millimeters(40)));
+    let parts = [Millimeters(120), board_length, Millimeters(80)];
+    r.line(format!("Total length of {} parts: {}", parts.len(), total_length(&parts)));
+    let progress = Percent::new(42.0);
+    r.line(format!("{} + {} = {} (clamped)", progress, Percent::new(70.0), progress + Percent::new(70.0)));
+    r.line(format!("{} - {} = {} (clamped)", progress, Percent::new(50.0), progress - Percent::new(50.0)));
+    let filled = (progress.as_fraction() * 20.0).round() as usize;
+    r.line(format!("[{}{}] {}", "#".repeat(filled), ".".repeat(20 - filled), progress));
 }
 
-/// Demonstrates different loop types.
-fn loop_examples() {
-    // Infinite loop with break
-    let mut counter = 0;
-    let result = loop {
-        counter += 1;
-        if counter == 10 {
-            break counter * 2; // Return a value from the loop
-        }
-    };
-    println!("Loop result: {}", result);
+/// Section 21: number theory with iterators.
+fn number_theory_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Number Theory");
+    let fibs: Vec<u128> = numbers::fibonacci().take(15).collect();
+    r.line(format!("First 15 Fibonacci numbers: {:?}", fibs));
+    r.line(format!("Fibonacci numbers that fit in u128: {}", numbers::fibonacci().count()));
+    let small_primes: Vec<u64> = numbers::primes().take_while(|&p| p < 50).collect();
+    r.line(format!("Primes below 50: {:?}", small_primes));
+    r.line(format!("Is 561 prime? {}", numbers::is_prime(561)));
+    r.line(format!("Collatz steps for 27: {:?}", numbers::collatz_steps(27)));
+}
 
-    // While loop
-    let mut number = 3;
-    while number != 0 {
-        println!("{}!", number);
-        number -= 1;
+/// Section 22: grouping records with `HashMap`.
+fn grouping_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Grouping");
+    let users = vec![
+        User { username: String::from("alice"), email: String::from("alice@example.com"), sign_in_count: 3, active: true },
+        User { username: String::from("bob"), email: String::from("bob@example.com"), sign_in_count: 0, active: false },
+        User { username: String::from("carol"), email: String::from("carol@penguins.org"), sign_in_count: 7, active: true },
+        User { username: String::from("dave"), email: String::from("dave.example.com"), sign_in_count: 1, active: false },
+    ];
+    let mut by_domain: Vec<(String, Vec<&User>)> = group_by_domain(&users).into_iter().collect();
+    by_domain.sort_by(|a, b| a.0.cmp(&b.0)); // HashMap order is unspecified
+    for (domain, members) in &by_domain {
+        let names: Vec<&str> = members.iter().map(|u| u.username.as_str()).collect();
+        r.line(format!("@{}: {}", domain, names.join(", ")));
     }
-    println!("WHILE loop finished!");
-
-    // For loop (iterating over a range)
-    for i in 1..4 { // 1, 2, 3 (exclusive end)
-        println!("For loop (1..4): {}", i);
+    let by_status = group_by_key(users, |u| if u.active { "active" } else { "inactive" });
+    for status in ["active", "inactive"] {
+        let names: Vec<&str> = by_status.get(status).into_iter().flatten().map(|u| u.username.as_str()).collect();
+        r.line(format!("{} users: {}", status, names.join(", ")));
     }
-     for i in 1..=4 { // 1, 2, 3, 4 (inclusive end)
-        println!("For loop (1..=4): {}", i);
+    let shapes = [Rectangle::square(4), Rectangle { width: 2, height: 5 }, Rectangle::square(1)];
+    let square_counts = count_by_key(&shapes, Rectangle::is_square);
+    r.line(format!("Squares: {}, non-squares: {}", square_counts[&true], square_counts[&false]));
+    let by_shape = group_by_key(shapes.to_vec(), Rectangle::is_square);
+    r.line(format!("Square rectangles: {:?}", by_shape.get(&true)));
+}
+
+/// Section 23: parsing a stream line by line.
+fn streaming_parsing_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Streaming Parsing");
+    let sample = "# name,score,tags\nalice,90,rust;go\n\nbob,eighty,python\ncarol,75\n";
+    for result in records::parse_records(std::io::Cursor::new(sample)) {
+        match result {
+            Ok(record) => {
+                r.line(format!("Record: {} scored {} with tags {:?}", record.name, record.score, record.tags))
+            }
+            Err(e) => r.line(format!("Error: {}", e)),
+        }
     }
+}
 
-    // For loop (iterating over collection)
-    let a = [10, 20, 30, 40, 50];
-    for element in a.iter() { // Using iter() to borrow elements
-        println!("Array element: {}", element);
+/// Section 24: `Cell` vs `RefCell` vs `Mutex`.
+fn interior_mutability_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Interior Mutability");
+    use counters::Counter; // Bring the trait into scope to call its methods
+    let cell_counter = counters::CellCounter::new();
+    let refcell_counter = counters::RefCellCounter::new();
+    let mutex_counter = counters::MutexCounter::new();
+    let all_counters: [(&str, &dyn Counter); 3] =
+        [("Cell", &cell_counter), ("RefCell", &refcell_counter), ("Mutex", &mutex_counter)];
+    for (name, counter) in all_counters {
+        for _ in 0..5 {
+            counter.increment(); // Mutation through a shared `&` reference
+        }
+        r.line(format!("{} counter: {}", name, counter.value()));
+    }
+    r.line(format!("RefCell history: {:?}", refcell_counter.history()));
+    // Holding one mutable borrow and asking for another: `borrow_mut` would panic, `try_borrow_mut` reports it
+    match refcell_counter.try_double_borrow() {
+        Ok(()) => r.line("Second mutable borrow succeeded?!"),
+        Err(e) => r.line(format!("Second mutable borrow refused: {}", e)),
     }
 }
 
-// === Ownership & Borrowing Functions ===
+/// Section 25: format specifiers and alignment.
+fn formatting_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Formatting");
+    formatting_demo(r);
+}
 
-/// Demonstrates ownership transfer.
-fn ownership_demo() {
-    let s1 = String::from("hello"); // s1 owns the String data
-    takes_ownership(s1); // s1's ownership is moved into the function
-    // println!("{}", s1); // Error! s1 is no longer valid here
+/// Section 26: a circuit breaker around fallible calls.
+fn circuit_breaker_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Circuit Breaker");
+    let mut breaker = CircuitBreaker::new(2, Duration::from_millis(20));
+    for denominator in [0.0, 0.0, 5.0] {
+        match breaker.call(|| divide(10.0, denominator)) {
+            Ok(v) => r.line(format!("10 / {} = {}", denominator, v)),
+            Err(e) => r.line(format!("10 / {} failed: {} (state: {})", denominator, e, breaker.state())),
+        }
+    }
+    thread::sleep(Duration::from_millis(25)); // Wait out the cooldown
+    match breaker.call(|| divide(10.0, 5.0)) {
+        Ok(v) => r.line(format!("After cooldown: 10 / 5 = {} (state: {})", v, breaker.state())),
+        Err(e) => r.line(format!("After cooldown: {}", e)),
+    }
+}
 
-    let x = 5; // x is i32, which implements the Copy trait
-    makes_copy(x); // A copy of x is passed to the function
-    println!("x is still valid: {}", x); // x is still valid here
+/// Section 27: observers held through `Weak` references.
+fn observers_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Observers");
+    let mut temperature = Subject::new();
+    // Observers must be `'static`, so they cannot borrow `r`; they note what they saw in a shared list
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let logger_seen = Rc::clone(&seen);
+    let logger: Rc<Observer<f64>> =
+        Rc::new(move |t: &f64| logger_seen.borrow_mut().push(format!("Logger saw {} degrees", t)));
+    let alarm_seen = Rc::clone(&seen);
+    let alarm: Rc<Observer<f64>> =
+        Rc::new(move |t: &f64| alarm_seen.borrow_mut().push(format!("Alarm checked {} degrees", t)));
+    temperature.subscribe(&logger);
+    temperature.subscribe(&alarm);
+    r.line(format!("Observers subscribed: {}", temperature.observer_count()));
+    drop(alarm); // The subject only held a Weak, so this really frees the observer
+    let fired = temperature.notify(&21.5);
+    seen.borrow().iter().for_each(|line| r.line(line));
+    r.line(format!("Observers fired: {}, remaining: {}", fired, temperature.observer_count()));
 }
 
-fn takes_ownership(some_string: String) {
-    println!("Inside takes_ownership: {}", some_string);
-} // `some_string` goes out of scope, `drop` is called. Memory is freed.
+/// Section 28: bit manipulation and bitflags.
+fn bitflags_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Bitflags");
+    let mut perms = Permissions::READ | Permissions::WRITE;
+    r.line(format!("READ | WRITE = {} ({:#05b})", perms, perms.0));
+    r.line(format!(
+        "Contains WRITE? {}. Contains EXECUTE? {}",
+        perms.contains(Permissions::WRITE),
+        perms.contains(Permissions::EXECUTE)
+    ));
+    perms |= Permissions::EXECUTE;
+    r.line(format!("After adding EXECUTE: {}", perms));
+    perms.remove(Permissions::EXECUTE);
+    r.line(format!(
+        "After removing EXECUTE: {}, masked with READ: {}, inverted: {}",
+        perms,
+        perms & Permissions::READ,
+        !perms
+    ));
+    r.line(format!("Round trip through \"{}\": {:?}", perms, Permissions::parse(&perms.to_string())));
+    for bad in [Permissions::parse("rwz"), Permissions::from_bits(0b1000_0001)] {
+        if let Err(e) = bad {
+            r.line(format!("Error: {}", e));
+        }
+    }
+}
 
-fn makes_copy(some_integer: i32) {
-    println!("Inside makes_copy: {}", some_integer);
-} // `some_integer` goes out of scope. Nothing special happens for Copy types.
+/// Section 29: memoization with a sparse table.
+fn memoization_section(r: &mut SectionReporter, showcase: &mut Showcase) {
+    let numbers = &showcase.data.numbers;
+    r.section("Memoization");
+    let mut gcd_table: SparseTable<u64, u64, u64> = SparseTable::default();
+    for _pass in 0..2 {
+        for &a in numbers {
+            for &b in numbers {
+                let (a, b) = (a as u64 * 12, b as u64 * 18);
+                gcd_table.get_or_compute(a, b, |&a, &b| {
+                    thread::sleep(Duration::from_micros(100)); // Pretend this is expensive
+                    gcd(a, b)
+                });
+            }
+        }
+    }
+    let lookups = gcd_table.hits() + gcd_table.misses();
+    r.line(format!(
+        "{} distinct pairs cached; {} hits / {} misses ({:.0}% hit rate)",
+        gcd_table.len(),
+        gcd_table.hits(),
+        gcd_table.misses(),
+        100.0 * gcd_table.hits() as f64 / lookups as f64
+    ));
+    r.line(format!("gcd(48, 90) from the table: {}", gcd_table.get_or_compute(48, 90, |&a, &b| gcd(a, b))));
+}
 
-/// Demonstrates borrowing (references).
-fn borrowing_demo() {
-    let s1 = String::from("world");
+/// Section 30: trait object lifetimes: `+ 'a` vs `+ 'static`.
+fn trait_object_lifetimes_section(r: &mut SectionReporter, showcase: &mut Showcase) {
+    let rect = &showcase.data.rect;
+    r.section("Trait Object Lifetimes");
+    let prefix = String::from("showcase rect");
+    let mut formatters = FormatterRegistry::new();
+    formatters.register(|r| format!("{}: {}", prefix, r)); // Borrows `prefix`
+    formatters.register(|r| format!("area {}, perimeter {}", r.area(), r.perimeter()));
+    for line in formatters.format_all(rect) {
+        r.line(line);
+    }
+    let owned = owned_registry("px");
+    r.line(format!("Owned registry: {:?}", owned.format_all(rect)));
+}
 
-    // Pass an immutable reference (&) - borrows s1
-    let len = calculate_length(&s1);
-    println!("The length of '{}' is {}.", s1, len); // s1 is still valid
+/// Section 31: a line-oriented digest format.
+fn feed_digest_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Feed Digest");
+    let mut digest_feed = Feed::new();
+    digest_feed.push(Tweet::new("ferris", "tabs\tand\\backslashes survive", false, false).unwrap());
+    digest_feed.push(Rectangle::square(3));
+    let digest = digest_feed.to_digest();
+    digest.lines().for_each(|line| r.line(line));
+    match Feed::from_digest(&digest) {
+        Ok(pairs) => r.line(format!("Parsed back: {:?}", pairs)),
+        Err(e) => r.line(format!("Error: {}", e)),
+    }
+    r.line(format!("Malformed digest: {:?}", Feed::from_digest("no tab here").map_err(|e| e.to_string())));
+    // Cloning a feed deep-copies every boxed item, so the copy can change independently
+    let mut extended = digest_feed.clone();
+    extended.push(NewsArticleBuilder::new().headline("Feeds can be cloned").author("Iceburgh").build());
+    r.line(format!(
+        "Original feed: {} items, clone: {:?}",
+        digest_feed.summarize_all().len(),
+        extended.summarize_all()
+    ));
+}
 
-    let mut s2 = String::from("mutable");
-    // Pass a mutable reference (&mut) - mutably borrows s2
-    change_string(&mut s2);
-    println!("Changed string: {}", s2); // s2 has been modified
+/// Section 32: validating user input.
+fn input_validation_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Input Validation");
+    for input in [" 42 ", "", "forty", "-3", "151", "0x1F"] {
+        match validation::parse_age(input) {
+            Ok(age) => r.line(format!("Age {:?}: {}", input, age)),
+            Err(e) => r.line(format!("Age {:?}: {}", input, e)),
+        }
+    }
+    for input in ["255,128,0", "#ff8000", "1,2", "1,two,3", "0,256,0", "#FFF", "  "] {
+        match validation::parse_rgb(input) {
+            Ok(color) => r.line(format!("Color {:?}: {:?}", input, color)),
+            Err(e) => r.line(format!("Color {:?}: {}", input, e)),
+        }
+    }
 }
 
-fn calculate_length(s: &String) -> usize { // `s` is a reference to a String
-    s.len()
-} // `s` goes out of scope, but does *not* drop what it refers to.
-
-fn change_string(some_string: &mut String) { // takes a mutable reference
-    some_string.push_str(" changed");
+/// Section 33: an iterator chain vs threads and channels.
+fn log_pipeline_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Log Pipeline");
+    let sample_log = "INFO server started\nDEBUG cache warmed\nWARN disk at 85%\n\
+        garbage line\nERROR request failed\nINFO request served\nTRACE too chatty\nWARN\nINFO shutting down";
+    let log_lines: Vec<&str> = sample_log.lines().collect();
+    let iter_stats = pipeline::process_iter(&log_lines);
+    let threaded_stats = pipeline::process_threaded(log_lines.iter().map(|l| l.to_string()).collect());
+    let mut counts: Vec<_> = iter_stats.counts.iter().collect();
+    counts.sort();
+    r.line(format!("Counts at {:?} and above: {:?}", pipeline::MIN_LEVEL, counts));
+    r.line(format!("Skipped {} malformed lines", iter_stats.skipped));
+    r.line(format!("Threaded pipeline agrees: {}", iter_stats == threaded_stats));
 }
 
-// === Struct Definitions ===
-
-/// Represents a user account.
-struct User {
-    username: String,
-    email: String,
-    sign_in_count: u64,
-    active: bool,
+/// Section 34: dynamic dispatch vs enum dispatch.
+fn dispatch_comparison_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Dispatch Comparison");
+    let sample_tweet = |i: usize| {
+        let username = format!("user{}", i % 10);
+        TweetBuilder::new().username(&username).content("dispatch benchmark").build().expect("short tweet")
+    };
+    let sample_article = |i: usize| {
+        NewsArticleBuilder::new()
+            .headline("Benchmarks")
+            .location("Anywhere")
+            .author(&format!("writer{}", i % 10))
+            .build()
+    };
+    const FEED_SIZE: usize = 100_000;
+    let dyn_feed: Vec<Box<dyn Summary>> = (0..FEED_SIZE)
+        .map(|i| if i % 2 == 0 { Box::new(sample_tweet(i)) as Box<dyn Summary> } else { Box::new(sample_article(i)) })
+        .collect();
+    let enum_feed: Vec<SummaryItem> = (0..FEED_SIZE)
+        .map(|i| if i % 2 == 0 { sample_tweet(i).into() } else { sample_article(i).into() })
+        .collect();
+    let start = Instant::now();
+    let dyn_summaries = summarize_dyn_feed(&dyn_feed);
+    let dyn_elapsed = start.elapsed();
+    let start = Instant::now();
+    let enum_summaries = summarize_enum_feed(&enum_feed);
+    let enum_elapsed = start.elapsed();
+    // String formatting dominates here, so expect similar numbers; the gap grows as per-call work shrinks
+    r.line(format!(
+        "Summarized {} items: Box<dyn Summary> took {:?} and enum took {:?}",
+        humanize::with_thousands(FEED_SIZE as i64),
+        dyn_elapsed,
+        enum_elapsed
+    ));
+    // Inline storage: boxes cost a pointer pair each plus their heap allocations; the enum is one flat buffer
+    r.line(format!(
+        "Feed storage: Box<dyn Summary> {} (plus the boxes), enum {}",
+        humanize::human_bytes((FEED_SIZE * mem::size_of::<Box<dyn Summary>>()) as u64),
+        humanize::human_bytes((FEED_SIZE * mem::size_of::<SummaryItem>()) as u64)
+    ));
+    r.line(format!("Same summaries either way: {}", dyn_summaries == enum_summaries));
 }
 
-/// A tuple struct for RGB color.
-struct Color(u8, u8, u8);
+/// Section 35: custom hash keys.
+fn custom_hash_keys_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Custom Hash Keys");
+    let mut world = WorldMap::new();
+    world.place(GridCoord { x: 0, y: 0 }, Rectangle::square(1));
+    world.place(GridCoord { x: 1, y: 0 }, Rectangle { width: 2, height: 1 });
+    world.place(GridCoord { x: 0, y: -1 }, Rectangle::square(3));
+    let replaced = world.place(GridCoord { x: 1, y: 0 }, Rectangle::square(4));
+    r.line(format!("Placing at (1, 0) replaced {:?}", replaced));
+    r.line(format!("Occupied neighbors of (0, 0): {:?}", world.occupied_neighbors(GridCoord { x: 0, y: 0 })));
+    let names = color_names();
+    for color in [Color(255, 0, 0), Color(12, 34, 56)] {
+        r.line(format!("{:?} is called {}", color, names.get(&color).unwrap_or(&"(unnamed)")));
+    }
+}
 
-/// A unit-like struct (no fields).
-struct AlwaysEqual;
+/// Section 36: departments with `HashMap`, `Vec`, and sorting.
+fn company_directory_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Company Directory");
+    let script = [
+        "Add Sally to Engineering",
+        "Add Amir to Sales",
+        "Add Bob to Engineering",
+        "Add Sally to Engineering",
+        "Remove Zed from Sales",
+        "Hire Carol",
+        "List Engineering",
+        "Remove Amir from Sales",
+        "Add Dana to Sales",
+        "List all",
+    ];
+    let mut company = Company::new();
+    for line in script {
+        match parse_command(line) {
+            Some(command) => r.line(company.execute(command)),
+            None => r.line(format!("Unrecognized command: {:?}", line)),
+        }
+    }
+}
 
-// === Enum Definition ===
+/// Section 37: retaining, draining, and splitting vectors.
+fn vec_manipulation_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Vec Manipulation");
+    let member = |name: &str, domain: &str, active: bool| User {
+        username: name.to_string(),
+        email: format!("{}@{}", name, domain),
+        sign_in_count: 0,
+        active,
+    };
+    let names = |users: &[User]| users.iter().map(|u| u.username.clone()).collect::<Vec<_>>();
+    let mut roster = vec![
+        member("ann", "a.com", true),
+        member("ben", "a.com", false),
+        member("cid", "b.com", true),
+        member("dot", "b.com", false),
+    ];
+    let inactive = deactivate_and_collect(&mut roster);
+    r.line(format!("drain + partition: active {:?}, inactive {:?}", names(&roster), names(&inactive)));
+    roster.extend(inactive);
+    r.line(format!("retain dropped {}, leaving {:?}", retain_active(&mut roster), names(&roster)));
+    let mut queue = vec![10, 20, 30, 40, 50];
+    r.line(format!("Took {:?} from the front, {:?} left", take_first_n(&mut queue, 2), queue));
+    let (shifted, swapped) = compare_removals(&[1, 2, 3, 4, 5], 1);
+    r.line(format!("Removing index 1: remove {:?}, swap_remove {:?}", shifted, swapped));
+    let mut head = vec![1, 2, 3, 4, 5];
+    let tail = split_tail(&mut head, 3);
+    r.line(format!("split_off(3): head {:?}, tail {:?}", head, tail));
+    let mut log_ins = vec![
+        member("eve", "a.com", true),
+        member("fay", "a.com", true),
+        member("gus", "b.com", true),
+        member("hal", "a.com", true),
+    ];
+    collapse_domain_runs(&mut log_ins);
+    r.line(format!("First of each domain run: {:?}", names(&log_ins)));
+}
 
-/// Represents different types of messages.
-enum Message {
-    Quit,                       // No data associated
-    Move { x: i32, y: i32 },    // Anonymous struct variant
-    Write(String),              // Includes a String
-    ChangeColor(u8, u8, u8), // Includes three u8 values
+/// Section 38: string interning.
+fn string_interning_section(r: &mut SectionReporter, showcase: &mut Showcase) {
+    let user1 = &showcase.data.user1;
+    let tweet = &showcase.data.tweet;
+    let article = &showcase.data.article;
+    r.section("String Interning");
+    let mut interner = Interner::new();
+    let mut names: Vec<&str> = vec![&user1.username, &tweet.username, &article.author, "Blue"];
+    let mut teams: Vec<&str> = showcase.data.scores.keys().map(String::as_str).collect();
+    teams.sort(); // HashMap order varies between runs
+    names.extend(teams);
+    let symbols: Vec<Symbol> = names.iter().map(|name| interner.intern(name)).collect();
+    r.line(format!("Interned {} names into {} symbols", names.len(), interner.len()));
+    let again = [interner.intern("Blue"), interner.intern(&user1.username)];
+    r.line(format!("Interning known names again: {:?}, table still has {} entries", again, interner.len()));
+    r.line(format!("{:?} resolves to {:?}", symbols[0], interner.resolve(symbols[0])));
 }
 
-/// Processes a Message enum.
-fn process_message(msg: Message) {
-    match msg {
-        Message::Quit => println!("Message: Quit"),
-        Message::Move { x, y } => println!("Message: Move to x={}, y={}", x, y),
-        Message::Write(text) => println!("Message: Write - {}", text),
-        Message::ChangeColor(r, g, b) => println!("Message: ChangeColor to ({}, {}, {})", r, g, b),
+/// Section 39: `mem::replace`, `take`, and `swap`.
+fn std_mem_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("std::mem");
+    let mut slot = Some(String::from("first"));
+    let taken = rotate_option(&mut slot);
+    r.line(format!("Took {:?}, slot is now {:?}", taken, slot));
+    r.line(format!("Taking from the empty slot gives {:?}", rotate_option(&mut slot)));
+    let mut light = TrafficLight::Red { intersection: String::from("5th & Main") };
+    for _ in 0..3 {
+        advance_state(&mut light);
+        r.line(format!("Light: {:?}", light));
     }
+    let mut ends = vec![1, 2, 3, 4];
+    swap_ends(&mut ends);
+    r.line(format!("Ends swapped: {:?}", ends));
 }
 
-// === Methods (`impl`) ===
+/// Section 40: range-checked newtypes.
+fn range_checked_newtypes_section(r: &mut SectionReporter, showcase: &mut Showcase) {
+    let numbers = &showcase.data.numbers;
+    r.section("Percentages and Probabilities");
+    let evens = numbers.iter().filter(|&&n| n % 2 == 0).count();
+    let even_share = Probability::new(evens as f64 / numbers.len() as f64).expect("a share is within 0..=1");
+    r.line(format!("{} of {:?} are even: {} or {}", evens, numbers, even_share.as_percentage(), even_share));
+    let seventy = Percentage::new(70).expect("70 is within 0..=100");
+    r.line(format!("{} + {} = {} (saturating)", HALF, seventy, HALF.saturating_add(seventy)));
+    r.line(format!("{} - {} = {} (saturating)", HALF, seventy, HALF.saturating_sub(seventy)));
+    r.line(format!("{} as a probability: {}", HALF, HALF.as_probability()));
+    r.line(format!("Certain + {} = {}", even_share, CERTAIN.saturating_add(even_share)));
+    match Percentage::new(101) {
+        Ok(p) => r.line(format!("Percentage: {}", p)),
+        Err(e) => r.line(format!("Error: {}", e)),
+    }
+    r.line(format!("Probability::new(NaN): {:?}", Probability::new(f64::NAN).map_err(|e| e.to_string())));
+}
 
-#[derive(Debug)] // Auto-implement Debug trait for printing
-struct Rectangle {
-    width: u32,
-    height: u32,
+/// Section 41: parallel map with a shared work queue.
+fn parallel_map_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Parallel Map");
+    let candidates: Vec<u64> = (1_000_000_000..1_000_002_000).collect();
+    let start = Instant::now();
+    let sequential: Vec<bool> = candidates.iter().map(|&n| numbers::is_prime(n)).collect();
+    let sequential_elapsed = start.elapsed();
+    let start = Instant::now();
+    let parallel = parallel_map(candidates.clone(), 0, numbers::is_prime);
+    let parallel_elapsed = start.elapsed();
+    r.line(format!(
+        "{} primes among {} candidates, same results: {}",
+        parallel.iter().filter(|&&p| p).count(),
+        candidates.len(),
+        sequential == parallel
+    ));
+    // Durations stand alone as words so `--verify` can mask them
+    r.line(format!("Sequential took {:?} and parallel took {:?}", sequential_elapsed, parallel_elapsed));
 }
 
-// Implementation block for Rectangle
-impl Rectangle {
-    /// Calculates the area of the rectangle.
-    fn area(&self) -> u32 { // `&self` is shorthand for `self: &Self` (immutable borrow)
-        self.width * self.height
+/// Section 42: ordered collections: `BTreeMap` and `BTreeSet`.
+fn leaderboard_section(r: &mut SectionReporter, showcase: &mut Showcase) {
+    r.section("Leaderboard");
+    let mut board = Leaderboard::new();
+    // The team scores, recorded in name order so ties rank the same on every run
+    let mut teams: Vec<(&String, &i32)> = showcase.data.scores.iter().collect();
+    teams.sort();
+    for (team, &score) in teams {
+        board.record(team, score.max(0) as u32); // Leaderboard scores are unsigned
     }
+    board.record("Green", 30);
+    board.record("Purple", 42);
+    board.record("Orange", 8);
+    r.line(format!("Top 3: {:?}", board.top_n(3)));
+    r.line(format!("Scoring 10..=40: {:?}", board.scores_between(10, 40)));
+    r.line(format!("Rank of Green: {:?}, of Pink: {:?}", board.rank_of("Green"), board.rank_of("Pink")));
+    let tags = ["rust", "async", "ownership", "rust", "traits", "async"];
+    let sorted_tags: BTreeSet<&str> = tags.iter().copied().collect();
+    let hashed_tags: HashSet<&str> = tags.iter().copied().collect();
+    r.line(format!("BTreeSet iterates sorted: {:?}", sorted_tags));
+    r.line(format!("HashSet has the same {} tags, in arbitrary order", hashed_tags.len()));
+}
 
-    /// Checks if this rectangle can hold another rectangle.
-    fn can_hold(&self, other: &Rectangle) -> bool {
-        self.width > other.width && self.height > other.height
+/// Section 43: `DoubleEndedIterator` and `ExactSizeIterator`.
+fn custom_iterators_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Custom Iterators");
+    let counter = RangeCounter::new(1, 6);
+    r.line(format!("Counter has {} items; reversed: {:?}", counter.len(), counter.clone().rev().collect::<Vec<_>>()));
+    let mut partly_used = counter.clone();
+    partly_used.next();
+    partly_used.next_back();
+    r.line(format!("After one from each end: {} left, {:?}", partly_used.len(), partly_used));
+    r.line(format!("Sum from both ends of 1..6: {:?}", sum_from_both_ends(counter)));
+}
+
+/// Section 44: string templates with placeholders.
+fn templates_section(r: &mut SectionReporter, showcase: &mut Showcase) {
+    let user1 = &showcase.data.user1;
+    r.section("Templates");
+    let vars: HashMap<&str, String> = HashMap::from([
+        ("name", user1.username.clone()),
+        ("email", user1.email.clone()),
+        ("count", user1.sign_in_count.to_string()),
+    ]);
+    let greeting = "Hi {name}! We'll write to {email}. You have signed in {count} time(s). {{not a placeholder}}";
+    match template::render(greeting, &vars) {
+        Ok(text) => r.line(text),
+        Err(e) => r.line(format!("Template error: {}", e)),
+    }
+    for broken in ["Bye {nmae}", "Oops {name", "Stray } brace"] {
+        match template::render(broken, &vars) {
+            Ok(text) => r.line(text),
+            Err(e) => r.line(format!("Template error: {}", e)),
+        }
     }
+    let (text, missing) = template::render_lenient("Dear {title} {name}, re: {subject}", &vars);
+    r.line(format!("Lenient: {:?}, missing {:?}", text, missing));
+}
 
-    /// Associated function (like a static method) to create a square.
-    fn square(size: u32) -> Rectangle { // No `self` parameter
-        Rectangle { width: size, height: size }
+/// Section 45: sorting by composite keys.
+fn sorting_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Sorting");
+    let signup = |name: &str, sign_in_count: u64, active: bool| User {
+        username: name.to_string(),
+        email: format!("{}@example.com", name),
+        sign_in_count,
+        active,
+    };
+    let mut members = vec![
+        signup("zoe", 4, true),
+        signup("adam", 9, false),
+        signup("mia", 9, true),
+        signup("leo", 4, true),
+        signup("ivy", 0, false),
+    ];
+    let top_two: Vec<&str> = top_users(&members, 2).iter().map(|u| u.username.as_str()).collect();
+    r.line(format!("Top two without a full sort: {:?}", top_two));
+    sort_users(&mut members);
+    for u in &members {
+        r.line(format!("{:<5} active: {:<5} sign-ins: {}", u.username, u.active, u.sign_in_count));
     }
+    let mut plots = [Rectangle::square(2), Rectangle { width: 3, height: 5 }, Rectangle { width: 1, height: 9 }];
+    sort_rects_by_area_desc(&mut plots);
+    r.line(format!("Rectangles by area, largest first: {:?}", plots.iter().map(Rectangle::area).collect::<Vec<_>>()));
 }
 
-// We can implement traits on our types
-impl fmt::Display for Rectangle {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Rectangle({}x{})", self.width, self.height)
+/// Section 46: shelf packing rectangles into a bin.
+fn shelf_packing_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Shelf Packing");
+    let sheet = Rectangle { width: 10, height: 6 };
+    let sprites = [
+        Rectangle { width: 4, height: 3 },
+        Rectangle { width: 4, height: 2 },
+        Rectangle { width: 3, height: 3 },
+        Rectangle { width: 11, height: 1 },
+        Rectangle::square(5),
+        Rectangle { width: 2, height: 2 },
+    ];
+    for (sprite, place) in sprites.iter().zip(pack(sheet.clone(), &sprites)) {
+        match place {
+            Some(p) => r.line(format!("{} at ({}, {})", sprite, p.x, p.y)),
+            None => r.line(format!("{} does not fit in {}", sprite, sheet)),
+        }
     }
 }
 
+/// Section 47: object pools with RAII guards.
+fn object_pools_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Object Pools");
+    let buffers = Pool::new(vec![String::with_capacity(64), String::with_capacity(64), String::with_capacity(64)]);
+    let mut first = buffers.checkout().unwrap();
+    let second = buffers.checkout().unwrap();
+    first.push_str("reused buffer"); // DerefMut reaches the String inside the guard
+    r.line(format!(
+        "Checked out 2 of 3, available: {}, first holds {:?} (capacity {})",
+        buffers.available(),
+        *first,
+        first.capacity()
+    ));
+    drop(second);
+    r.line(format!("Dropped one guard, available: {}", buffers.available()));
+    let _third = buffers.checkout().unwrap();
+    let _fourth = buffers.checkout().unwrap();
+    r.line(format!("Pool exhausted, checkout: {:?}", buffers.checkout().map(|b| b.len())));
+    r.line(format!(
+        "Waiting 10ms for a return: {:?}",
+        buffers.checkout_timeout(Duration::from_millis(10)).map(|b| b.len())
+    ));
+    thread::scope(|s| {
+        s.spawn(|| {
+            thread::sleep(Duration::from_millis(10));
+            drop(first); // Wakes the waiter below
+        });
+        let handed_back = buffers.checkout_timeout(Duration::from_secs(1));
+        r.line(format!("Waiting while another thread returns one: {:?}", handed_back.as_deref()));
+    });
+    let local = LocalPool::new(vec![1, 2]);
+    let a = local.checkout().unwrap();
+    r.line(format!("LocalPool: took {}, available {}", *a, local.available()));
+    drop(a);
+    r.line(format!("LocalPool after drop: available {}", local.available()));
+}
 
-// === Traits (Interfaces) ===
+/// Section 48: a two-stack text buffer.
+fn text_editing_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Text Editing");
+    let mut buffer = TextBuffer::new();
+    "helo".chars().for_each(|c| buffer.insert_char(c));
+    r.line(format!("Typed {:?}, cursor at {}", buffer.as_string(), buffer.cursor_position()));
+    buffer.move_left();
+    buffer.insert_char('l');
+    r.line(format!("Moved back and fixed it: {:?}, cursor at {}", buffer.as_string(), buffer.cursor_position()));
+    buffer.move_to(usize::MAX); // Clamps to the end
+    " wörld 🐧".chars().for_each(|c| buffer.insert_char(c));
+    buffer.delete_backward();
+    r.line(format!("Multibyte text, one Backspace: {:?}, cursor at {}", buffer.as_string(), buffer.cursor_position()));
+    buffer.move_to(0);
+    r.line(format!("Backspace at the start deletes {:?}", buffer.delete_backward()));
+}
 
-/// Defines summarizable behavior.
-pub trait Summary {
-    // Method signature (requires implementation)
-    fn summarize_author(&self) -> String;
+/// Section 49: a progress bar over worker threads.
+fn progress_bars_section(_: &mut Showcase) {
+    println!("\n--- Progress Bars ---");
+    let checksum = AtomicU64::new(0);
+    let elapsed = progress::run_with_progress(4_000, 4, |item| {
+        thread::sleep(Duration::from_micros(250)); // Pretend each item takes real work
+        checksum.fetch_add(item as u64, AtomicOrdering::Relaxed);
+    });
+    println!("Processed {} fake items in {:?}, checksum {}", humanize::with_thousands(4_000), elapsed, checksum.into_inner());
+}
 
-    // Method with default implementation
-    fn summarize(&self) -> String {
-        format!("(Read more from {}...)", self.summarize_author())
-    }
+/// Section 50: units in the type system with `PhantomData`.
+fn units_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Units with PhantomData");
+    use units::{Millimeters as Mm, Pixels, Rect};
+    let window: Rect<Pixels> = Rectangle { width: 800, height: 600 }.into();
+    let icon = Rect::<Pixels>::new(64, 64);
+    r.line(format!("Window {}x{} px holds a 64x64 px icon: {}", window.width, window.height, window.can_hold(&icon)));
+    let printed: Rect<Mm> = window.convert(300);
+    let back: Rect<Pixels> = printed.convert(300);
+    r.line(format!(
+        "At 300 dpi the window prints at {}x{} mm, which is {}x{} px again",
+        printed.width,
+        printed.height,
+        back.width,
+        back.height
+    ));
+    r.line(format!("PhantomData is free: Rect<Pixels> is {} bytes", mem::size_of::<Rect<Pixels>>()));
+    // Units are part of the type, so mixing them is caught at compile time:
+    // window.can_hold(&printed); // <-- COMPILER ERROR E0308: mismatched types, expected `&Rect<Pixels>`, found `&Rect<Millimeters>`
 }
 
-pub struct NewsArticle {
-    pub headline: String,
-    pub location: String,
-    pub author: String,
-    pub content: String,
+/// Section 51: unwind safety and `catch_unwind`.
+fn unwind_safety_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Unwind Safety");
+    // Silence the default "thread 'main' panicked" report while the demo's panics are expected.
+    // The hook is process-wide, so it is swapped here in the section rather than in the demo.
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    unwind_safety_demo(r);
+    panic::set_hook(default_hook);
 }
 
-impl Summary for NewsArticle {
-    fn summarize_author(&self) -> String {
-        format!("@{}", self.author)
-    }
-    // Uses the default summarize method
+/// Section 52: a scene graph in a generational arena.
+fn generational_arenas_section(r: &mut SectionReporter, _: &mut Showcase) {
+    r.section("Generational Arenas");
+    let mut scene = Arena::new();
+    let root = scene.insert(SceneNode::new("world"));
+    let player = add_child(&mut scene, root, "player").unwrap();
+    add_child(&mut scene, player, "sword");
+    let shield = add_child(&mut scene, player, "shield").unwrap();
+    add_child(&mut scene, root, "camera");
+    let print_scene = |r: &mut SectionReporter, scene: &Arena<SceneNode>| {
+        visit(scene, root, 0, &mut |node, depth| r.line(format!("{}{}", "  ".repeat(depth), node.name)));
+    };
+    print_scene(r, &scene);
+    let removed = remove_subtree(&mut scene, Some(root), player);
+    r.line(format!("Removed the player subtree: {} nodes, {} left", removed, scene.len()));
+    let enemy = add_child(&mut scene, root, "enemy").unwrap(); // Reuses a freed slot
+    r.line(format!("New node {:?} reuses the slot of stale {:?}", enemy, shield));
+    r.line(format!("Stale handle lookup: {:?}", scene.get(shield).map(|node| &node.name)));
+    print_scene(r, &scene);
+    let live: Vec<&str> = scene.iter().map(|(_, node)| node.name.as_str()).collect();
+    r.line(format!("Live nodes in slot order: {:?}", live));
 }
 
-pub struct Tweet {
-    pub username: String,
-    pub content: String,
-    pub reply: bool,
-    pub retweet: bool,
+// === Section Reporting ===
+
+/// Collects section output in order so it can be inspected before (or instead of) printing.
+struct SectionReporter {
+    sections: Vec<ReportSection>,
 }
 
-impl Summary for Tweet {
-    fn summarize_author(&self) -> String {
-        format!("@{}", self.username)
+/// One titled block of output lines.
+#[derive(Debug, Clone, PartialEq)]
+struct ReportSection {
+    title: String,
+    lines: Vec<String>,
+}
+
+/// The finished output of a `SectionReporter`; `Display` renders it exactly as printed.
+#[derive(Debug, Clone, PartialEq)]
+struct Report {
+    sections: Vec<ReportSection>,
+}
+
+impl SectionReporter {
+    fn new() -> Self {
+        SectionReporter { sections: Vec::new() }
     }
 
-    // Override the default summarize method
-    fn summarize(&self) -> String {
-        format!("{}: {}", self.summarize_author(), self.content)
+    /// Starts a new section; subsequent lines belong to it.
+    fn section(&mut self, title: &str) {
+        self.sections.push(ReportSection { title: title.to_string(), lines: Vec::new() });
+    }
+
+    /// Appends a line to the current section (an untitled one is started if needed).
+    fn line(&mut self, text: impl fmt::Display) {
+        if self.sections.is_empty() {
+            self.section("");
+        }
+        let current = self.sections.last_mut().unwrap(); // Non-empty, checked above
+        current.lines.push(text.to_string());
+    }
+
+    fn finish(self) -> Report {
+        Report { sections: self.sections }
     }
 }
 
-// Function that accepts any type implementing the Summary trait (static dispatch via monomorphization)
-pub fn notify<T: Summary>(item: &T) {
-    println!("Breaking news! {}", item.summarize());
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for section in &self.sections {
+            if !section.title.is_empty() {
+                writeln!(f, "\n--- {} ---", section.title)?;
+            }
+            for line in &section.lines {
+                writeln!(f, "{}", line)?;
+            }
+        }
+        Ok(())
+    }
 }
 
-// Function accepting a trait object (dynamic dispatch)
-// pub fn notify_dynamic(item: &dyn Summary) {
-//    println!("Breaking news (dynamic)! {}", item.summarize());
-// }
+// === Function Definitions ===
 
+/// Adds two i32 numbers.
+fn add_numbers(x: i32, y: i32) -> i32 {
+    x + y // Implicit return (no semicolon)
+}
 
-// === Generics ===
+/// Largest `n` whose factorial fits in a `u64` (21! overflows).
+const MAX_FACTORIAL_INPUT: u64 = 20;
 
-/// Finds the largest item in a slice.
-/// Works for any type T that implements PartialOrd (for comparison >) and Copy.
-// fn largest<T: PartialOrd + Copy>(list: &[T]) -> T {
-//     let mut largest = list[0];
-//     for &item in list {
-//         if item > largest {
-//             largest = item;
-//         }
-//     }
-//     largest
-// }
-// Version returning a reference (avoids Copy requirement, but needs lifetimes)
-fn largest<T: PartialOrd>(list: &[T]) -> &T {
-     if list.is_empty() {
-        panic!("Cannot find largest in empty list");
+/// Calculates factorial recursively; `None` above `MAX_FACTORIAL_INPUT`.
+/// The limit is checked before recursing, so a huge `n` cannot exhaust the stack.
+fn factorial(n: u64) -> Option<u64> {
+    if n > MAX_FACTORIAL_INPUT {
+        return None;
     }
-    let mut largest = &list[0];
-    for item in list.iter() {
-        if item > largest {
-            largest = item;
-        }
+    fn go(n: u64) -> u64 {
+        if n == 0 { 1 } else { n * go(n - 1) }
     }
-    largest
+    Some(go(n))
 }
 
+/// Same result as `factorial`, computed with a loop instead of recursion.
+fn factorial_iter(n: u64) -> Option<u64> {
+    (1..=n).try_fold(1u64, |acc, k| acc.checked_mul(k))
+}
 
-/// A generic Point struct.
-struct Point<T> {
-    x: T,
-    y: T,
+/// Demonstrates basic control flow.
+fn control_flow_example(number: i32, r: &mut SectionReporter) {
+    if number % 4 == 0 {
+        r.line(format!("{} is divisible by 4", number));
+    } else if number % 3 == 0 {
+        r.line(format!("{} is divisible by 3", number));
+    } else if number % 2 == 0 {
+        r.line(format!("{} is divisible by 2", number));
+    } else {
+        r.line(format!("{} is not divisible by 4, 3, or 2", number));
+    }
+
+    // `if` is an expression
+    let condition = true;
+    let value = if condition { 5 } else { 6 };
+    r.line(format!("The value from if expression is: {}", value));
 }
 
-// Implement methods on the generic Point<T>
-impl<T> Point<T> {
-    fn x(&self) -> &T {
-        &self.x
+/// Demonstrates different loop types.
+fn loop_examples(r: &mut SectionReporter) {
+    // Infinite loop with break
+    let mut counter = 0;
+    let result = loop {
+        counter += 1;
+        if counter == 10 {
+            break counter * 2; // Return a value from the loop
+        }
+    };
+    r.line(format!("Loop result: {}", result));
+
+    // While loop
+    let mut number = 3;
+    while number != 0 {
+        r.line(format!("{}!", number));
+        number -= 1;
     }
-    fn y(&self) -> &T {
-        &self.y
+    r.line("WHILE loop finished!");
+
+    // For loop (iterating over a range)
+    for i in 1..4 { // 1, 2, 3 (exclusive end)
+        r.line(format!("For loop (1..4): {}", i));
+    }
+     for i in 1..=4 { // 1, 2, 3, 4 (inclusive end)
+        r.line(format!("For loop (1..=4): {}", i));
     }
-}
 
-// === Error Handling Functions ===
+    // For loop (iterating over collection)
+    let a = [10, 20, 30, 40, 50];
+    for element in a.iter() { // Using iter() to borrow elements
+        r.line(format!("Array element: {}", element));
+    }
 
-/// Finds the index of an item in a slice, returning Option<usize>.
-fn find_item(haystack: &[i32], needle: i32) -> Option<usize> {
-    for (index, &item) in haystack.iter().enumerate() {
-        if item == needle {
-            return Some(index); // Found it!
+    // The `break counter * 2` pattern, generalized
+    r.line(format!("First multiple of 7 after 50: {}", first_where(51, |n| n + 1, |n| n % 7 == 0)));
+    let capped = first_where_capped(3u64, |n| n * 3, |n| n % 10 == 1, 3);
+    r.line(format!("Power of 3 ending in 1 within 3 steps: {:?}", capped));
+}
+
+/// Starting from `start`, applies `step` until `pred` holds and returns that value
+/// (`start` itself if it already matches). Loops forever if no value ever matches;
+/// `first_where_capped` is the guarded version.
+fn first_where<T: Copy, F: Fn(T) -> bool>(start: T, step: impl Fn(T) -> T, pred: F) -> T {
+    let mut value = start;
+    loop {
+        if pred(value) {
+            break value;
         }
+        value = step(value);
     }
-    None // Not found
 }
 
-/// Divides two f64 numbers, returning Result<f64, String>.
-fn divide(numerator: f64, denominator: f64) -> Result<f64, String> {
-    if denominator == 0.0 {
-        Err(String::from("Cannot divide by zero!"))
-    } else {
-        Ok(numerator / denominator)
+/// Like `first_where`, but gives up with `None` after `max_steps` applications of `step`.
+fn first_where_capped<T: Copy, F: Fn(T) -> bool>(
+    start: T,
+    step: impl Fn(T) -> T,
+    pred: F,
+    max_steps: usize,
+) -> Option<T> {
+    let mut value = start;
+    for _ in 0..max_steps {
+        if pred(value) {
+            return Some(value);
+        }
+        value = step(value);
     }
+    pred(value).then_some(value) // The value reached by the final step still counts
 }
 
-/// Demonstrates propagating errors using the `?` operator.
-fn process_division(num: f64, den: f64) -> Result<f64, String> {
-    let result = divide(num, den)?; // If divide returns Err, this function returns the Err immediately
-    // ... do more processing if needed ...
-    println!("Division successful, proceeding...");
-    Ok(result * 2.0) // Return Ok wrapping the final value
+// === Ownership & Borrowing Functions ===
+
+/// Demonstrates ownership transfer.
+fn ownership_demo(r: &mut SectionReporter) {
+    let s1 = String::from("hello"); // s1 owns the String data
+    takes_ownership(s1, r); // s1's ownership is moved into the function
+    // println!("{}", s1); // Error! s1 is no longer valid here
+
+    let x = 5; // x is i32, which implements the Copy trait
+    makes_copy(x, r); // A copy of x is passed to the function
+    r.line(format!("x is still valid: {}", x)); // x is still valid here
 }
 
+fn takes_ownership(some_string: String, r: &mut SectionReporter) {
+    r.line(format!("Inside takes_ownership: {}", some_string));
+} // `some_string` goes out of scope, `drop` is called. Memory is freed.
 
-// === Lifetimes ===
+fn makes_copy(some_integer: i32, r: &mut SectionReporter) {
+    r.line(format!("Inside makes_copy: {}", some_integer));
+} // `some_integer` goes out of scope. Nothing special happens for Copy types.
 
-/// Returns the longest of two string slices.
-/// The lifetime 'a annotation ensures the returned reference is valid
-/// for as long as *both* input references are valid.
-fn longest<'a>(x: &'a str, y: &'a str) -> &'a str {
-    if x.len() > y.len() {
-        x
-    } else {
-        y
-    }
+/// Small plain-data struct: every field is Copy, so the whole struct can be Copy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Dimensions {
+    w: u32,
+    h: u32,
 }
-// Note: Often, lifetimes are inferred by the compiler (lifetime elision),
-// but explicit annotation is needed in ambiguous cases like this function.
 
+// This would not compile: `String` is not Copy, so a struct containing one can't be either.
+// #[derive(Clone, Copy)]
+// struct Label { text: String } // <-- COMPILER ERROR E0204: the trait `Copy` cannot be implemented for this type
 
-// === Modules ===
+fn area_of(d: Dimensions) -> u32 {
+    d.w * d.h
+} // `d` was a bitwise copy, so the caller's value is untouched
 
-mod my_module {
-    // Items are private by default
+/// Demonstrates Copy vs Clone vs move, including partial moves and `move` closures.
+fn clone_vs_copy_demo(r: &mut SectionReporter) {
+    // Copy: passing by value duplicates the bits, the original stays usable
+    let dims = Dimensions { w: 3, h: 4 };
+    let area = area_of(dims);
+    r.line(format!("Copy: area {} and dims still usable: {:?}", area, dims));
 
-    /// This function is public and can be called from outside `my_module`.
-    pub fn public_function() {
-        println!("Called my_module::public_function()");
-        private_function(); // Can call private functions within the same module
-    }
+    // Clone: an explicit deep copy. For User this allocates two new Strings, so it is not free.
+    let original = User {
+        username: String::from("clone_me"),
+        email: String::from("clone@example.com"),
+        sign_in_count: 1,
+        active: true,
+    };
+    let mut copy = original.clone();
+    copy.username.push_str("_2");
+    r.line(format!("Clone: original '{}', clone '{}'", original.username, copy.username));
 
-    /// This function is private.
-    fn private_function() {
-        println!("Called my_module::private_function()");
-    }
+    // Move: assignment transfers ownership of heap data
+    let moved = original;
+    // r.line(original.email); // <-- COMPILER ERROR E0382: borrow of moved value: `original`
 
-    // Nested module
-    pub mod nested {
-         /// Public function in a nested module.
-        pub fn nested_function() {
-            println!("Called my_module::nested::nested_function()");
-        }
+    // Partial move: take one field out; the other fields stay usable, the struct as a whole does not
+    let taken_name = moved.username;
+    r.line(format!("Partial move: took '{}', remaining fields still usable: {}", taken_name, moved.email));
+    // let whole = moved; // <-- COMPILER ERROR E0382: use of partially moved value: `moved`
+
+    // Moved in a loop: a non-Copy value can only be moved once
+    let ticket = String::from("ticket");
+    for round in 0..2 {
+        // let owned = ticket; // <-- COMPILER ERROR E0382: value moved here, in previous iteration of loop
+        r.line(format!("Round {}: borrowing '{}' instead of moving it", round, &ticket));
     }
 
-    // You can also have structs, enums, traits, etc., inside modules
-    // Use `pub` to make them accessible outside.
+    // `move` closures take ownership of captures, which `thread::spawn` requires ('static)
+    let payload = String::from("owned by the thread");
+    let handle = thread::spawn(move || payload.len());
+    // r.line(&payload); // <-- COMPILER ERROR E0382: borrow of moved value: `payload`
+    r.line(format!("move closure measured {} bytes on another thread", handle.join().unwrap()));
+
+    // Sizes are what Copy duplicates; Clone may follow pointers and copy much more.
+    // Pointer-based types are counted in words (`usize`s), which is the same on every target
+    let words = |bytes: usize| match bytes / std::mem::size_of::<usize>() {
+        1 => String::from("1 word"),
+        n => format!("{} words", n),
+    };
+    r.line(format!("{:<12} {:<8}  {}", "type", "size", "on assignment"));
+    r.line(format!("{:<12} {:<8}  {}", "i32", format!("{} bytes", std::mem::size_of::<i32>()), "copy"));
+    r.line(format!("{:<12} {:<8}  {}", "Dimensions", format!("{} bytes", std::mem::size_of::<Dimensions>()), "copy"));
+    r.line(format!("{:<12} {:<8}  {}", "String", words(std::mem::size_of::<String>()), "move (clone copies heap)"));
+    r.line(format!("{:<12} {:<8}  {}", "Vec<User>", words(std::mem::size_of::<Vec<User>>()), "move (clone copies heap)"));
+    r.line(format!("{:<12} {:<8}  {}", "Box<User>", words(std::mem::size_of::<Box<User>>()), "move (clone copies heap)"));
+}
+
+/// Demonstrates borrowing (references).
+fn borrowing_demo(r: &mut SectionReporter) {
+    let s1 = String::from("world");
+
+    // Pass an immutable reference (&) - borrows s1
+    let len = calculate_length(&s1);
+    r.line(format!("The length of '{}' is {}.", s1, len)); // s1 is still valid
+
+    let mut s2 = String::from("mutable");
+    // Pass a mutable reference (&mut) - mutably borrows s2
+    change_string(&mut s2);
+    r.line(format!("Changed string: {}", s2)); // s2 has been modified
+
+    // A mutable borrow of a slice is enough to rearrange it in place
+    let mut ring = [1, 2, 3, 4, 5];
+    rotate_left(&mut ring, 7);
+    r.line(format!("[1, 2, 3, 4, 5] rotated left by 7: {:?}", ring));
+}
+
+fn calculate_length(s: &String) -> usize { // `s` is a reference to a String
+    s.len()
+} // `s` goes out of scope, but does *not* drop what it refers to.
+
+fn change_string(some_string: &mut String) { // takes a mutable reference
+    some_string.push_str(" changed");
+}
+
+// Classifying an expression as `&`, `&mut` or owned. Without specialization, three
+// overlapping blanket impls can't live on one trait, so each gets its own trait and
+// method lookup picks between them: `(&&&probe).borrow_kind()` tries the receiver
+// `&&&Probe` first, then auto-derefs to `&&Probe`, then `&Probe`. The impls sit at
+// those levels in order of specificity, so the first match is the most specific.
+struct BorrowProbe<'a, T: ?Sized>(&'a T);
+
+trait MutBorrowKind {
+    fn borrow_kind(&self) -> &'static str {
+        "&mut"
+    }
+}
+impl<T: ?Sized> MutBorrowKind for &&BorrowProbe<'_, &mut T> {}
+
+trait SharedBorrowKind {
+    fn borrow_kind(&self) -> &'static str {
+        "&"
+    }
+}
+impl<T: ?Sized> SharedBorrowKind for &BorrowProbe<'_, &T> {}
+
+trait OwnedKind {
+    fn borrow_kind(&self) -> &'static str {
+        "owned"
+    }
+}
+impl<T: ?Sized> OwnedKind for BorrowProbe<'_, T> {}
+
+/// Reports an expression's type and whether it is a `&`, `&mut`, or owned value, then
+/// evaluates to that kind. The expression is only borrowed, never moved.
+macro_rules! debug_borrows {
+    ($r:expr, $e:expr) => {{
+        let value = &$e;
+        let kind = (&&&BorrowProbe(value)).borrow_kind();
+        $r.line(format!("{}: {} ({})", stringify!($e), short_type_name(std::any::type_name_of_val(value)), kind));
+        kind
+    }};
+}
+
+/// `type_name` output without module paths: `&mut alloc::string::String` becomes
+/// `&mut String`. The exact paths are not guaranteed to stay the same across compiler
+/// versions, and the short form is the part worth reading anyway.
+fn short_type_name(full: &str) -> String {
+    let mut short = String::new();
+    let mut segment = String::new();
+    let mut chars = full.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            segment.clear(); // A path prefix: drop it
+        } else if c.is_alphanumeric() || c == '_' {
+            segment.push(c);
+        } else {
+            short.push_str(&segment);
+            segment.clear();
+            short.push(c);
+        }
+    }
+    short + &segment
+}
+
+/// Non-lexical lifetimes: a borrow lasts until its last use, not until the end of scope.
+fn nll_example(scores: &mut Vec<i32>) -> i32 {
+    let first = &scores[0]; // Immutable borrow starts...
+    let doubled = *first * 2; // ...and ends here, at its last use
+    scores.push(doubled); // So this mutable borrow is fine
+    // Using `first` after the push would extend the borrow and fail:
+    // scores.push(doubled);
+    // println!("{}", first); // Compiler Error E0502: cannot borrow `*scores` as mutable because it is also borrowed as immutable
+    doubled
+}
+
+/// Borrows of different fields are tracked separately, so both can be live at once.
+fn disjoint_field_borrows(user: &mut User) {
+    let email = &mut user.email;
+    let name = &user.username; // Fine: `username` and `email` are different places
+    email.clear();
+    email.push_str(name);
+    email.push_str("@users.example.com");
+    // Going through a method borrows the *whole* struct, which does conflict:
+    // let email = &mut user.email;
+    // let name = user.name(); // Compiler Error E0502: cannot borrow `*user` as immutable because it is also borrowed as mutable
+    // email.push_str(name);
+}
+
+/// Adds each element of the front half into the matching element of the back half.
+/// Odd lengths leave the middle element untouched.
+fn add_front_into_back(v: &mut [i32]) {
+    let half = v.len() / 2;
+    // Indexing twice would borrow `v` mutably twice:
+    // let (front, back) = (&v[..half], &mut v[v.len() - half..]); // Compiler Error E0502
+    let (front, rest) = v.split_at_mut(half);
+    let middle = rest.len() - half; // 0 or 1
+    let back = &mut rest[middle..];
+    for (b, f) in back.iter_mut().zip(front.iter()) {
+        *b += *f;
+    }
+}
+
+/// Compiling counterparts of the borrow errors people hit most.
+fn borrowing_advanced_demo(r: &mut SectionReporter) {
+    let mut scores = vec![21, 5];
+    let doubled = nll_example(&mut scores);
+    r.line(format!("NLL: pushed {} while a borrow of scores[0] had already ended: {:?}", doubled, scores));
+
+    let mut user = User {
+        username: String::from("ferris"),
+        email: String::from("old@example.com"),
+        sign_in_count: 0,
+        active: true,
+    };
+    disjoint_field_borrows(&mut user);
+    r.line(format!("Disjoint fields: email rebuilt from username: {}", user.email));
+
+    let mut halves = [1, 2, 3, 10, 20, 30];
+    add_front_into_back(&mut halves);
+    r.line(format!("split_at_mut: front half added into back half: {:?}", halves));
+
+    let mut owned = String::from("owned");
+    debug_borrows!(r, owned.clone());
+    debug_borrows!(r, &owned);
+    debug_borrows!(r, &mut owned);
+}
+
+/// Rotates `slice` left by `by` positions (wrapping modulo the length), in place.
+/// Three reversals do it without allocating: reverse the first `by` elements, then
+/// the rest, then the whole slice. (The standard library has `slice::rotate_left`.)
+fn rotate_left<T>(slice: &mut [T], by: usize) {
+    if slice.is_empty() {
+        return; // Avoids `% 0`
+    }
+    let by = by % slice.len();
+    slice[..by].reverse();
+    slice[by..].reverse();
+    slice.reverse();
+}
+
+// === Struct Definitions ===
+
+/// Represents a user account.
+#[derive(Debug, Clone)] // Deep copy on request via `.clone()`; can't be Copy because of the Strings
+struct User {
+    username: String,
+    email: String,
+    sign_in_count: u64,
+    active: bool,
+}
+
+impl User {
+    /// The email's domain (after the '@'), or `None` for a malformed address.
+    fn domain(&self) -> Option<&str> {
+        Email::split(&self.email).ok().map(|(_, domain)| domain)
+    }
+
+    /// The email as a validated `Email`.
+    fn email_typed(&self) -> Result<Email, EmailError> {
+        Email::parse(&self.email)
+    }
+}
+
+/// An email address with exactly one '@' and text on both sides of it.
+/// Deliberately loose beyond that: full RFC 5322 validation is rarely worth it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Email {
+    address: String,
+    at: usize, // Byte index of the '@'
+}
+
+/// Why a string is not an `Email`.
+#[derive(Debug, PartialEq)]
+enum EmailError {
+    MissingAt,
+    MultipleAt,
+    EmptyLocal,
+    EmptyDomain,
+}
+
+impl fmt::Display for EmailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmailError::MissingAt => write!(f, "email has no '@'"),
+            EmailError::MultipleAt => write!(f, "email has more than one '@'"),
+            EmailError::EmptyLocal => write!(f, "email has nothing before the '@'"),
+            EmailError::EmptyDomain => write!(f, "email has nothing after the '@'"),
+        }
+    }
+}
+
+impl Email {
+    fn parse(s: &str) -> Result<Email, EmailError> {
+        let (local, _) = Email::split(s)?;
+        Ok(Email { address: s.to_string(), at: local.len() })
+    }
+
+    /// Validates `s` exactly like `parse`, but borrows the local part and domain from `s`
+    /// instead of building an `Email`.
+    fn split(s: &str) -> Result<(&str, &str), EmailError> {
+        let at = s.find('@').ok_or(EmailError::MissingAt)?;
+        if s[at + 1..].contains('@') {
+            return Err(EmailError::MultipleAt);
+        }
+        if at == 0 {
+            return Err(EmailError::EmptyLocal);
+        }
+        if at + 1 == s.len() {
+            return Err(EmailError::EmptyDomain);
+        }
+        Ok((&s[..at], &s[at + 1..]))
+    }
+
+    /// The part before the '@'.
+    fn local(&self) -> &str {
+        &self.address[..self.at]
+    }
+
+    /// The part after the '@'.
+    fn domain(&self) -> &str {
+        &self.address[self.at + 1..]
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.address)
+    }
+}
+
+/// A tuple struct for RGB color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)] // Eq + Hash: usable as a HashSet/HashMap key
+struct Color(u8, u8, u8);
+
+/// How `Color::blend` combines a layer with the color beneath it, per channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlendMode {
+    /// The layer covers the base.
+    Normal,
+    /// Product of the two: always darker; white is neutral.
+    Multiply,
+    /// Inverted product of the inverses: always lighter; black is neutral.
+    Screen,
+    /// Multiply where the base is dark, Screen where it is light: adds contrast.
+    Overlay,
+    /// Sum, clamped at 255.
+    Add,
+}
+
+impl Color {
+    /// Adds channel-wise, clamping each channel at 255.
+    fn saturating_add(self, other: Color) -> Color {
+        Color(self.0.saturating_add(other.0), self.1.saturating_add(other.1), self.2.saturating_add(other.2))
+    }
+
+    /// Adds channel-wise, wrapping around past 255 (modulo 256).
+    fn wrapping_add(self, other: Color) -> Color {
+        Color(self.0.wrapping_add(other.0), self.1.wrapping_add(other.1), self.2.wrapping_add(other.2))
+    }
+
+    /// Subtracts channel-wise, clamping each channel at 0.
+    fn saturating_sub(self, other: Color) -> Color {
+        Color(self.0.saturating_sub(other.0), self.1.saturating_sub(other.1), self.2.saturating_sub(other.2))
+    }
+
+    /// Blends `other` as a layer on top of `self` (the base), using the usual
+    /// image-editor formulas on channels normalized to 0..=1. Works on the sRGB
+    /// values directly, as those editors do, rather than in linear light.
+    fn blend(&self, other: &Color, mode: BlendMode) -> Color {
+        // a * b / 255, rounded to nearest, without leaving integers
+        let mul = |a: u32, b: u32| (a * b + 127) / 255;
+        let channel = |base: u8, top: u8| {
+            let (a, b) = (u32::from(base), u32::from(top));
+            let value = match mode {
+                BlendMode::Normal => b,
+                BlendMode::Multiply => mul(a, b),
+                BlendMode::Screen => 255 - mul(255 - a, 255 - b),
+                BlendMode::Overlay if a < 128 => mul(2 * a, b),
+                BlendMode::Overlay => 255 - mul(2 * (255 - a), 255 - b),
+                BlendMode::Add => (a + b).min(255),
+            };
+            value as u8
+        };
+        Color(channel(self.0, other.0), channel(self.1, other.1), channel(self.2, other.2))
+    }
+
+    /// Decodes sRGB channels to linear light in 0.0..=1.0 (the sRGB transfer function).
+    fn to_linear(self) -> [f64; 3] {
+        let decode = |c: u8| {
+            let c = f64::from(c) / 255.0;
+            if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        };
+        [decode(self.0), decode(self.1), decode(self.2)]
+    }
+
+    /// Encodes linear light back to sRGB; values outside 0.0..=1.0 are clamped.
+    fn from_linear(linear: [f64; 3]) -> Color {
+        let encode = |l: f64| {
+            let l = l.clamp(0.0, 1.0);
+            let c = if l <= 0.0031308 { 12.92 * l } else { 1.055 * l.powf(1.0 / 2.4) - 0.055 };
+            (c * 255.0).round() as u8
+        };
+        Color(encode(linear[0]), encode(linear[1]), encode(linear[2]))
+    }
+
+    /// Straight-line distance between the two colors in RGB space (0.0 to about 441.7).
+    fn distance_rgb(&self, other: &Color) -> f64 {
+        let d = |a: u8, b: u8| f64::from(a) - f64::from(b);
+        (d(self.0, other.0).powi(2) + d(self.1, other.1).powi(2) + d(self.2, other.2).powi(2)).sqrt()
+    }
+
+    /// Perceptual difference ΔE (CIE76): Euclidean distance in CIELAB, where equal
+    /// steps look roughly equally different. A ΔE around 2.3 is just noticeable.
+    fn distance_lab(&self, other: &Color) -> f64 {
+        let (a, b) = (self.to_lab(), other.to_lab());
+        ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+    }
+
+    // sRGB -> linear -> CIE XYZ (D65 white) -> CIELAB [L*, a*, b*]
+    fn to_lab(self) -> [f64; 3] {
+        let [r, g, b] = self.to_linear();
+        let x = 0.4124 * r + 0.3576 * g + 0.1805 * b;
+        let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        let z = 0.0193 * r + 0.1192 * g + 0.9505 * b;
+        let f = |t: f64| {
+            const DELTA: f64 = 6.0 / 29.0;
+            if t > DELTA.powi(3) { t.cbrt() } else { t / (3.0 * DELTA * DELTA) + 4.0 / 29.0 }
+        };
+        let (fx, fy, fz) = (f(x / 0.95047), f(y), f(z / 1.08883));
+        [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+    }
+
+    /// Applies a simple power-law gamma: each normalized channel becomes `c^(1/g)`.
+    /// `g > 1` brightens midtones, `g < 1` darkens them; 0 and 255 are unchanged.
+    fn gamma(&self, g: f64) -> Color {
+        let apply = |c: u8| ((f64::from(c) / 255.0).powf(1.0 / g) * 255.0).round() as u8;
+        Color(apply(self.0), apply(self.1), apply(self.2))
+    }
+
+    /// Mean of each channel, rounded to nearest; `None` for an empty slice.
+    fn average(colors: &[Color]) -> Option<Color> {
+        if colors.is_empty() {
+            return None;
+        }
+        let n = colors.len() as u64;
+        // Sum in a wider type so 255 + 255 + ... can't overflow before dividing
+        let (r, g, b) = colors.iter().fold((0u64, 0u64, 0u64), |(r, g, b), c| {
+            (r + u64::from(c.0), g + u64::from(c.1), b + u64::from(c.2))
+        });
+        let mean = |sum: u64| ((sum + n / 2) / n) as u8; // Adding n/2 rounds half up
+        Some(Color(mean(r), mean(g), mean(b)))
+    }
+
+    /// Approximates the color of a black-body radiator at `kelvin` degrees.
+    /// Uses Tanner Helland's curve fit, valid for 1000K..=40000K; inputs outside are clamped.
+    fn from_kelvin(kelvin: f64) -> Color {
+        let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+        let red = if t <= 66.0 { 255.0 } else { 329.698727446 * (t - 60.0).powf(-0.1332047592) };
+        let green = if t <= 66.0 {
+            99.4708025861 * t.ln() - 161.1195681661
+        } else {
+            288.1221695283 * (t - 60.0).powf(-0.0755148492)
+        };
+        let blue = if t >= 66.0 {
+            255.0
+        } else if t <= 19.0 {
+            0.0
+        } else {
+            138.5177312231 * (t - 10.0).ln() - 305.0447927307
+        };
+        // `as u8` on a float saturates, but clamp first to make the intent explicit
+        let channel = |v: f64| v.round().clamp(0.0, 255.0) as u8;
+        Color(channel(red), channel(green), channel(blue))
+    }
+}
+
+/// A channel value fell outside 0..=255 when converting to `Color`.
+#[derive(Debug, PartialEq)]
+struct ColorRangeError {
+    channel: &'static str,
+    value: i64,
+}
+
+impl fmt::Display for ColorRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} channel {} is outside 0..=255", self.channel, self.value)
+    }
+}
+
+// Infallible: every u8 is a valid channel
+impl From<(u8, u8, u8)> for Color {
+    fn from((r, g, b): (u8, u8, u8)) -> Color {
+        Color(r, g, b)
+    }
+}
+
+// Fallible: wide integers (e.g. from a database) must be range-checked
+impl TryFrom<(i64, i64, i64)> for Color {
+    type Error = ColorRangeError;
+
+    fn try_from((r, g, b): (i64, i64, i64)) -> Result<Color, ColorRangeError> {
+        let channel = |name, value: i64| u8::try_from(value).map_err(|_| ColorRangeError { channel: name, value });
+        Ok(Color(channel("red", r)?, channel("green", g)?, channel("blue", b)?))
+    }
+}
+
+/// Removes exact duplicates, keeping the first occurrence of each color in order.
+fn dedup_colors(colors: &[Color]) -> Vec<Color> {
+    let mut seen = HashSet::new();
+    // `insert` returns false for a color already in the set
+    colors.iter().copied().filter(|c| seen.insert(*c)).collect()
+}
+
+/// Keeps a color only if it is farther than `threshold` (Euclidean distance in RGB space)
+/// from every color kept so far. Order-dependent: earlier colors win. O(n * kept).
+fn dedup_similar(colors: &[Color], threshold: f64) -> Vec<Color> {
+    let mut kept: Vec<Color> = Vec::new();
+    for color in colors {
+        if kept.iter().all(|k| k.distance_rgb(color) > threshold) {
+            kept.push(*color);
+        }
+    }
+    kept
+}
+
+/// Renders colors as `#rrggbb`, comma-separated, `per_line` to a line (0 means one line).
+/// Line breaks follow the comma, so the output is still a single list: `#ff0000, #00ff00,\n#0000ff`.
+fn hex_dump(colors: &[Color], per_line: usize) -> String {
+    let per_line = if per_line == 0 { colors.len().max(1) } else { per_line };
+    colors
+        .chunks(per_line)
+        .map(|line| line.iter().map(Color::to_string).collect::<Vec<_>>().join(", "))
+        .collect::<Vec<_>>()
+        .join(",\n")
+}
+
+// Display as a CSS-style hex code
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+// Operator overloading: `+` saturates, which is what additive blending usually wants
+impl Add for Color {
+    type Output = Color;
+
+    fn add(self, other: Color) -> Color {
+        self.saturating_add(other)
+    }
+}
+
+// `-` also saturates, so dimming never underflows
+impl Sub for Color {
+    type Output = Color;
+
+    fn sub(self, other: Color) -> Color {
+        self.saturating_sub(other)
+    }
+}
+
+/// A unit-like struct (no fields).
+struct AlwaysEqual;
+
+// === Enum Definition ===
+
+/// Represents different types of messages.
+#[derive(Debug, Clone, PartialEq)]
+enum Message {
+    Quit,                       // No data associated
+    Move { x: i32, y: i32 },    // Anonymous struct variant
+    Write(String),              // Includes a String
+    ChangeColor(u8, u8, u8), // Includes three u8 values
+}
+
+/// Processes a Message enum.
+fn process_message(msg: Message, reporter: &mut SectionReporter) {
+    match msg {
+        Message::Quit => reporter.line("Message: Quit"),
+        Message::Move { x, y } => reporter.line(format!("Message: Move to x={}, y={}", x, y)),
+        Message::Write(text) => reporter.line(format!("Message: Write - {}", text)),
+        Message::ChangeColor(r, g, b) => reporter.line(format!("Message: ChangeColor to ({}, {}, {})", r, g, b)),
+    }
+}
+
+impl Message {
+    /// Names of every variant, in declaration order.
+    fn variant_names() -> &'static [&'static str] {
+        &["Quit", "Move", "Write", "ChangeColor"]
+    }
+
+    /// One representative (zero-valued) message per variant, in the same order as
+    /// `variant_names`, for tests and demos that should cover every shape.
+    fn sample_variants() -> Vec<Message> {
+        vec![
+            Message::Quit,
+            Message::Move { x: 0, y: 0 },
+            Message::Write(String::new()),
+            Message::ChangeColor(0, 0, 0),
+        ]
+    }
+
+    /// The color carried by a `ChangeColor` message, if this is one.
+    fn as_color(&self) -> Option<Color> {
+        match *self {
+            Message::ChangeColor(r, g, b) => Some(Color(r, g, b)),
+            _ => None,
+        }
+    }
+
+    /// The variant name of this message, ignoring any data it carries.
+    fn variant_name(&self) -> &'static str {
+        match self {
+            Message::Quit => "Quit",
+            Message::Move { .. } => "Move",
+            Message::Write(_) => "Write",
+            Message::ChangeColor(..) => "ChangeColor",
+        }
+    }
+}
+
+// Any color can become a ChangeColor message; `as_color` is the inverse
+impl From<Color> for Message {
+    fn from(color: Color) -> Message {
+        Message::ChangeColor(color.0, color.1, color.2)
+    }
+}
+
+/// A record of one message applied by the `Cursor` interpreter; `at` is measured from the
+/// session's first message, so it means the same thing after a save and resume.
+#[derive(Debug, Clone, PartialEq)]
+struct LogEntry {
+    at: Duration,
+    message: Message,
+}
+
+/// Shortens a message stream without changing what it does to a `Cursor`: a run of
+/// adjacent `Move`s becomes its last one (moves are absolute), likewise for adjacent
+/// `ChangeColor`s. `Write` and `Quit` are kept as they are, and they break runs.
+fn coalesce(messages: Vec<Message>) -> Vec<Message> {
+    let mut out: Vec<Message> = Vec::with_capacity(messages.len());
+    for message in messages {
+        let supersedes_last = matches!(
+            (out.last(), &message),
+            (Some(Message::Move { .. }), Message::Move { .. })
+                | (Some(Message::ChangeColor(..)), Message::ChangeColor(..))
+        );
+        match out.last_mut() {
+            Some(last) if supersedes_last => *last = message,
+            _ => out.push(message),
+        }
+    }
+    out
+}
+
+/// A line of a cursor script that is not a valid message; `line` is 1-based.
+#[derive(Debug, PartialEq)]
+struct ScriptError {
+    line: usize,
+    text: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: cannot read {:?} as a message", self.line, self.text)
+    }
+}
+
+/// Reads a script offset written by `Cursor::to_script`: whole seconds, a '.', then exactly
+/// nine digits of nanoseconds and an `s`.
+fn parse_offset(token: &str) -> Option<Duration> {
+    let (secs, nanos) = token.strip_suffix('s')?.split_once('.')?;
+    if nanos.len() != 9 || !nanos.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(Duration::new(secs.parse().ok()?, nanos.parse().ok()?))
+}
+
+/// A tiny interpreter whose state is driven entirely by `Message`s.
+#[derive(Debug)]
+struct Cursor {
+    x: i32,
+    y: i32,
+    color: Color,
+    text: String,
+    quit: bool,
+    log: Vec<LogEntry>,
+    origin: Option<Instant>, // When offset zero was, in this run; `None` until the first message
+}
+
+// `origin` is left out: it only locates the log within one run, while the offsets carry the timing
+impl PartialEq for Cursor {
+    fn eq(&self, other: &Self) -> bool {
+        (self.x, self.y, self.color, &self.text, self.quit, &self.log)
+            == (other.x, other.y, other.color, &other.text, other.quit, &other.log)
+    }
+}
+
+impl Cursor {
+    fn new() -> Self {
+        Cursor { x: 0, y: 0, color: Color(0, 0, 0), text: String::new(), quit: false, log: Vec::new(), origin: None }
+    }
+
+    /// Applies a message to the cursor state and records it in the history.
+    fn apply(&mut self, message: Message) {
+        self.step(&message);
+        let now = Instant::now();
+        let origin = *self.origin.get_or_insert(now);
+        self.log.push(LogEntry { at: now - origin, message });
+    }
+
+    /// Every message applied so far, oldest first.
+    fn history(&self) -> &[LogEntry] {
+        &self.log
+    }
+
+    /// Removes the most recent `Move` or `ChangeColor` and rebuilds the state by replaying the rest.
+    /// Returns the undone message, or `None` if there was nothing to undo.
+    fn undo(&mut self) -> Option<Message> {
+        let index = self
+            .log
+            .iter()
+            .rposition(|entry| matches!(entry.message, Message::Move { .. } | Message::ChangeColor(..)))?;
+        let undone = self.log.remove(index);
+
+        let log = std::mem::take(&mut self.log);
+        let origin = self.origin;
+        *self = Cursor::new();
+        for entry in &log {
+            self.step(&entry.message);
+        }
+        // Keep the original offsets and the point they count from
+        self.log = log;
+        self.origin = origin;
+        Some(undone.message)
+    }
+
+    /// Saves the session as one message per line, each prefixed with its offset in seconds
+    /// (`0.000001500s move X Y`, likewise `color R G B`, `write TEXT` and `quit`). The state is
+    /// fully determined by the log, so this is all `from_script` needs.
+    fn to_script(&self) -> String {
+        self.log
+            .iter()
+            .map(|entry| {
+                let offset = format!("{}.{:09}s", entry.at.as_secs(), entry.at.subsec_nanos());
+                match &entry.message {
+                    Message::Quit => format!("{} quit\n", offset),
+                    Message::Move { x, y } => format!("{} move {} {}\n", offset, x, y),
+                    Message::Write(text) => format!("{} write {}\n", offset, escape_digest(text)),
+                    Message::ChangeColor(r, g, b) => format!("{} color {} {} {}\n", offset, r, g, b),
+                }
+            })
+            .collect()
+    }
+
+    /// Resumes a session saved by `to_script` by replaying its messages with their saved
+    /// offsets. Messages applied afterwards continue from the last offset.
+    fn from_script(script: &str) -> Result<Cursor, ScriptError> {
+        let mut cursor = Cursor::new();
+        for (index, line) in script.lines().enumerate().filter(|(_, line)| !line.is_empty()) {
+            let error = || ScriptError { line: index + 1, text: line.to_string() };
+            let (offset, line) = line.split_once(' ').ok_or_else(error)?;
+            let at = parse_offset(offset).ok_or_else(error)?;
+            let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+            let numbers = || args.split(' ').map(str::parse::<i64>).collect::<Result<Vec<_>, _>>().ok();
+            let message = match (command, numbers().as_deref()) {
+                ("quit", _) if args.is_empty() => Message::Quit,
+                ("write", _) => Message::Write(unescape_digest(args).ok_or_else(error)?),
+                ("move", Some(&[x, y])) => Message::Move {
+                    x: i32::try_from(x).map_err(|_| error())?,
+                    y: i32::try_from(y).map_err(|_| error())?,
+                },
+                ("color", Some(&[r, g, b])) => Color::try_from((r, g, b)).map_err(|_| error())?.into(),
+                _ => return Err(error()),
+            };
+            cursor.step(&message);
+            cursor.log.push(LogEntry { at, message });
+        }
+        if let Some(last) = cursor.log.last() {
+            let now = Instant::now();
+            cursor.origin = Some(now.checked_sub(last.at).unwrap_or(now));
+        }
+        Ok(cursor)
+    }
+
+    // Updates state without touching the log (shared by `apply` and replay)
+    fn step(&mut self, message: &Message) {
+        match message {
+            Message::Quit => self.quit = true,
+            Message::Move { x, y } => {
+                self.x = *x;
+                self.y = *y;
+            }
+            Message::Write(text) => self.text.push_str(text),
+            Message::ChangeColor(r, g, b) => self.color = Color(*r, *g, *b),
+        }
+    }
+}
+
+// === Methods (`impl`) ===
+
+#[derive(Debug, Clone)] // Auto-implement Debug (for printing) and Clone
+struct Rectangle {
+    width: u32,
+    height: u32,
+}
+
+// Implementation block for Rectangle
+impl Rectangle {
+    /// Calculates the area of the rectangle.
+    fn area(&self) -> u32 { // `&self` is shorthand for `self: &Self` (immutable borrow)
+        self.width * self.height
+    }
+
+    /// `area` without the overflow panic: `None` when `width * height` exceeds `u32::MAX`.
+    fn checked_area(&self) -> Option<u32> {
+        checked_area(self.width, self.height)
+    }
+
+    /// Total length of all four sides.
+    fn perimeter(&self) -> u32 {
+        2 * (self.width + self.height)
+    }
+
+    /// Length of the diagonal (Pythagoras: the hypotenuse of width and height).
+    fn diagonal(&self) -> f64 {
+        f64::from(self.width).hypot(f64::from(self.height))
+    }
+
+    /// Whether all sides are equal.
+    fn is_square(&self) -> bool {
+        self.width == self.height
+    }
+
+    /// Checks if this rectangle can hold another rectangle.
+    fn can_hold(&self, other: &Rectangle) -> bool {
+        self.width > other.width && self.height > other.height
+    }
+
+    /// Geometric containment: `other` lies fully inside `self`, edges allowed to touch.
+    /// Rectangles carry no position yet, so both are treated as anchored at the same origin,
+    /// which makes this a non-strict size comparison (unlike the strict `can_hold`).
+    fn contains_rect(&self, other: &Rectangle) -> bool {
+        self.width >= other.width && self.height >= other.height
+    }
+
+    /// Grows by `dx` on the left and right and `dy` on the top and bottom (negative values shrink).
+    /// Sizes are clamped at 0 and at `u32::MAX`.
+    fn inflate(&self, dx: i32, dy: i32) -> Rectangle {
+        let grow = |size: u32, d: i32| (i64::from(size) + 2 * i64::from(d)).clamp(0, i64::from(u32::MAX)) as u32;
+        Rectangle { width: grow(self.width, dx), height: grow(self.height, dy) }
+    }
+
+    /// Associated function (like a static method) to create a square.
+    fn square(size: u32) -> Rectangle { // No `self` parameter
+        Rectangle { width: size, height: size }
+    }
+
+    /// Returns the sub-rectangle for cell (`col`, `row`) when split into a `cols` x `rows` grid.
+    /// Cell boundaries are rounded down, so uneven sizes spread the remainder across cells.
+    fn cell_at(&self, col: u32, row: u32, cols: u32, rows: u32) -> Option<Rectangle> {
+        if col >= cols || row >= rows {
+            return None; // Also covers a zero-sized grid
+        }
+        let edge = |i: u32, n: u32, len: u32| (u64::from(i) * u64::from(len) / u64::from(n)) as u32;
+        Some(Rectangle {
+            width: edge(col + 1, cols, self.width) - edge(col, cols, self.width),
+            height: edge(row + 1, rows, self.height) - edge(row, rows, self.height),
+        })
+    }
+
+    /// Maps a point (relative to the rectangle's top-left corner) back to its `(col, row)` cell.
+    fn cell_containing(&self, p: &Point<i32>, cols: u32, rows: u32) -> Option<(u32, u32)> {
+        if cols == 0 || rows == 0 || p.x < 0 || p.y < 0 {
+            return None;
+        }
+        let (x, y) = (p.x as u32, p.y as u32);
+        if x >= self.width || y >= self.height {
+            return None; // Outside the rectangle
+        }
+        // Inverse of `cell_at`'s rounding: the last cell whose left/top edge is <= the coordinate.
+        let index = |v: u32, n: u32, len: u32| ((u64::from(v) + 1) * u64::from(n) - 1) / u64::from(len);
+        Some((index(x, cols, self.width) as u32, index(y, rows, self.height) as u32))
+    }
+
+    /// The closest point inside or on the edge of the rectangle, for a point relative to its
+    /// top-left corner (y grows downward). A point already inside comes back unchanged.
+    fn clamp_point(&self, p: &Point<i32>) -> Point<i32> {
+        let limit = |len: u32| i32::try_from(len).unwrap_or(i32::MAX);
+        Point { x: p.x.clamp(0, limit(self.width)), y: p.y.clamp(0, limit(self.height)) }
+    }
+
+    /// The side closest to `p` (relative to the top-left corner), measuring to each side as a
+    /// segment, so points beyond a corner are judged correctly. Ties go Top, Bottom, Left, Right.
+    fn nearest_edge(&self, p: &Point<i32>) -> Edge {
+        let (x, y) = (i64::from(p.x), i64::from(p.y));
+        let (w, h) = (i64::from(self.width), i64::from(self.height));
+        // Squared distance from `p` to the segment from (x0, y0) to (x1, y1), axis-aligned
+        let to_segment = |x0: i64, y0: i64, x1: i64, y1: i64| {
+            let (dx, dy) = (x - x.clamp(x0, x1), y - y.clamp(y0, y1));
+            dx * dx + dy * dy
+        };
+        let candidates = [
+            (Edge::Top, to_segment(0, 0, w, 0)),
+            (Edge::Bottom, to_segment(0, h, w, h)),
+            (Edge::Left, to_segment(0, 0, 0, h)),
+            (Edge::Right, to_segment(w, 0, w, h)),
+        ];
+        // `min_by_key` keeps the first of equal keys, which gives the tie order above
+        candidates.iter().min_by_key(|&&(_, distance)| distance).map(|&(edge, _)| edge).unwrap()
+    }
+}
+
+/// A side of a rectangle, in screen orientation (Top is y = 0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+// We can implement traits on our types
+impl fmt::Display for Rectangle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rectangle({}x{})", self.width, self.height)
+    }
+}
+
+/// Places `rects` inside `bin` with the shelf next-fit heuristic, returning each one's
+/// top-left origin, or `None` if it did not fit. Rectangles go left to right along the
+/// current shelf; one that would overflow the width opens a new shelf below the tallest
+/// rectangle so far. Shelves are never revisited, so it is fast and simple but not
+/// optimal; placements never overlap and always lie inside the bin.
+fn pack(bin: Rectangle, rects: &[Rectangle]) -> Vec<Option<Point<u32>>> {
+    let (mut x, mut shelf_y, mut shelf_height) = (0, 0, 0);
+    rects
+        .iter()
+        .map(|r| {
+            if r.width > bin.width || r.height > bin.height {
+                return None; // Could never fit, so don't disturb the current shelf
+            }
+            if x + r.width > bin.width {
+                shelf_y += shelf_height;
+                x = 0;
+                shelf_height = 0;
+            }
+            if shelf_y + r.height > bin.height {
+                return None;
+            }
+            let origin = Point { x, y: shelf_y };
+            x += r.width;
+            shelf_height = shelf_height.max(r.height);
+            Some(origin)
+        })
+        .collect()
+}
+
+
+// === Traits (Interfaces) ===
+
+/// Defines summarizable behavior.
+pub trait Summary {
+    // Method signature (requires implementation)
+    fn summarize_author(&self) -> String;
+
+    // Method with default implementation
+    fn summarize(&self) -> String {
+        format!("(Read more from {}...)", self.summarize_author())
+    }
+
+    /// The item's body text; empty unless the type has one.
+    fn full_text(&self) -> &str {
+        ""
+    }
+
+    /// Lazily splits `full_text` on Unicode whitespace, borrowing each word from `self`.
+    /// `Sized` keeps this out of the vtable: `impl Trait` returns can't be dynamically dispatched.
+    fn words(&self) -> impl Iterator<Item = &str>
+    where
+        Self: Sized,
+    {
+        self.full_text().split_whitespace()
+    }
+
+    /// Alternative summary for types that already implement `Display`: reuse that output.
+    /// The `where` clause makes this method available only on `Display` implementors;
+    /// `Sized` keeps it out of the vtable so `dyn Summary` remains usable.
+    fn summarize_via_display(&self) -> String
+    where
+        Self: fmt::Display + Sized,
+    {
+        self.to_string()
+    }
+
+    /// Localized author label; unknown locales (and "en") fall back to `summarize_author`.
+    fn summarize_author_localized(&self, locale: &str) -> String {
+        let author = self.summarize_author();
+        match locale {
+            "ja" => format!("著者: {}", author),
+            "de" => format!("Autor: {}", author),
+            "fr" => format!("Auteur : {}", author),
+            _ => author,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct NewsArticle {
+    pub headline: String,
+    pub location: String,
+    pub author: String,
+    pub content: String,
+}
+
+impl Summary for NewsArticle {
+    fn summarize_author(&self) -> String {
+        format!("@{}", self.author)
+    }
+    // Uses the default summarize method
+
+    fn full_text(&self) -> &str {
+        &self.content
+    }
+}
+
+#[derive(Clone)]
+pub struct Tweet {
+    pub username: String,
+    pub content: String,
+    pub reply: bool,
+    pub retweet: bool,
+}
+
+impl Summary for Tweet {
+    fn summarize_author(&self) -> String {
+        format!("@{}", self.username)
+    }
+
+    // Override the default summarize method
+    fn summarize(&self) -> String {
+        format!("{}: {}", self.summarize_author(), self.content)
+    }
+
+    fn full_text(&self) -> &str {
+        &self.content
+    }
+}
+
+/// Maximum tweet length, counted in Unicode scalar values (`char`s), not bytes.
+pub const TWEET_MAX_CHARS: usize = 280;
+
+/// Why a tweet could not be created.
+#[derive(Debug, PartialEq)]
+pub enum TweetError {
+    TooLong { chars: usize },
+}
+
+impl fmt::Display for TweetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TweetError::TooLong { chars } => {
+                write!(f, "tweet is {} characters, the limit is {}", chars, TWEET_MAX_CHARS)
+            }
+        }
+    }
+}
+
+impl Tweet {
+    /// Validated constructor: rejects content longer than `TWEET_MAX_CHARS`.
+    pub fn new(username: &str, content: &str, reply: bool, retweet: bool) -> Result<Tweet, TweetError> {
+        let chars = content.chars().count(); // `len()` would count bytes, so emoji would count 4x
+        if chars > TWEET_MAX_CHARS {
+            return Err(TweetError::TooLong { chars });
+        }
+        Ok(Tweet { username: username.to_string(), content: content.to_string(), reply, retweet })
+    }
+
+    /// Characters left before the limit (negative if a struct literal bypassed `new`).
+    pub fn remaining_chars(&self) -> isize {
+        TWEET_MAX_CHARS as isize - self.content.chars().count() as isize
+    }
+}
+
+/// Fluent construction for `Tweet`; `reply` and `retweet` default to false.
+#[derive(Default)]
+pub struct TweetBuilder {
+    username: String,
+    content: String,
+    reply: bool,
+    retweet: bool,
+}
+
+impl TweetBuilder {
+    pub fn new() -> Self {
+        TweetBuilder::default()
+    }
+
+    pub fn username(mut self, username: &str) -> Self {
+        self.username = username.to_string();
+        self
+    }
+
+    pub fn content(mut self, content: &str) -> Self {
+        self.content = content.to_string();
+        self
+    }
+
+    pub fn reply(mut self, reply: bool) -> Self {
+        self.reply = reply;
+        self
+    }
+
+    pub fn retweet(mut self, retweet: bool) -> Self {
+        self.retweet = retweet;
+        self
+    }
+
+    /// Goes through `Tweet::new`, so the `TWEET_MAX_CHARS` limit still applies.
+    pub fn build(self) -> Result<Tweet, TweetError> {
+        Tweet::new(&self.username, &self.content, self.reply, self.retweet)
+    }
+}
+
+/// Fluent construction for `NewsArticle`; unset fields (such as `location`) stay empty.
+#[derive(Default)]
+pub struct NewsArticleBuilder {
+    article: NewsArticle,
+}
+
+impl NewsArticleBuilder {
+    pub fn new() -> Self {
+        NewsArticleBuilder::default()
+    }
+
+    pub fn headline(mut self, headline: &str) -> Self {
+        self.article.headline = headline.to_string();
+        self
+    }
+
+    pub fn location(mut self, location: &str) -> Self {
+        self.article.location = location.to_string();
+        self
+    }
+
+    pub fn author(mut self, author: &str) -> Self {
+        self.article.author = author.to_string();
+        self
+    }
+
+    pub fn content(mut self, content: &str) -> Self {
+        self.article.content = content.to_string();
+        self
+    }
+
+    pub fn build(self) -> NewsArticle {
+        self.article
+    }
+}
+
+// Rectangle already has a Display impl, so its summary simply delegates to it
+impl Summary for Rectangle {
+    fn summarize_author(&self) -> String {
+        String::from("@geometry")
+    }
+
+    fn summarize(&self) -> String {
+        self.summarize_via_display()
+    }
+}
+
+// Function that accepts any type implementing the Summary trait (static dispatch via monomorphization)
+pub fn notify<T: Summary>(item: &T) {
+    print_wrapped("Breaking news!", &item.summarize());
+}
+
+/// What `notify` prints, as a line for a `SectionReporter`.
+fn notify_line<T: Summary>(item: &T) -> String {
+    format_wrapped("Breaking news!", &item.summarize())
+}
+
+/// Returns the longer of two summaries, labelled "first" or "second". Ties go to `a`.
+/// `?Sized` lets callers pass trait objects like `&dyn Summary` as well as concrete types.
+fn summarize_and_compare<T, U>(a: &T, b: &U) -> String
+where
+    T: Summary + ?Sized,
+    U: Summary + ?Sized,
+{
+    let (first, second) = (a.summarize(), b.summarize());
+    if second.len() > first.len() {
+        format!("second is longer: {}", second)
+    } else {
+        format!("first is longer (or tied): {}", first)
+    }
+}
+
+/// Reports each item with both its `Display` and `Debug` representations.
+fn print_all<T>(r: &mut SectionReporter, items: &[T])
+where
+    T: fmt::Display + fmt::Debug,
+{
+    for item in items {
+        r.line(format!("{} / {:?}", item, item));
+    }
+}
+
+/// The item with the longest summary (the earliest one on ties), borrowed from `items`.
+fn largest_summary<'a, T: Summary>(items: &'a [T]) -> Option<&'a T> {
+    let mut best: Option<(&'a T, usize)> = None;
+    for item in items {
+        let len = item.summarize().len();
+        if best.is_none_or(|(_, best_len)| len > best_len) {
+            best = Some((item, len));
+        }
+    }
+    best.map(|(item, _)| item)
+}
+
+// Function accepting a trait object (dynamic dispatch)
+// pub fn notify_dynamic(item: &dyn Summary) {
+//    println!("Breaking news (dynamic)! {}", item.summarize());
+// }
+
+/// Wraps a `Summary` and computes `summarize()` at most once, on first read.
+struct CachedSummary<T: Summary> {
+    inner: T,
+    cache: OnceCell<String>,
+}
+
+impl<T: Summary> CachedSummary<T> {
+    fn new(inner: T) -> Self {
+        CachedSummary { inner, cache: OnceCell::new() }
+    }
+
+    /// The summary, computed on the first call and borrowed from the cache afterwards.
+    fn summary(&self) -> &str {
+        // `OnceCell` allows filling the cache through `&self`
+        self.cache.get_or_init(|| self.inner.summarize())
+    }
+
+    /// Drops the cached summary so the next read recomputes it.
+    fn invalidate(&mut self) {
+        self.cache.take();
+    }
+
+    /// Mutable access to the wrapped item; clears the cache since the summary may change.
+    fn inner_mut(&mut self) -> &mut T {
+        self.invalidate();
+        &mut self.inner
+    }
+}
+
+/// A heterogeneous container that recovers concrete types at runtime via `Any` downcasting.
+///
+/// Reaching for `dyn Any` is usually a design smell: a trait or an enum expresses
+/// "one of these types" with compile-time checking, while downcasting defers type
+/// errors to runtime. It is legitimately useful when the set of types is open-ended
+/// and owned by someone else, e.g. plugin or extension maps keyed by type, or
+/// carrying arbitrary user data through a library that never inspects it.
+struct Registry {
+    items: Vec<Box<dyn Any>>,
+}
+
+impl Registry {
+    fn new() -> Self {
+        Registry { items: Vec::new() }
+    }
+
+    /// Stores any `'static` value, erasing its concrete type.
+    fn insert<T: Any>(&mut self, value: T) {
+        self.items.push(Box::new(value));
+    }
+
+    /// Borrows every stored value whose concrete type is `T`.
+    fn get_all<T: Any>(&self) -> Vec<&T> {
+        self.items.iter().filter_map(|item| item.downcast_ref::<T>()).collect()
+    }
+
+    /// Removes and returns the first stored value of type `T`.
+    fn take_first<T: Any>(&mut self) -> Option<T> {
+        let index = self.items.iter().position(|item| item.is::<T>())?;
+        // `downcast` consumes the box and hands back `Box<T>` on success
+        self.items.remove(index).downcast::<T>().ok().map(|boxed| *boxed)
+    }
+}
+
+/// A `Summary` that can also clone itself behind a `Box` (the "dyn clone" idiom).
+/// `Clone` itself can't be a supertrait of a trait object, since `clone` returns `Self`,
+/// whose size is unknown; `clone_box` returns another box instead.
+pub trait SummaryClone: Summary {
+    fn clone_box(&self) -> Box<dyn SummaryClone>;
+}
+
+// Every cloneable summary gets `clone_box` for free
+impl<T: Summary + Clone + 'static> SummaryClone for T {
+    fn clone_box(&self) -> Box<dyn SummaryClone> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn SummaryClone> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// An ordered collection of summarizable items of mixed types. Items must be `Clone`
+/// so the whole feed can be cloned.
+#[derive(Clone)]
+struct Feed {
+    items: Vec<Box<dyn SummaryClone>>,
+}
+
+/// Why a digest line could not be parsed; `line` is 1-based.
+#[derive(Debug, PartialEq)]
+enum DigestError {
+    MissingTab { line: usize },
+    BadEscape { line: usize },
+}
+
+impl fmt::Display for DigestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DigestError::MissingTab { line } => write!(f, "line {}: expected AUTHOR<tab>SUMMARY", line),
+            DigestError::BadEscape { line } => write!(f, "line {}: invalid escape sequence", line),
+        }
+    }
+}
+
+impl Feed {
+    fn new() -> Self {
+        Feed { items: Vec::new() }
+    }
+
+    fn push(&mut self, item: impl Summary + Clone + 'static) {
+        self.items.push(Box::new(item));
+    }
+
+    fn summarize_all(&self) -> Vec<String> {
+        self.items.iter().map(|item| item.summarize()).collect()
+    }
+
+    /// One `AUTHOR<tab>SUMMARY` line per item. Backslashes, tabs and newlines inside
+    /// fields are escaped as `\\`, `\t` and `\n`, so every line splits on exactly one tab.
+    fn to_digest(&self) -> String {
+        self.items
+            .iter()
+            .map(|item| {
+                format!("{}\t{}\n", escape_digest(&item.summarize_author()), escape_digest(&item.summarize()))
+            })
+            .collect()
+    }
+
+    /// Parses a digest back into `(author, summary)` pairs. The original items cannot be
+    /// rebuilt, since the concrete types behind `dyn Summary` are not recorded.
+    fn from_digest(s: &str) -> Result<Vec<(String, String)>, DigestError> {
+        s.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(i, line)| {
+                let (author, summary) = line.split_once('\t').ok_or(DigestError::MissingTab { line: i + 1 })?;
+                let unescape = |field| unescape_digest(field).ok_or(DigestError::BadEscape { line: i + 1 });
+                Ok((unescape(author)?, unescape(summary)?))
+            })
+            .collect()
+    }
+}
+
+/// The closed set of feed item types, dispatched with a `match` instead of a vtable.
+/// Adding a type means adding a variant, but calls can be inlined and items are stored
+/// inline in the `Vec` rather than behind a `Box` each.
+enum SummaryItem {
+    Tweet(Tweet),
+    Article(NewsArticle),
+}
+
+impl Summary for SummaryItem {
+    fn summarize_author(&self) -> String {
+        match self {
+            SummaryItem::Tweet(tweet) => tweet.summarize_author(),
+            SummaryItem::Article(article) => article.summarize_author(),
+        }
+    }
+
+    // Delegated too, so each variant keeps its own override of the default
+    fn summarize(&self) -> String {
+        match self {
+            SummaryItem::Tweet(tweet) => tweet.summarize(),
+            SummaryItem::Article(article) => article.summarize(),
+        }
+    }
+
+    fn full_text(&self) -> &str {
+        match self {
+            SummaryItem::Tweet(tweet) => tweet.full_text(),
+            SummaryItem::Article(article) => article.full_text(),
+        }
+    }
+}
+
+impl From<Tweet> for SummaryItem {
+    fn from(tweet: Tweet) -> Self {
+        SummaryItem::Tweet(tweet)
+    }
+}
+
+impl From<NewsArticle> for SummaryItem {
+    fn from(article: NewsArticle) -> Self {
+        SummaryItem::Article(article)
+    }
+}
+
+fn summarize_enum_feed(items: &[SummaryItem]) -> Vec<String> {
+    items.iter().map(Summary::summarize).collect()
+}
+
+/// The `dyn Summary` counterpart of `summarize_enum_feed`.
+fn summarize_dyn_feed(items: &[Box<dyn Summary>]) -> Vec<String> {
+    items.iter().map(|item| item.summarize()).collect()
+}
+
+/// Escapes backslashes, tabs, and line breaks so a field fits on one tab-separated line.
+/// `\r` counts as a line break because `str::lines` strips it before a `\n`.
+fn escape_digest(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reverses `escape_digest`; `None` for a dangling or unknown escape.
+fn unescape_digest(field: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => unescaped.push('\\'),
+            't' => unescaped.push('\t'),
+            'n' => unescaped.push('\n'),
+            'r' => unescaped.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(unescaped)
+}
+
+
+// === Generics ===
+
+/// Finds the largest item in a slice.
+/// Works for any type T that implements PartialOrd (for comparison >) and Copy.
+// fn largest<T: PartialOrd + Copy>(list: &[T]) -> T {
+//     let mut largest = list[0];
+//     for &item in list {
+//         if item > largest {
+//             largest = item;
+//         }
+//     }
+//     largest
+// }
+// Version returning a reference (avoids Copy requirement, but needs lifetimes)
+fn largest<T: PartialOrd>(list: &[T]) -> &T {
+     if list.is_empty() {
+        panic!("Cannot find largest in empty list");
+    }
+    let mut largest = &list[0];
+    for item in list.iter() {
+        if item > largest {
+            largest = item;
+        }
+    }
+    largest
+}
+
+/// Finds the largest item according to `cmp`, or `None` for an empty slice.
+/// Ties resolve to the first such element; pass `|a, b| b.cmp(a)` to get the smallest.
+fn largest_by<T, F: Fn(&T, &T) -> Ordering>(list: &[T], cmp: F) -> Option<&T> {
+    let mut iter = list.iter();
+    let mut largest = iter.next()?;
+    for item in iter {
+        if cmp(item, largest) == Ordering::Greater {
+            largest = item;
+        }
+    }
+    Some(largest)
+}
+
+/// Adds up a slice. An empty slice sums to `T::default()`, which is zero for the numeric types.
+fn sum<T: Add<Output = T> + Copy + Default>(list: &[T]) -> T {
+    list.iter().fold(T::default(), |total, &item| total + item)
+}
+
+/// Arithmetic mean, or `None` for an empty slice (rather than `0.0 / 0.0 == NaN`).
+fn mean(list: &[f64]) -> Option<f64> {
+    if list.is_empty() {
+        return None;
+    }
+    Some(sum(list) / list.len() as f64)
+}
+
+/// Moving average: the mean of each run of `window` consecutive values, so the result has
+/// `data.len() - window + 1` entries. Empty if `window` is 0 or longer than `data`.
+fn windows_mean(data: &[f64], window: usize) -> Vec<f64> {
+    if window == 0 {
+        return Vec::new(); // `windows(0)` would panic
+    }
+    data.windows(window).filter_map(mean).collect() // Windows are never empty, so every `mean` is `Some`
+}
+
+/// Multiplication that reports overflow instead of wrapping or panicking.
+trait CheckedMul: Sized {
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+// The integer impls defer to the inherent `checked_mul` (inherent methods win over trait ones)
+macro_rules! impl_checked_mul_int {
+    ($($t:ty),*) => {
+        $(impl CheckedMul for $t {
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                <$t>::checked_mul(self, other)
+            }
+        })*
+    };
+}
+impl_checked_mul_int!(u8, u16, u32, u64, usize, i32, i64);
+
+// Floats never overflow: too-large products become infinity, which is still a value
+impl CheckedMul for f64 {
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+/// `width * height` for any element type, or `None` if the product overflows.
+fn checked_area<T: CheckedMul>(width: T, height: T) -> Option<T> {
+    width.checked_mul(height)
+}
+
+/// A generic Point struct.
+#[derive(Debug, Clone, Copy)] // Copy only when T is Copy
+struct Point<T> {
+    x: T,
+    y: T,
+}
+
+// Implement methods on the generic Point<T>
+impl<T> Point<T> {
+    fn x(&self) -> &T {
+        &self.x
+    }
+    fn y(&self) -> &T {
+        &self.y
+    }
+}
+
+// Hand-written equivalents of `#[derive(PartialEq, PartialOrd)]`: lexicographic by (x, y).
+// `f64` only gets the *partial* traits because NaN compares unequal (and unordered)
+// to everything, itself included, so `Ord`/`Eq` would break their contracts.
+impl<T: PartialEq> PartialEq for Point<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.x == other.x && self.y == other.y
+    }
+}
+
+impl<T: PartialOrd + PartialEq> PartialOrd for Point<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.x.partial_cmp(&other.x) {
+            Some(Ordering::Equal) => self.y.partial_cmp(&other.y),
+            ord => ord, // `None` if either x is NaN
+        }
+    }
+}
+
+/// Total order for floats with every NaN (of either sign) placed after all numbers.
+fn cmp_nan_last(a: f64, b: f64) -> Ordering {
+    // `false < true`, so non-NaN values sort first; `total_cmp` settles the rest
+    a.is_nan().cmp(&b.is_nan()).then_with(|| a.total_cmp(&b))
+}
+
+impl<T: PartialOrd + Copy> Point<T> {
+    /// Clamps each axis independently into the box spanned by `min` and `max`.
+    /// Bounds are ordered per axis first, so a swapped pair (min > max) describes the same box.
+    fn clamp(&self, min: Point<T>, max: Point<T>) -> Point<T> {
+        let clamp_axis = |v: T, a: T, b: T| {
+            let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+            if v < lo {
+                lo
+            } else if v > hi {
+                hi
+            } else {
+                v
+            }
+        };
+        Point { x: clamp_axis(self.x, min.x, max.x), y: clamp_axis(self.y, min.y, max.y) }
+    }
+}
+
+impl Point<f64> {
+    /// The unit vector pointing the same way, or `None` for the zero vector.
+    fn normalized(&self) -> Option<Point<f64>> {
+        let length = self.x.hypot(self.y);
+        if length == 0.0 {
+            return None;
+        }
+        Some(Point { x: self.x / length, y: self.y / length })
+    }
+
+    /// Compares by x only, using a total order so it never fails on NaN.
+    fn total_cmp_by_x(a: &Point<f64>, b: &Point<f64>) -> Ordering {
+        cmp_nan_last(a.x, b.x)
+    }
+}
+
+/// Sorts points by (x, y) without panicking on NaN; NaN coordinates sort last.
+fn sort_points(points: &mut [Point<f64>]) {
+    // `sort_by(|a, b| a.partial_cmp(b).unwrap())` would panic on the first NaN
+    points.sort_by(|a, b| Point::total_cmp_by_x(a, b).then_with(|| cmp_nan_last(a.y, b.y)));
+}
+
+/// A point whose axes may have different types, e.g. a `u64` timestamp paired with an `f64` reading.
+#[derive(Debug, Clone, PartialEq)]
+struct Pair<X, Y> {
+    x: X,
+    y: Y,
+}
+
+impl<X, Y> Pair<X, Y> {
+    fn x(&self) -> &X {
+        &self.x
+    }
+    fn y(&self) -> &Y {
+        &self.y
+    }
+
+    /// Transforms the x axis, possibly changing its type.
+    fn map_x<X2, F: FnOnce(X) -> X2>(self, f: F) -> Pair<X2, Y> {
+        Pair { x: f(self.x), y: self.y }
+    }
+
+    /// Transforms the y axis, possibly changing its type.
+    fn map_y<Y2, F: FnOnce(Y) -> Y2>(self, f: F) -> Pair<X, Y2> {
+        Pair { x: self.x, y: f(self.y) }
+    }
+}
+
+// === Error Handling Functions ===
+
+/// Finds the index of an item in a slice, returning Option<usize>.
+fn find_item(haystack: &[i32], needle: i32) -> Option<usize> {
+    for (index, &item) in haystack.iter().enumerate() {
+        if item == needle {
+            return Some(index); // Found it!
+        }
+    }
+    None // Not found
+}
+
+/// Lazily yields every index where `needle` occurs, without allocating.
+fn matches<'a, T: PartialEq>(haystack: &'a [T], needle: &'a T) -> impl Iterator<Item = usize> + 'a {
+    haystack
+        .iter()
+        .enumerate()
+        .filter(move |(_, item)| *item == needle)
+        .map(|(index, _)| index)
+}
+
+/// Start index of the first occurrence of `needle` as a contiguous run in `haystack`,
+/// like `str::find` for slices. An empty needle matches at index 0.
+fn find_subslice<T: PartialEq>(haystack: &[T], needle: &[T]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0); // `windows(0)` would panic
+    }
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Divides two f64 numbers, returning Result<f64, String>.
+fn divide(numerator: f64, denominator: f64) -> Result<f64, String> {
+    if denominator == 0.0 {
+        Err(String::from("Cannot divide by zero!"))
+    } else {
+        Ok(numerator / denominator)
+    }
+}
+
+/// Why a division (of two numbers, or element-wise over two slices) failed.
+#[derive(Debug, PartialEq)]
+enum DivideError {
+    DivideByZero,
+    LengthMismatch { numerators: usize, denominators: usize },
+    AtIndex { index: usize, reason: String },
+}
+
+impl fmt::Display for DivideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DivideError::DivideByZero => write!(f, "cannot divide by zero"),
+            DivideError::LengthMismatch { numerators, denominators } => {
+                write!(f, "{} numerators but {} denominators", numerators, denominators)
+            }
+            DivideError::AtIndex { index, reason } => write!(f, "index {}: {}", index, reason),
+        }
+    }
+}
+
+/// `divide` with a typed error, for callers that combine it with other error types.
+fn checked_divide(numerator: f64, denominator: f64) -> Result<f64, DivideError> {
+    divide(numerator, denominator).map_err(|_| DivideError::DivideByZero)
+}
+
+/// Divides pairwise with `divide`, short-circuiting on the first failing index.
+fn divide_slices(nums: &[f64], dens: &[f64]) -> Result<Vec<f64>, DivideError> {
+    if nums.len() != dens.len() {
+        return Err(DivideError::LengthMismatch { numerators: nums.len(), denominators: dens.len() });
+    }
+    // Collecting an iterator of Results into a Result stops at the first Err
+    nums.iter()
+        .zip(dens)
+        .enumerate()
+        .map(|(index, (&n, &d))| divide(n, d).map_err(|reason| DivideError::AtIndex { index, reason }))
+        .collect()
+}
+
+/// Everything that can go wrong in the arithmetic below, with each cause kept intact.
+#[derive(Debug, PartialEq)]
+enum MathError {
+    Division(DivideError),
+    NegativeSqrt(f64),
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MathError::Division(e) => write!(f, "division failed: {}", e),
+            MathError::NegativeSqrt(x) => write!(f, "cannot take the square root of {}", x),
+        }
+    }
+}
+
+// Lets `?` lift a `DivideError` into a `MathError` without an explicit `map_err`
+impl From<DivideError> for MathError {
+    fn from(e: DivideError) -> Self {
+        MathError::Division(e)
+    }
+}
+
+/// Square root that errors on negative input instead of returning NaN.
+fn safe_sqrt(x: f64) -> Result<f64, MathError> {
+    if x < 0.0 {
+        Err(MathError::NegativeSqrt(x))
+    } else {
+        Ok(x.sqrt())
+    }
+}
+
+/// `sqrt(a / b)`: one `?` converts a `DivideError` through `From`, the other passes a `MathError` through.
+fn process_sqrt_divide(a: f64, b: f64) -> Result<f64, MathError> {
+    let quotient = checked_divide(a, b)?;
+    safe_sqrt(quotient)
+}
+
+/// Demonstrates propagating errors using the `?` operator.
+fn process_division(num: f64, den: f64, r: &mut SectionReporter) -> Result<f64, String> {
+    let result = divide(num, den)?; // If divide returns Err, this function returns the Err immediately
+    // ... do more processing if needed ...
+    r.line("Division successful, proceeding...");
+    Ok(result * 2.0) // Return Ok wrapping the final value
+}
+
+// `?` also works on Option: a `None` returns `None` from the enclosing function
+
+/// The last character of the first line, or `None` if there is no first line or it is empty.
+fn last_char_of_first_line(text: &str) -> Option<char> {
+    text.lines().next()?.chars().last()
+}
+
+/// The first word longer than `n` bytes. Elision ties the result to `text`, the only
+/// reference input; spelled out it reads `fn<'a>(text: &'a str, n: usize) -> Option<&'a str>`.
+fn first_word_longer_than(text: &str, n: usize) -> Option<&str> {
+    text.split_whitespace().find(|word| word.len() > n)
+}
+
+/// The part of the user's email after the '@', or `None` if there is no '@'.
+fn user_email_domain(user: &User) -> Option<&str> {
+    let (_, domain) = user.email.split_once('@')?;
+    Some(domain)
+}
+
+/// Why one field of a user record is unusable; the innermost layer of `ProfileError`.
+#[derive(Debug)]
+enum FieldError {
+    Missing,
+    BadNumber(ParseIntError),
+    BadBool(ParseBoolError),
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldError::Missing => write!(f, "field is missing"),
+            FieldError::BadNumber(_) => write!(f, "expected a whole number"),
+            FieldError::BadBool(_) => write!(f, "expected true or false"),
+        }
+    }
+}
+
+// `source` exposes the wrapped error instead of repeating its message in `Display`
+impl Error for FieldError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FieldError::Missing => None,
+            FieldError::BadNumber(e) => Some(e),
+            FieldError::BadBool(e) => Some(e),
+        }
+    }
+}
+
+/// A user record failed to parse because of one field.
+#[derive(Debug)]
+struct ParseUserError {
+    field: &'static str,
+    source: FieldError,
+}
+
+impl fmt::Display for ParseUserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid field `{}`", self.field)
+    }
+}
+
+impl Error for ParseUserError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Loading a profile failed while reading the file or while parsing it.
+#[derive(Debug)]
+enum ProfileError {
+    Io { path: String, source: io::Error },
+    Parse { path: String, source: ParseUserError },
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::Io { path, .. } => write!(f, "could not read profile {}", path),
+            ProfileError::Parse { path, .. } => write!(f, "could not parse profile {}", path),
+        }
+    }
+}
+
+impl Error for ProfileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProfileError::Io { source, .. } => Some(source),
+            ProfileError::Parse { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Parses `key=value` lines with `username`, `email`, `sign_in_count` and `active`.
+fn parse_user(text: &str) -> Result<User, ParseUserError> {
+    let fields: HashMap<&str, &str> =
+        text.lines().filter_map(|line| line.split_once('=')).map(|(k, v)| (k.trim(), v.trim())).collect();
+    let get = |field: &'static str| {
+        fields.get(field).copied().ok_or(ParseUserError { field, source: FieldError::Missing })
+    };
+    let sign_in_count = get("sign_in_count")?;
+    let active = get("active")?;
+    Ok(User {
+        username: get("username")?.to_string(),
+        email: get("email")?.to_string(),
+        sign_in_count: sign_in_count
+            .parse()
+            .map_err(|e| ParseUserError { field: "sign_in_count", source: FieldError::BadNumber(e) })?,
+        active: active.parse().map_err(|e| ParseUserError { field: "active", source: FieldError::BadBool(e) })?,
+    })
+}
+
+/// Reads and parses a profile file, wrapping each failure with the path.
+fn load_profile(path: &str) -> Result<User, ProfileError> {
+    let text = fs::read_to_string(path).map_err(|source| ProfileError::Io { path: path.to_string(), source })?;
+    parse_user(&text).map_err(|source| ProfileError::Parse { path: path.to_string(), source })
+}
+
+/// An error annotated with what was being attempted, e.g. "reading users file". The
+/// original error stays reachable through `source()`.
+#[derive(Debug)]
+struct ContextError<E> {
+    context: String,
+    source: E,
+}
+
+impl<E: fmt::Display> fmt::Display for ContextError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.context, self.source)
+    }
+}
+
+impl<E: Error + 'static> Error for ContextError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Adds context to any `Result` whose error is a std `Error`, without a bespoke error enum.
+trait ResultExt<T, E> {
+    fn context(self, msg: &'static str) -> Result<T, ContextError<E>>;
+
+    /// Like `context`, but only builds the message if there is an error to attach it to.
+    fn with_context<F: FnOnce() -> String>(self, f: F) -> Result<T, ContextError<E>>;
+}
+
+impl<T, E: Error> ResultExt<T, E> for Result<T, E> {
+    fn context(self, msg: &'static str) -> Result<T, ContextError<E>> {
+        self.map_err(|source| ContextError { context: msg.to_string(), source })
+    }
+
+    fn with_context<F: FnOnce() -> String>(self, f: F) -> Result<T, ContextError<E>> {
+        self.map_err(|source| ContextError { context: f(), source })
+    }
+}
+
+/// Reads profiles separated by blank lines. Unlike `load_profile`, there is no error enum
+/// to maintain: each step says what it was doing and `?` boxes the result.
+fn load_users(path: &str) -> Result<Vec<User>, Box<dyn Error>> {
+    let text = fs::read_to_string(path).context("reading users file")?;
+    // A profile is a run of non-blank lines. `lines()` also strips `\r\n`, and runs of
+    // blank lines (or trailing ones) just produce empty blocks, which are skipped.
+    let mut blocks: Vec<Vec<&str>> = vec![Vec::new()];
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            blocks.push(Vec::new());
+        } else {
+            blocks.last_mut().unwrap().push(line); // Never empty: starts with one block
+        }
+    }
+    let mut users = Vec::new();
+    for (index, block) in blocks.iter().filter(|block| !block.is_empty()).enumerate() {
+        let user = parse_user(&block.join("\n")).with_context(|| format!("parsing user #{} in {}", index + 1, path))?;
+        users.push(user);
+    }
+    Ok(users)
+}
+
+/// The `Display` text of `err` and of each error in its `source()` chain, outermost first.
+fn error_chain(err: &dyn Error) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut current = Some(err);
+    while let Some(e) = current {
+        chain.push(e.to_string());
+        current = e.source();
+    }
+    chain
+}
+
+/// Like `error_chain`, but drops a cause whose text its parent already ends with. Wrappers
+/// such as `ContextError` print their source after a colon, so it would show up twice.
+fn condensed_error_chain(err: &dyn Error) -> Vec<String> {
+    let chain = error_chain(err);
+    let mut condensed = Vec::new();
+    for (index, message) in chain.iter().enumerate() {
+        if index == 0 || !chain[index - 1].ends_with(message.as_str()) {
+            condensed.push(message.clone());
+        }
+    }
+    condensed
+}
+
+/// Reports an error and its causes, indenting one step per layer. Causes already spelled
+/// out by their parent are skipped, see `condensed_error_chain`.
+fn print_error_chain(r: &mut SectionReporter, err: &dyn Error) {
+    for (depth, message) in condensed_error_chain(err).iter().enumerate() {
+        match depth {
+            0 => r.line(format!("Error: {}", message)),
+            _ => r.line(format!("{}caused by: {}", "  ".repeat(depth), message)),
+        }
+    }
+}
+
+/// Parses the first line as an integer. `?` cannot turn a `None` into an `Err` on its
+/// own, so `ok_or` converts Option -> Result first (`.ok()` goes the other way).
+fn first_line_number(text: &str) -> Result<i64, String> {
+    let line = text.lines().next().ok_or("text is empty")?;
+    line.trim().parse::<i64>().map_err(|e| format!("{:?}: {}", line, e))
+}
+
+
+// === Lifetimes ===
+
+/// Returns the longest of two string slices.
+/// The lifetime 'a annotation ensures the returned reference is valid
+/// for as long as *both* input references are valid.
+fn longest<'a>(x: &'a str, y: &'a str) -> &'a str {
+    if x.len() > y.len() {
+        x
+    } else {
+        y
+    }
+}
+// Note: Often, lifetimes are inferred by the compiler (lifetime elision),
+// but explicit annotation is needed in ambiguous cases like this function.
+
+/// A boxed rectangle formatter whose captures must outlive `'a`.
+type RectFormatter<'a> = Box<dyn Fn(&Rectangle) -> String + 'a>;
+
+/// Formatters that may borrow from their surroundings: `+ 'a` lets each closure
+/// capture references that live at least as long as the registry.
+struct FormatterRegistry<'a> {
+    formatters: Vec<RectFormatter<'a>>,
+}
+
+impl<'a> FormatterRegistry<'a> {
+    fn new() -> Self {
+        FormatterRegistry { formatters: Vec::new() }
+    }
+
+    fn register(&mut self, formatter: impl Fn(&Rectangle) -> String + 'a) {
+        self.formatters.push(Box::new(formatter));
+    }
+
+    /// Runs every formatter, in registration order.
+    fn format_all(&self, r: &Rectangle) -> Vec<String> {
+        self.formatters.iter().map(|f| f(r)).collect()
+    }
+}
+
+/// Formatters that own everything they capture (`+ 'static`), so the registry can
+/// be built in one function and used anywhere afterwards.
+struct OwnedRegistry {
+    formatters: Vec<RectFormatter<'static>>,
+}
+
+impl OwnedRegistry {
+    fn register(&mut self, formatter: impl Fn(&Rectangle) -> String + 'static) {
+        self.formatters.push(Box::new(formatter));
+    }
+
+    fn format_all(&self, r: &Rectangle) -> Vec<String> {
+        self.formatters.iter().map(|f| f(r)).collect()
+    }
+}
+
+/// Builds an `OwnedRegistry`; the `move` closure takes ownership of `unit`, so nothing dangles.
+fn owned_registry(unit: &str) -> OwnedRegistry {
+    let unit = unit.to_string();
+    let mut registry = OwnedRegistry { formatters: Vec::new() };
+    registry.register(move |r| format!("{}x{} {}", r.width, r.height, unit));
+    registry
+}
+
+// Returning a *borrowing* registry from a function does not compile, because the
+// closure would outlive the local it borrows:
+// fn borrowed_registry<'a>() -> FormatterRegistry<'a> {
+//     let prefix = String::from("rect");
+//     let mut registry = FormatterRegistry::new();
+//     registry.register(|r| format!("{}: {}", prefix, r.area())); // Compiler Error E0373: closure may outlive `prefix`
+//     registry
+// }
+
+
+// === Graphs ===
+
+mod graph {
+    use std::collections::{HashMap, HashSet, VecDeque};
+    use std::hash::Hash;
+
+    /// An undirected graph stored as an adjacency list.
+    pub struct Graph<N: Eq + Hash + Clone> {
+        adjacency: HashMap<N, Vec<N>>,
+    }
+
+    impl<N: Eq + Hash + Clone> Graph<N> {
+        pub fn new() -> Self {
+            Graph { adjacency: HashMap::new() }
+        }
+
+        /// Inserts a node without any edges (no-op if it already exists).
+        pub fn add_node(&mut self, n: N) {
+            self.adjacency.entry(n).or_default();
+        }
+
+        /// Adds an undirected edge between `a` and `b`, inserting both nodes if needed.
+        pub fn add_edge(&mut self, a: N, b: N) {
+            self.adjacency.entry(a.clone()).or_default().push(b.clone());
+            self.adjacency.entry(b).or_default().push(a);
+        }
+
+        /// Returns the neighbors of `n` (empty if the node is unknown).
+        pub fn neighbors(&self, n: &N) -> &[N] {
+            self.adjacency.get(n).map(Vec::as_slice).unwrap_or(&[])
+        }
+
+        /// Finds a shortest path (fewest edges) from `from` to `to`, inclusive of both ends.
+        pub fn bfs_shortest_path(&self, from: &N, to: &N) -> Option<Vec<N>> {
+            if !self.adjacency.contains_key(from) || !self.adjacency.contains_key(to) {
+                return None;
+            }
+            // Remember how we reached each node so the path can be rebuilt backwards
+            let mut came_from: HashMap<&N, &N> = HashMap::new();
+            let mut visited: HashSet<&N> = HashSet::from([from]);
+            let mut queue = VecDeque::from([from]);
+
+            while let Some(current) = queue.pop_front() {
+                if current == to {
+                    let mut path = vec![current.clone()];
+                    let mut step = current;
+                    while let Some(&prev) = came_from.get(step) {
+                        path.push(prev.clone());
+                        step = prev;
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                for next in self.neighbors(current) {
+                    if visited.insert(next) {
+                        came_from.insert(next, current);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            None // `to` is unreachable from `from`
+        }
+
+        /// Groups every node into its connected component.
+        pub fn connected_components(&self) -> Vec<Vec<N>> {
+            let mut visited: HashSet<&N> = HashSet::new();
+            let mut components = Vec::new();
+
+            for start in self.adjacency.keys() {
+                if !visited.insert(start) {
+                    continue;
+                }
+                let mut component = Vec::new();
+                let mut queue = VecDeque::from([start]);
+                while let Some(current) = queue.pop_front() {
+                    component.push(current.clone());
+                    for next in self.neighbors(current) {
+                        if visited.insert(next) {
+                            queue.push_back(next);
+                        }
+                    }
+                }
+                components.push(component);
+            }
+            components
+        }
+    }
+}
+
+// === Numeral Conversions ===
+
+mod numerals {
+    use std::fmt;
+
+    /// A number or base fell outside the supported range.
+    #[derive(Debug, PartialEq)]
+    pub struct RangeError {
+        pub value: u64,
+        pub min: u64,
+        pub max: u64,
+    }
+
+    impl fmt::Display for RangeError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{} is out of range {}..={}", self.value, self.min, self.max)
+        }
+    }
+
+    /// Why a string could not be read as a Roman numeral.
+    #[derive(Debug, PartialEq)]
+    pub enum RomanParseError {
+        Empty,
+        InvalidChar(char),
+        /// Valid symbols in a non-standard order or repetition, like "IIII" or "IC".
+        Malformed(String),
+    }
+
+    impl fmt::Display for RomanParseError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                RomanParseError::Empty => write!(f, "empty Roman numeral"),
+                RomanParseError::InvalidChar(c) => write!(f, "invalid Roman numeral character '{}'", c),
+                RomanParseError::Malformed(s) => write!(f, "malformed Roman numeral \"{}\"", s),
+            }
+        }
+    }
+
+    // Largest value first, including the subtractive pairs
+    const SYMBOLS: [(u32, &str); 13] = [
+        (1000, "M"), (900, "CM"), (500, "D"), (400, "CD"),
+        (100, "C"), (90, "XC"), (50, "L"), (40, "XL"),
+        (10, "X"), (9, "IX"), (5, "V"), (4, "IV"), (1, "I"),
+    ];
+
+    /// Converts 1..=3999 to standard Roman numerals.
+    pub fn to_roman(n: u32) -> Result<String, RangeError> {
+        if !(1..=3999).contains(&n) {
+            return Err(RangeError { value: u64::from(n), min: 1, max: 3999 });
+        }
+        let mut remaining = n;
+        let mut out = String::new();
+        for &(value, symbol) in SYMBOLS.iter() {
+            while remaining >= value {
+                out.push_str(symbol);
+                remaining -= value;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Parses standard (uppercase only) Roman numerals, including subtractive notation.
+    /// Lowercase input is rejected as `InvalidChar` rather than silently accepted.
+    pub fn from_roman(s: &str) -> Result<u32, RomanParseError> {
+        if s.is_empty() {
+            return Err(RomanParseError::Empty);
+        }
+        let digit = |c: char| match c {
+            'I' => Ok(1),
+            'V' => Ok(5),
+            'X' => Ok(10),
+            'L' => Ok(50),
+            'C' => Ok(100),
+            'D' => Ok(500),
+            'M' => Ok(1000),
+            other => Err(RomanParseError::InvalidChar(other)),
+        };
+        let values = s.chars().map(digit).collect::<Result<Vec<u32>, _>>()?;
+
+        // A smaller digit before a larger one is subtracted (IV = 4)
+        let mut total = 0;
+        for (i, &v) in values.iter().enumerate() {
+            match values.get(i + 1) {
+                Some(&next) if v < next => total -= v as i64,
+                _ => total += v as i64,
+            }
+        }
+        // Only accept the canonical spelling, which rejects "IIII", "IC", "VV", ...
+        match u32::try_from(total).ok().filter(|&n| to_roman(n).is_ok_and(|r| r == s)) {
+            Some(n) => Ok(n),
+            None => Err(RomanParseError::Malformed(s.to_string())),
+        }
+    }
+
+    /// Formats `n` in any base from 2 to 36, using lowercase letters past 9.
+    pub fn to_base(n: u64, base: u32) -> Result<String, RangeError> {
+        if !(2..=36).contains(&base) {
+            return Err(RangeError { value: u64::from(base), min: 2, max: 36 });
+        }
+        if n == 0 {
+            return Ok(String::from("0"));
+        }
+        let mut digits = Vec::new();
+        let mut remaining = n;
+        while remaining > 0 {
+            let d = (remaining % u64::from(base)) as u32;
+            digits.push(std::char::from_digit(d, base).unwrap()); // d < base, so always valid
+            remaining /= u64::from(base);
+        }
+        Ok(digits.iter().rev().collect())
+    }
+}
+
+// === Text Wrapping ===
+
+mod textwrap {
+    /// Greedily wraps `text` into lines of at most `width` characters.
+    ///
+    /// Breaks on whitespace, hard-splits words longer than `width`, and keeps
+    /// paragraph breaks as a single empty line. Width is counted in `char`s, a
+    /// simplification: wide glyphs (CJK, emoji) occupy two terminal columns but
+    /// count as one here. A `width` of 0 is treated as 1.
+    pub fn wrap(text: &str, width: usize) -> Vec<String> {
+        let width = width.max(1);
+        let mut lines = Vec::new();
+        let mut current = String::new();
+        let mut current_len = 0; // In chars, not bytes
+
+        for raw_line in text.lines() {
+            if raw_line.trim().is_empty() {
+                // Paragraph break: flush, then a single blank line (never leading or doubled)
+                if !current.is_empty() {
+                    lines.push(std::mem::take(&mut current));
+                    current_len = 0;
+                }
+                if lines.last().is_some_and(|l: &String| !l.is_empty()) {
+                    lines.push(String::new());
+                }
+                continue;
+            }
+            for word in raw_line.split_whitespace() {
+                let word_len = word.chars().count();
+                if current_len > 0 && current_len + 1 + word_len <= width {
+                    current.push(' ');
+                    current.push_str(word);
+                    current_len += 1 + word_len;
+                    continue;
+                }
+                if !current.is_empty() {
+                    lines.push(std::mem::take(&mut current));
+                }
+                // Start a new line; words longer than the width are split into chunks
+                let chars: Vec<char> = word.chars().collect();
+                let mut chunks = chars.chunks(width).peekable();
+                while let Some(chunk) = chunks.next() {
+                    if chunks.peek().is_some() {
+                        lines.push(chunk.iter().collect());
+                    } else {
+                        current = chunk.iter().collect();
+                        current_len = chunk.len();
+                    }
+                }
+            }
+        }
+        if !current.is_empty() {
+            lines.push(current);
+        }
+        if lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop(); // No trailing paragraph break
+        }
+        lines
+    }
+
+    /// Prefixes every non-empty line of `text` with `prefix`.
+    pub fn indent(text: &str, prefix: &str) -> String {
+        text.lines()
+            .map(|line| if line.is_empty() { String::new() } else { format!("{}{}", prefix, line) })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Column width used for wrapped section output.
+const WRAP_WIDTH: usize = 72;
+
+/// Prints `heading` followed by `body` wrapped to `WRAP_WIDTH` and indented two spaces.
+fn print_wrapped(heading: &str, body: &str) {
+    println!("{}", format_wrapped(heading, body));
+}
+
+/// Formats what `print_wrapped` prints, for callers that collect lines instead.
+fn format_wrapped(heading: &str, body: &str) -> String {
+    let wrapped = textwrap::wrap(body, WRAP_WIDTH - 2).join("\n");
+    format!("{}\n{}", heading, textwrap::indent(&wrapped, "  "))
+}
+
+// === Concurrency Helpers ===
+
+/// How many worker threads to use when the caller asks for "auto": the parallelism
+/// the OS reports, or 1 where it cannot be determined.
+fn default_thread_count() -> usize {
+    thread_count_or_fallback(thread::available_parallelism())
+}
+
+// Split out so the fallback can be exercised without an unusual platform
+fn thread_count_or_fallback(detected: io::Result<NonZeroUsize>) -> usize {
+    detected.map(NonZeroUsize::get).unwrap_or(1)
+}
+
+/// Maps `f` over `items` on up to `threads` scoped worker threads, keeping input order.
+/// `threads == 0` means `default_thread_count()`. With one thread (or fewer than two
+/// items) it simply maps sequentially.
+fn par_map<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Send + Sync,
+    R: Send,
+    F: Fn(&T) -> R + Send + Sync,
+{
+    let threads = if threads == 0 { default_thread_count() } else { threads };
+    if threads == 1 || items.len() < 2 {
+        return items.iter().map(f).collect();
+    }
+    // One contiguous chunk per thread; joining the chunks in order preserves the original order
+    let chunk_size = items.len().div_ceil(threads);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+/// Maps `f` over owned `items` on `num_threads` scoped threads (0 means
+/// `default_thread_count()`), returning results in input order.
+///
+/// Unlike `par_map`'s fixed chunks, workers pull one item at a time from a shared queue,
+/// so a few slow items don't leave the other threads idle. Results therefore arrive out
+/// of order; each carries its input index and they are sorted back at the end.
+fn parallel_map<T: Send, U: Send, F: Fn(T) -> U + Sync>(items: Vec<T>, num_threads: usize, f: F) -> Vec<U> {
+    let len = items.len();
+    let threads = if num_threads == 0 { default_thread_count() } else { num_threads };
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        let (queue, f) = (&queue, &f);
+        for _ in 0..threads.min(len) {
+            let sender = sender.clone();
+            scope.spawn(move || loop {
+                // The guard is a temporary, so the lock is released before `f` runs
+                let next = queue.lock().unwrap().next();
+                match next {
+                    Some((index, item)) => sender.send((index, f(item))).unwrap(),
+                    None => break,
+                }
+            });
+        }
+    });
+    drop(sender); // Close the channel so collecting below ends
+    let mut tagged: Vec<(usize, U)> = receiver.into_iter().collect();
+    tagged.sort_unstable_by_key(|&(index, _)| index);
+    tagged.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Returned by `join_timeout` when the thread is still running at the deadline.
+#[derive(Debug, PartialEq)]
+struct JoinTimeout {
+    waited: Duration,
+}
+
+impl fmt::Display for JoinTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "thread did not finish within {:?}", self.waited)
+    }
+}
+
+/// Joins `handle` if the thread finishes within `timeout`, polling `is_finished`.
+/// Rust threads can't be cancelled: on timeout the handle is dropped and the thread keeps
+/// running detached. A panic in the thread is re-raised here, as `join().unwrap()` would.
+fn join_timeout<T>(handle: thread::JoinHandle<T>, timeout: Duration) -> Result<T, JoinTimeout> {
+    let start = Instant::now();
+    while !handle.is_finished() {
+        if start.elapsed() >= timeout {
+            return Err(JoinTimeout { waited: timeout });
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    // Finished, so this join returns immediately
+    match handle.join() {
+        Ok(value) => Ok(value),
+        Err(panic) => std::panic::resume_unwind(panic),
+    }
+}
+
+/// Why a computation didn't deliver its result.
+#[derive(Debug, PartialEq)]
+enum TimeoutError {
+    /// The deadline passed first; the worker may still be running.
+    TimedOut { after: Duration },
+    /// The worker panicked (dropping its sender) before sending a result.
+    WorkerPanicked,
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutError::TimedOut { after } => write!(f, "timed out after {:?}", after),
+            TimeoutError::WorkerPanicked => write!(f, "failed: the worker panicked"),
+        }
+    }
+}
+
+/// Runs `f` on a new thread and waits at most `timeout` for its result over a channel.
+/// On timeout the thread is *not* stopped: it keeps running detached in the background,
+/// and its eventual `send` simply fails because the receiver is gone.
+fn run_with_timeout<T, F>(f: F, timeout: Duration) -> Result<T, TimeoutError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(f()); // Err only if the caller already gave up
+    });
+    receiver.recv_timeout(timeout).map_err(|e| match e {
+        mpsc::RecvTimeoutError::Timeout => TimeoutError::TimedOut { after: timeout },
+        // A panicking worker drops the sender without sending
+        mpsc::RecvTimeoutError::Disconnected => TimeoutError::WorkerPanicked,
+    })
+}
+
+/// Like `run_with_timeout`, but hands `f` a flag that is set on timeout so it can stop early.
+/// Cancellation is cooperative: `f` must check the flag itself. A worker that panicked is
+/// already gone, so its flag is left alone.
+fn run_with_timeout_cancellable<T, F>(f: F, timeout: Duration) -> Result<T, TimeoutError>
+where
+    T: Send + 'static,
+    F: FnOnce(Arc<AtomicBool>) -> T + Send + 'static,
+{
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&cancelled);
+    let result = run_with_timeout(move || f(flag), timeout);
+    if let Err(TimeoutError::TimedOut { .. }) = result {
+        cancelled.store(true, AtomicOrdering::Relaxed);
+    }
+    result
+}
+
+// === Shared Mutability: Rc<RefCell<T>> vs Arc<Mutex<T>> ===
+
+/// A line item in a shopping cart.
+#[derive(Debug, Clone)]
+struct Item {
+    name: String,
+    price_cents: u64,
+}
+
+/// Single-threaded shared cart: `Rc` for shared ownership, `RefCell` for runtime-checked borrows.
+/// Cloning the cart clones the handle, not the items.
+#[derive(Clone)]
+struct LocalCart {
+    items: Rc<RefCell<Vec<Item>>>,
+}
+
+impl LocalCart {
+    fn new() -> Self {
+        LocalCart { items: Rc::new(RefCell::new(Vec::new())) }
+    }
+
+    fn add_item(&self, item: Item) {
+        self.items.borrow_mut().push(item); // Panics if already borrowed elsewhere
+    }
+
+    fn total_cents(&self) -> u64 {
+        self.items.borrow().iter().map(|item| item.price_cents).sum()
+    }
+
+    fn item_count(&self) -> usize {
+        self.items.borrow().len()
+    }
+}
+
+/// Multi-threaded shared cart: `Arc` for atomic reference counting, `Mutex` for exclusive access.
+#[derive(Clone)]
+struct ConcurrentCart {
+    items: Arc<Mutex<Vec<Item>>>,
+}
+
+impl ConcurrentCart {
+    fn new() -> Self {
+        ConcurrentCart { items: Arc::new(Mutex::new(Vec::new())) }
+    }
+
+    fn add_item(&self, item: Item) {
+        self.items.lock().unwrap().push(item); // Blocks until the lock is free
+    }
+
+    fn total_cents(&self) -> u64 {
+        self.items.lock().unwrap().iter().map(|item| item.price_cents).sum()
+    }
+
+    fn item_count(&self) -> usize {
+        self.items.lock().unwrap().len()
+    }
+}
+
+/// Fills both cart flavors with the same items and reports their totals.
+fn shared_cart_demo() {
+    let groceries = [("apple", 99), ("bread", 349), ("cheese", 1250)];
+
+    // Two closures in one thread share the same local cart
+    let local = LocalCart::new();
+    let (handle_a, handle_b) = (local.clone(), local.clone());
+    let add_fruit = move |name: &str, cents| handle_a.add_item(Item { name: name.to_string(), price_cents: cents });
+    let add_other = move |name: &str, cents| handle_b.add_item(Item { name: name.to_string(), price_cents: cents });
+    add_fruit(groceries[0].0, groceries[0].1);
+    add_other(groceries[1].0, groceries[1].1);
+    add_other(groceries[2].0, groceries[2].1);
+    println!("LocalCart: {} items, {} cents", local.item_count(), local.total_cents());
+    let names: Vec<String> = local.items.borrow().iter().map(|item| item.name.clone()).collect();
+    println!("LocalCart contents: {}", names.join(", "));
+
+    // Rc is not Send, so the local cart cannot cross a thread boundary:
+    // thread::spawn(move || local.add_item(...)); // <-- COMPILER ERROR E0277: `Rc<RefCell<Vec<Item>>>` cannot be sent between threads safely (required by the `Send` bound on `thread::spawn`)
+
+    let concurrent = ConcurrentCart::new();
+    let handles: Vec<_> = groceries
+        .iter()
+        .map(|&(name, cents)| {
+            let cart = concurrent.clone();
+            thread::spawn(move || cart.add_item(Item { name: name.to_string(), price_cents: cents }))
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    println!("ConcurrentCart: {} items, {} cents", concurrent.item_count(), concurrent.total_cents());
+}
+
+// === Configuration from the Environment ===
+
+mod config {
+    use std::env;
+    use std::fmt;
+
+    /// Settings that tweak the showcase output, read from `SHOWCASE_*` environment variables.
+    #[derive(Debug, PartialEq)]
+    pub struct ShowcaseConfig {
+        pub verbose: bool,
+        /// Maximum number of items printed per collection. Must be at least 1:
+        /// `0` is rejected because it would silently hide whole sections.
+        pub max_items: usize,
+        pub name: String,
+    }
+
+    /// A variable was set but its value could not be parsed.
+    #[derive(Debug, PartialEq)]
+    pub struct ConfigError {
+        pub variable: String,
+        pub value: String,
+        pub expected: &'static str,
+    }
+
+    impl fmt::Display for ConfigError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}={:?} is invalid, expected {}", self.variable, self.value, self.expected)
+        }
+    }
+
+    impl Default for ShowcaseConfig {
+        fn default() -> Self {
+            ShowcaseConfig { verbose: false, max_items: 10, name: String::from("Rust Feature Showcase") }
+        }
+    }
+
+    impl ShowcaseConfig {
+        /// Loads the configuration from the real process environment.
+        pub fn from_env() -> Result<Self, ConfigError> {
+            Self::from_iter(env::vars())
+        }
+
+        /// Loads the configuration from arbitrary `(key, value)` pairs; unset keys keep their defaults.
+        pub fn from_iter(vars: impl Iterator<Item = (String, String)>) -> Result<Self, ConfigError> {
+            let mut config = ShowcaseConfig::default();
+            for (key, value) in vars {
+                let invalid = |expected| ConfigError { variable: key.clone(), value: value.clone(), expected };
+                match key.as_str() {
+                    "SHOWCASE_VERBOSE" => {
+                        config.verbose = match value.as_str() {
+                            "1" | "true" => true,
+                            "0" | "false" => false,
+                            _ => return Err(invalid("true, false, 1 or 0")),
+                        }
+                    }
+                    "SHOWCASE_MAX_ITEMS" => {
+                        config.max_items = match value.parse::<usize>() {
+                            Ok(n) if n > 0 => n,
+                            _ => return Err(invalid("a positive integer")),
+                        }
+                    }
+                    "SHOWCASE_NAME" => config.name = value,
+                    _ => {} // Ignore unrelated variables
+                }
+            }
+            Ok(config)
+        }
+    }
+}
+
+// === Operator Overloading with Units ===
+
+/// A length in millimeters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Millimeters(u32);
+
+/// A length in meters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Meters(u32);
+
+// `Add` is declared as `trait Add<Rhs = Self>`, so this impl uses the default: mm + mm
+impl Add for Millimeters {
+    type Output = Millimeters;
+
+    fn add(self, other: Millimeters) -> Millimeters {
+        Millimeters(self.0 + other.0)
+    }
+}
+
+// Overriding the `Rhs` default type parameter: mm + m
+impl Add<Meters> for Millimeters {
+    type Output = Millimeters;
+
+    fn add(self, other: Meters) -> Millimeters {
+        Millimeters(self.0 + other.0 * 1000)
+    }
+}
+
+// `Sum` lets `.sum()` produce a `Millimeters` directly; an empty iterator sums to zero
+impl Sum for Millimeters {
+    fn sum<I: Iterator<Item = Millimeters>>(iter: I) -> Millimeters {
+        iter.fold(Millimeters(0), |total, part| total + part)
+    }
+}
+
+impl fmt::Display for Millimeters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} mm", self.0)
+    }
+}
+
+impl fmt::Display for Meters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} m", self.0)
+    }
+}
+
+/// Adds up the lengths of all parts.
+fn total_length(parts: &[Millimeters]) -> Millimeters {
+    parts.iter().copied().sum()
+}
+
+/// A percentage, always within 0.0–100.0.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+struct Percent(f64);
+
+impl Percent {
+    /// Clamps `v` into 0.0–100.0; NaN becomes 0.0.
+    fn new(v: f64) -> Percent {
+        if v.is_nan() { Percent(0.0) } else { Percent(v.clamp(0.0, 100.0)) }
+    }
+
+    /// The same quantity as a fraction in 0.0–1.0.
+    fn as_fraction(&self) -> f64 {
+        self.0 / 100.0
+    }
+}
+
+// Arithmetic goes through `Percent::new`, so results stay clamped
+impl Add for Percent {
+    type Output = Percent;
+
+    fn add(self, other: Percent) -> Percent {
+        Percent::new(self.0 + other.0)
+    }
+}
+
+impl Sub for Percent {
+    type Output = Percent;
+
+    fn sub(self, other: Percent) -> Percent {
+        Percent::new(self.0 - other.0)
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1}%", self.0)
+    }
+}
+
+/// A value fell outside a newtype's valid range (or was NaN).
+#[derive(Debug, PartialEq)]
+struct OutOfRange {
+    value: f64,
+    min: f64,
+    max: f64,
+}
+
+impl fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is outside {}..={}", self.value, self.min, self.max)
+    }
+}
+
+/// A whole-number percentage in 0..=100. Unlike `Percent`, construction is checked
+/// rather than clamped, so bad input is reported instead of silently corrected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Percentage(u8);
+
+impl Percentage {
+    fn new(value: u8) -> Result<Percentage, OutOfRange> {
+        if value <= 100 {
+            Ok(Percentage(value))
+        } else {
+            Err(OutOfRange { value: f64::from(value), min: 0.0, max: 100.0 })
+        }
+    }
+
+    /// Skips the range check, e.g. for constants. Contract: `value` must be at most 100;
+    /// passing more is not memory-unsafe, but breaks every method that assumes the range.
+    const fn new_unchecked(value: u8) -> Percentage {
+        Percentage(value)
+    }
+
+    /// Adds, stopping at 100%.
+    fn saturating_add(self, other: Percentage) -> Percentage {
+        Percentage(self.0.saturating_add(other.0).min(100))
+    }
+
+    /// Subtracts, stopping at 0%.
+    fn saturating_sub(self, other: Percentage) -> Percentage {
+        Percentage(self.0.saturating_sub(other.0))
+    }
+
+    fn as_probability(self) -> Probability {
+        Probability(f64::from(self.0) / 100.0)
+    }
+}
+
+impl fmt::Display for Percentage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+/// A probability in 0.0..=1.0; never NaN.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+struct Probability(f64);
+
+impl Probability {
+    /// Rejects NaN as well as values outside 0.0..=1.0.
+    fn new(value: f64) -> Result<Probability, OutOfRange> {
+        // `contains` is false for NaN, since every comparison with NaN is false
+        if (0.0..=1.0).contains(&value) {
+            Ok(Probability(value))
+        } else {
+            Err(OutOfRange { value, min: 0.0, max: 1.0 })
+        }
+    }
+
+    /// Skips the range check. Contract: `value` must be in 0.0..=1.0 and not NaN.
+    const fn new_unchecked(value: f64) -> Probability {
+        Probability(value)
+    }
+
+    /// Adds, stopping at 1.0.
+    fn saturating_add(self, other: Probability) -> Probability {
+        Probability((self.0 + other.0).min(1.0))
+    }
+
+    /// Rounds to the nearest whole percentage.
+    fn as_percentage(self) -> Percentage {
+        Percentage((self.0 * 100.0).round() as u8) // In 0.0..=100.0, so the cast is exact
+    }
+}
+
+impl fmt::Display for Probability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.3}", self.0)
+    }
+}
+
+/// Certainty, usable in `const` contexts thanks to `new_unchecked` being a `const fn`.
+const CERTAIN: Probability = Probability::new_unchecked(1.0);
+const HALF: Percentage = Percentage::new_unchecked(50);
+
+// === Number Theory ===
+
+mod numbers {
+    /// Fibonacci numbers starting at 0; the iterator ends instead of overflowing `u128`.
+    pub fn fibonacci() -> impl Iterator<Item = u128> {
+        let (mut current, mut next) = (Some(0u128), Some(1u128));
+        std::iter::from_fn(move || {
+            let out = current?;
+            // Once the sum overflows, `next` becomes None and the sequence ends after `current`
+            let after = next.and_then(|n| out.checked_add(n));
+            current = next;
+            next = after;
+            Some(out)
+        })
+    }
+
+    /// All primes in ascending order, found by trial division.
+    pub fn primes() -> impl Iterator<Item = u64> {
+        (2..=u64::MAX).filter(|&n| is_prime(n))
+    }
+
+    /// Trial division by 2, 3, and numbers of the form 6k ± 1 up to sqrt(n).
+    pub fn is_prime(n: u64) -> bool {
+        if n < 4 {
+            return n >= 2; // 0 and 1 are not prime; 2 and 3 are
+        }
+        if n.is_multiple_of(2) || n.is_multiple_of(3) {
+            return false;
+        }
+        let mut i: u64 = 5;
+        // `i <= n / i` avoids overflowing `i * i` near u64::MAX
+        while i <= n / i {
+            if n.is_multiple_of(i) || n.is_multiple_of(i + 2) {
+                return false;
+            }
+            i += 6;
+        }
+        true
+    }
+
+    /// Steps for `n` to reach 1 under the Collatz map, or `None` for 0 or if `3n + 1` overflows.
+    pub fn collatz_steps(n: u64) -> Option<u64> {
+        if n == 0 {
+            return None; // 0 never reaches 1
+        }
+        let (mut value, mut steps) = (n, 0);
+        while value != 1 {
+            value = if value.is_multiple_of(2) { value / 2 } else { value.checked_mul(3)?.checked_add(1)? };
+            steps += 1;
+        }
+        Some(steps)
+    }
+}
+
+// === Collection Helpers ===
+
+/// A cell position, usable as a `HashMap` key. `Point<T>` is not, since `f64` has no
+/// `Eq`/`Hash`; this concrete integer type derives them instead.
+///
+/// Keys must not change while stored: the map files a key under its hash at insertion,
+/// so a key type with interior mutability (say, a `Cell<i32>` field) that is mutated
+/// afterwards is left in the wrong bucket and silently becomes unfindable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct GridCoord {
+    x: i32,
+    y: i32,
+}
+
+/// A sparse world: only occupied cells are stored.
+struct WorldMap {
+    cells: HashMap<GridCoord, Rectangle>,
+}
+
+impl WorldMap {
+    fn new() -> Self {
+        WorldMap { cells: HashMap::new() }
+    }
+
+    /// Puts `rect` at `coord`, returning the rectangle it replaced, if any.
+    fn place(&mut self, coord: GridCoord, rect: Rectangle) -> Option<Rectangle> {
+        self.cells.insert(coord, rect) // `insert` hands back the previous value
+    }
+
+    /// The occupied cells among the 4 orthogonal neighbors, in N, E, S, W order.
+    /// Neighbors past the edge of the `i32` range simply do not exist.
+    fn occupied_neighbors(&self, coord: GridCoord) -> Vec<GridCoord> {
+        let GridCoord { x, y } = coord;
+        let candidates = [
+            (Some(x), y.checked_sub(1)),
+            (x.checked_add(1), Some(y)),
+            (Some(x), y.checked_add(1)),
+            (x.checked_sub(1), Some(y)),
+        ];
+        candidates
+            .into_iter()
+            .filter_map(|(x, y)| Some(GridCoord { x: x?, y: y? }))
+            .filter(|neighbor| self.cells.contains_key(neighbor))
+            .collect()
+    }
+}
+
+/// Names for a few well-known colors, keyed by `Color` itself.
+fn color_names() -> HashMap<Color, &'static str> {
+    HashMap::from([
+        (Color(0, 0, 0), "black"),
+        (Color(255, 255, 255), "white"),
+        (Color(255, 0, 0), "red"),
+        (Color(0, 255, 0), "lime"),
+        (Color(0, 0, 255), "blue"),
+    ])
+}
+
+/// Employees by department, after the book's exercise. Names are case-sensitive
+/// ("Sally" and "sally" are different people) and adding someone twice is a no-op.
+struct Company {
+    departments: HashMap<String, Vec<String>>,
+}
+
+/// A scripted instruction for `Company`.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Add { employee: String, department: String },
+    Remove { employee: String, department: String },
+    List { department: String },
+    ListAll,
+}
+
+impl Company {
+    fn new() -> Self {
+        Company { departments: HashMap::new() }
+    }
+
+    fn add(&mut self, department: &str, employee: &str) {
+        let staff = self.departments.entry(department.to_string()).or_default();
+        if !staff.iter().any(|e| e == employee) {
+            staff.push(employee.to_string());
+        }
+    }
+
+    /// Employees of one department, alphabetically; empty for an unknown department.
+    fn employees_in(&self, department: &str) -> Vec<&str> {
+        let mut staff: Vec<&str> =
+            self.departments.get(department).into_iter().flatten().map(String::as_str).collect();
+        staff.sort_unstable();
+        staff
+    }
+
+    /// Every department (alphabetically) with its employees (alphabetically).
+    fn all_by_department(&self) -> Vec<(&str, Vec<&str>)> {
+        let mut all: Vec<(&str, Vec<&str>)> =
+            self.departments.keys().map(|department| (department.as_str(), self.employees_in(department))).collect();
+        all.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        all
+    }
+
+    /// Returns whether the employee was there to remove. A department left empty is dropped.
+    fn remove(&mut self, department: &str, employee: &str) -> bool {
+        let Some(staff) = self.departments.get_mut(department) else {
+            return false;
+        };
+        let before = staff.len();
+        staff.retain(|e| e != employee);
+        let removed = staff.len() < before;
+        if staff.is_empty() {
+            self.departments.remove(department);
+        }
+        removed
+    }
+
+    /// Carries out a parsed command, returning a line describing the outcome.
+    fn execute(&mut self, command: Command) -> String {
+        match command {
+            Command::Add { employee, department } => {
+                self.add(&department, &employee);
+                format!("Added {} to {}", employee, department)
+            }
+            Command::Remove { employee, department } => {
+                if self.remove(&department, &employee) {
+                    format!("Removed {} from {}", employee, department)
+                } else {
+                    format!("{} is not in {}", employee, department)
+                }
+            }
+            Command::List { department } => format!("{}: {:?}", department, self.employees_in(&department)),
+            Command::ListAll => format!("{:?}", self.all_by_department()),
+        }
+    }
+}
+
+/// Parses "Add NAME to DEPT", "Remove NAME from DEPT", "List DEPT" and "List all".
+/// Keywords are case-sensitive; names and departments may contain spaces.
+fn parse_command(line: &str) -> Option<Command> {
+    let (verb, rest) = line.trim().split_once(' ')?;
+    let rest = rest.trim();
+    match verb {
+        "Add" => {
+            let (employee, department) = rest.split_once(" to ")?;
+            Some(Command::Add { employee: employee.trim().to_string(), department: department.trim().to_string() })
+        }
+        "Remove" => {
+            let (employee, department) = rest.split_once(" from ")?;
+            Some(Command::Remove { employee: employee.trim().to_string(), department: department.trim().to_string() })
+        }
+        "List" if rest == "all" => Some(Command::ListAll),
+        "List" if !rest.is_empty() => Some(Command::List { department: rest.to_string() }),
+        _ => None,
+    }
+}
+
+/// Drops inactive users in place with `retain`, keeping the rest in order.
+/// Returns how many were dropped.
+fn retain_active(users: &mut Vec<User>) -> usize {
+    let before = users.len();
+    users.retain(|u| u.active);
+    before - users.len()
+}
+
+/// Moves the inactive users out of `users` and returns them. Both vectors keep the
+/// original relative order; `drain(..)` empties `users` without giving up its buffer.
+fn deactivate_and_collect(users: &mut Vec<User>) -> Vec<User> {
+    let (active, inactive): (Vec<User>, Vec<User>) = users.drain(..).partition(|u| u.active);
+    users.extend(active);
+    inactive
+}
+
+/// Removes and returns up to `n` elements from the front; asking for more than
+/// `len` takes everything. What remains shifts down to index 0.
+fn take_first_n(v: &mut Vec<i32>, n: usize) -> Vec<i32> {
+    v.drain(..n.min(v.len())).collect()
+}
+
+/// Removes `v[index]` both ways for comparison, returning `(remove, swap_remove)` results.
+/// `remove` shifts every later element left: order kept, O(n). `swap_remove` moves the
+/// last element into the hole: order lost, O(1).
+fn compare_removals(v: &[i32], index: usize) -> (Vec<i32>, Vec<i32>) {
+    let (mut shifted, mut swapped) = (v.to_vec(), v.to_vec());
+    shifted.remove(index);
+    swapped.swap_remove(index);
+    (shifted, swapped)
+}
+
+/// Splits off everything from `at` (clamped to `len`) with `split_off`; `v` keeps the head.
+fn split_tail(v: &mut Vec<i32>, at: usize) -> Vec<i32> {
+    v.split_off(at.min(v.len()))
+}
+
+/// Collapses runs of consecutive users on the same email domain to the first of each run.
+/// Like `dedup`, `dedup_by_key` only compares neighbors, so non-adjacent repeats survive.
+fn collapse_domain_runs(users: &mut Vec<User>) {
+    users.dedup_by_key(|u| u.domain().map(str::to_string));
+}
+
+/// Scores kept in a `BTreeMap`, which stays sorted by key and so answers ordered and
+/// range queries directly. Among equal scores, whoever reached the score first ranks first.
+struct Leaderboard {
+    by_score: BTreeMap<u32, Vec<String>>,
+}
+
+impl Leaderboard {
+    fn new() -> Self {
+        Leaderboard { by_score: BTreeMap::new() }
+    }
+
+    /// Sets `name`'s score, replacing any previous one. Recording the score `name` already
+    /// has changes nothing, so it keeps its place among equal scores.
+    fn record(&mut self, name: &str, score: u32) {
+        if self.by_score.get(&score).is_some_and(|names| names.iter().any(|n| n == name)) {
+            return;
+        }
+        self.by_score.retain(|_, names| {
+            names.retain(|n| n != name);
+            !names.is_empty()
+        });
+        self.by_score.entry(score).or_default().push(name.to_string());
+    }
+
+    // Highest score first; ties in recording order
+    fn ranked(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.by_score
+            .iter()
+            .rev()
+            .flat_map(|(&score, names)| names.iter().map(move |name| (name.as_str(), score)))
+    }
+
+    /// The best `n` entries (fewer if the board is smaller).
+    fn top_n(&self, n: usize) -> Vec<(&str, u32)> {
+        self.ranked().take(n).collect()
+    }
+
+    /// Entries scoring within `lo..=hi`, highest first; empty when `lo > hi`.
+    fn scores_between(&self, lo: u32, hi: u32) -> Vec<(&str, u32)> {
+        if lo > hi {
+            return Vec::new(); // `range` panics on a reversed range
+        }
+        self.by_score
+            .range(lo..=hi)
+            .rev()
+            .flat_map(|(&score, names)| names.iter().map(move |name| (name.as_str(), score)))
+            .collect()
+    }
+
+    /// 1-based position of `name` in the ranking, or `None` if it has no score.
+    fn rank_of(&self, name: &str) -> Option<usize> {
+        self.ranked().position(|(n, _)| n == name).map(|index| index + 1)
+    }
+}
+
+/// The user ordering used by `sort_users` and `top_users`: active users first, then by
+/// sign-in count (highest first), then by username (A to Z) to break remaining ties.
+fn user_order(a: &User, b: &User) -> Ordering {
+    b.active
+        .cmp(&a.active) // `true > false`, so comparing b to a puts active users first
+        .then_with(|| b.sign_in_count.cmp(&a.sign_in_count))
+        .then_with(|| a.username.cmp(&b.username))
+}
+
+/// Sorts by `user_order`. `sort_by` is stable, but with username as the final key only
+/// users identical in all three keys could tie, so stability is not relied on.
+fn sort_users(users: &mut [User]) {
+    users.sort_by(user_order);
+}
+
+/// The first `n` users by `user_order`, without sorting everyone: `select_nth_unstable_by`
+/// partitions around the n-th user in O(len) on average, and only those n are sorted.
+fn top_users(users: &[User], n: usize) -> Vec<&User> {
+    if n == 0 {
+        return Vec::new(); // `n - 1` below would underflow
+    }
+    let mut refs: Vec<&User> = users.iter().collect();
+    if n < refs.len() {
+        refs.select_nth_unstable_by(n - 1, |a, b| user_order(a, b));
+        refs.truncate(n);
+    }
+    refs.sort_by(|a, b| user_order(a, b));
+    refs
+}
+
+/// Largest area first. `Reverse` flips a key's ordering, so no custom comparator is needed.
+fn sort_rects_by_area_desc(rects: &mut [Rectangle]) {
+    rects.sort_by_key(|r| Reverse(r.area()));
+}
+
+/// Buckets users by email domain, keeping input order within each bucket.
+/// Users whose email has no '@' are skipped rather than grouped under a placeholder key.
+fn group_by_domain(users: &[User]) -> HashMap<String, Vec<&User>> {
+    let mut groups: HashMap<String, Vec<&User>> = HashMap::new();
+    for user in users {
+        if let Some(domain) = user.domain() {
+            groups.entry(domain.to_string()).or_default().push(user);
+        }
+    }
+    groups
+}
+
+/// Groups items by a key; within each group items keep their original relative order.
+fn group_by_key<T, K: Hash + Eq, F: Fn(&T) -> K>(items: Vec<T>, key: F) -> HashMap<K, Vec<T>> {
+    let mut groups: HashMap<K, Vec<T>> = HashMap::new();
+    for item in items {
+        groups.entry(key(&item)).or_default().push(item);
+    }
+    groups
+}
+
+/// Counts how many items map to each key.
+fn count_by_key<T, K: Hash + Eq, F: Fn(&T) -> K>(items: &[T], key: F) -> HashMap<K, usize> {
+    let mut counts = HashMap::new();
+    for item in items {
+        *counts.entry(key(item)).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// A memo table keyed by pairs, counting cache hits and misses.
+struct SparseTable<K1, K2, V> {
+    values: HashMap<(K1, K2), V>,
+    hits: u64,
+    misses: u64,
+}
+
+// Written by hand: `#[derive(Default)]` would needlessly require `K1, K2, V: Default`
+impl<K1, K2, V> Default for SparseTable<K1, K2, V> {
+    fn default() -> Self {
+        SparseTable { values: HashMap::new(), hits: 0, misses: 0 }
+    }
+}
+
+impl<K1: Hash + Eq, K2: Hash + Eq, V> SparseTable<K1, K2, V> {
+    /// Returns the cached value for `(a, b)`, computing it with `f` only on the first request.
+    fn get_or_compute(&mut self, a: K1, b: K2, f: impl FnOnce(&K1, &K2) -> V) -> &V {
+        match self.values.entry((a, b)) {
+            Entry::Occupied(entry) => {
+                self.hits += 1;
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => {
+                self.misses += 1;
+                let (a, b) = entry.key();
+                let value = f(a, b);
+                entry.insert(value)
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    fn misses(&self) -> u64 {
+        self.misses
+    }
+}
+
+// Builds a pre-filled table; for duplicate keys the last value wins, as with `HashMap`
+impl<K1: Hash + Eq, K2: Hash + Eq, V> FromIterator<((K1, K2), V)> for SparseTable<K1, K2, V> {
+    fn from_iter<I: IntoIterator<Item = ((K1, K2), V)>>(iter: I) -> Self {
+        SparseTable { values: iter.into_iter().collect(), hits: 0, misses: 0 }
+    }
+}
+
+/// Greatest common divisor by Euclid's algorithm.
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Adds `delta` to a team's score, starting new teams at `delta`.
+fn upsert_score(scores: &mut HashMap<String, i32>, team: &str, delta: i32) {
+    scores
+        .entry(team.to_string())
+        .and_modify(|score| *score += delta)
+        .or_insert(delta);
+}
+
+// === Streaming Parsing ===
+
+mod records {
+    use std::fmt;
+    use std::io::BufRead;
+
+    /// One parsed `name,score,tag1;tag2` line.
+    #[derive(Debug, PartialEq)]
+    pub struct Record {
+        pub name: String,
+        pub score: i32,
+        pub tags: Vec<String>,
+    }
+
+    /// Why a line could not be parsed.
+    #[derive(Debug, PartialEq)]
+    pub enum RecordErrorKind {
+        MissingField(&'static str),
+        BadInteger(String),
+        Io(String),
+    }
+
+    /// A parse failure tagged with its 1-based line number.
+    #[derive(Debug, PartialEq)]
+    pub struct RecordError {
+        pub line: usize,
+        pub kind: RecordErrorKind,
+    }
+
+    impl fmt::Display for RecordError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match &self.kind {
+                RecordErrorKind::MissingField(field) => write!(f, "line {}: missing field '{}'", self.line, field),
+                RecordErrorKind::BadInteger(text) => write!(f, "line {}: '{}' is not an integer", self.line, text),
+                RecordErrorKind::Io(message) => write!(f, "line {}: read error: {}", self.line, message),
+            }
+        }
+    }
+
+    /// Lazily parses records line by line; a bad line yields an `Err` and parsing continues.
+    /// Blank lines and lines starting with `#` are skipped. The tags field is optional.
+    pub fn parse_records<R: BufRead>(reader: R) -> impl Iterator<Item = Result<Record, RecordError>> {
+        reader
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line))
+            .filter(|(_, line)| match line {
+                Ok(text) => !text.trim().is_empty() && !text.trim_start().starts_with('#'),
+                Err(_) => true, // Surface I/O errors rather than hiding them
+            })
+            .map(|(number, line)| {
+                let error = |kind| RecordError { line: number, kind };
+                let text = line.map_err(|e| error(RecordErrorKind::Io(e.to_string())))?;
+                parse_line(&text).map_err(error)
+            })
+    }
+
+    fn parse_line(text: &str) -> Result<Record, RecordErrorKind> {
+        let mut fields = text.splitn(3, ',').map(str::trim);
+        let name = fields.next().filter(|n| !n.is_empty()).ok_or(RecordErrorKind::MissingField("name"))?;
+        let score_text = fields.next().filter(|s| !s.is_empty()).ok_or(RecordErrorKind::MissingField("score"))?;
+        let score = score_text
+            .parse::<i32>()
+            .map_err(|_| RecordErrorKind::BadInteger(score_text.to_string()))?;
+        let tags = fields
+            .next()
+            .map(|tags| tags.split(';').map(str::trim).filter(|t| !t.is_empty()).map(String::from).collect())
+            .unwrap_or_default();
+        Ok(Record { name: name.to_string(), score, tags })
+    }
+}
+
+// === Interior Mutability: Cell vs RefCell vs Mutex ===
+
+mod counters {
+    use std::cell::{BorrowMutError, Cell, RefCell};
+    use std::sync::Mutex;
+
+    /// A counter that can be bumped through a shared reference (`&self`, not `&mut self`).
+    pub trait Counter {
+        fn increment(&self);
+        fn value(&self) -> u64;
+    }
+
+    /// `Cell`: zero-overhead, but only moves values in and out (good for Copy types). Not Sync.
+    pub struct CellCounter {
+        count: Cell<u64>,
+    }
+
+    /// `RefCell`: hands out real references, checked at runtime. Needed for the `Vec`,
+    /// which can't be copied out of a `Cell` just to push onto it. Not Sync.
+    pub struct RefCellCounter {
+        count: RefCell<u64>,
+        history: RefCell<Vec<u64>>,
+    }
+
+    /// `Mutex`: like `RefCell` but blocks instead of panicking, and is safe to share across threads.
+    pub struct MutexCounter {
+        count: Mutex<u64>,
+    }
+
+    impl CellCounter {
+        pub fn new() -> Self {
+            CellCounter { count: Cell::new(0) }
+        }
+    }
+
+    impl RefCellCounter {
+        pub fn new() -> Self {
+            RefCellCounter { count: RefCell::new(0), history: RefCell::new(Vec::new()) }
+        }
+
+        /// Every value the counter has passed through, oldest first.
+        pub fn history(&self) -> Vec<u64> {
+            self.history.borrow().clone()
+        }
+
+        /// Holds a mutable borrow of the count and asks for a second one. `borrow_mut` would
+        /// panic here; `try_borrow_mut` reports the conflict as an error instead.
+        pub fn try_double_borrow(&self) -> Result<(), BorrowMutError> {
+            let _first = self.count.borrow_mut();
+            self.count.try_borrow_mut().map(|_| ())
+        }
+    }
+
+    impl MutexCounter {
+        pub fn new() -> Self {
+            MutexCounter { count: Mutex::new(0) }
+        }
+    }
+
+    impl Counter for CellCounter {
+        fn increment(&self) {
+            self.count.set(self.count.get() + 1);
+        }
+        fn value(&self) -> u64 {
+            self.count.get()
+        }
+    }
+
+    impl Counter for RefCellCounter {
+        fn increment(&self) {
+            let mut count = self.count.borrow_mut();
+            *count += 1;
+            self.history.borrow_mut().push(*count); // Remember every value we passed through
+        }
+        fn value(&self) -> u64 {
+            *self.count.borrow()
+        }
+    }
+
+    impl Counter for MutexCounter {
+        fn increment(&self) {
+            *self.count.lock().unwrap() += 1;
+        }
+        fn value(&self) -> u64 {
+            *self.count.lock().unwrap()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        // Here rather than in the file's main test module because it needs the private field
+        #[test]
+        #[should_panic(expected = "already borrowed")]
+        fn second_borrow_mut_panics() {
+            let counter = RefCellCounter::new();
+            let _first = counter.count.borrow_mut();
+            let _second = counter.count.borrow_mut();
+        }
+    }
+}
+
+// === Formatting ===
+
+/// Demonstrates format specifiers: radix, width, alignment, precision, sign, and pretty Debug.
+fn formatting_demo(r: &mut SectionReporter) {
+    let n = 202;
+    r.line(format!("binary {:b}, octal {:o}, hex {:x}, HEX {:X}, with prefix {:#x}", n, n, n, n, n));
+    r.line(format!("zero-padded width 8, precision 3: {:08.3}", std::f64::consts::E));
+    r.line(format!("[{:>10}] [{:^10}] [{:<10}]", "right", "center", "left"));
+    r.line(format!("[{:*^10}] custom fill character", "fill"));
+    r.line(format!("always signed: {:+} {:+}", 7, -7));
+    r.line(format!("scientific: {:e} {:E}", 1234.5, 0.00042));
+    let width = 6;
+    r.line(format!("width from a variable: [{:>width$}]", 42));
+    let user = User {
+        username: String::from("pretty"),
+        email: String::from("pretty@example.com"),
+        sign_in_count: 2,
+        active: true,
+    };
+    r.line(format!("{:#?}", user)); // Multi-line pretty Debug output
+}
+
+/// Renders rows as an aligned text table; every column is as wide as its widest cell.
+/// Widths count `char`s, matching how `format!` pads.
+fn format_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            let len = cell.chars().count();
+            match widths.get_mut(i) {
+                Some(width) => *width = (*width).max(len),
+                None => widths.push(len), // Rows may be wider than the header
+            }
+        }
+    }
+    let render = |cells: Vec<&str>| {
+        let padded: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(i, &width)| format!("{:<width$}", cells.get(i).copied().unwrap_or(""), width = width))
+            .collect();
+        padded.join(" | ").trim_end().to_string()
+    };
+
+    let mut lines = vec![render(headers.to_vec())];
+    lines.push(widths.iter().map(|&w| "-".repeat(w)).collect::<Vec<_>>().join("-+-"));
+    for row in rows {
+        lines.push(render(row.iter().map(String::as_str).collect()));
+    }
+    lines.join("\n")
+}
+
+// === Resilience: Circuit Breaker ===
+
+/// Where a `CircuitBreaker` is in its Closed -> Open -> HalfOpen cycle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BreakerState {
+    /// Calls pass through; counts consecutive failures.
+    Closed { failures: u32 },
+    /// Calls are rejected until the cooldown since `since` has elapsed.
+    Open { since: Instant },
+    /// One trial call decides whether to close again or re-open.
+    HalfOpen,
+}
+
+impl fmt::Display for BreakerState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreakerState::Closed { failures } => write!(f, "closed, {} consecutive failures", failures),
+            BreakerState::Open { .. } => write!(f, "open"),
+            BreakerState::HalfOpen => write!(f, "half-open"),
+        }
+    }
+}
+
+/// Error from a call made through a `CircuitBreaker`.
+#[derive(Debug, PartialEq)]
+enum BreakerError<E> {
+    /// Rejected without running the operation.
+    Open,
+    /// The operation ran and failed with its own error.
+    Inner(E),
+}
+
+impl<E: fmt::Display> fmt::Display for BreakerError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreakerError::Open => write!(f, "circuit open, call rejected"),
+            BreakerError::Inner(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Stops calling a failing operation for a while after too many consecutive failures.
+struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: BreakerState,
+    /// Clock source; injectable so tests can move time without sleeping.
+    now: Box<dyn FnMut() -> Instant>,
+}
+
+impl CircuitBreaker {
+    fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self::with_clock(failure_threshold, cooldown, Box::new(Instant::now))
+    }
+
+    /// Like `new`, but reads the time from `now`. A threshold of 0 behaves like 1.
+    fn with_clock(failure_threshold: u32, cooldown: Duration, now: Box<dyn FnMut() -> Instant>) -> Self {
+        CircuitBreaker {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: BreakerState::Closed { failures: 0 },
+            now,
+        }
+    }
+
+    fn state(&self) -> BreakerState {
+        self.state
+    }
+
+    /// Runs `op` unless the circuit is open, updating the state from its outcome.
+    fn call<F, T, E>(&mut self, op: F) -> Result<T, BreakerError<E>>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        if let BreakerState::Open { since } = self.state {
+            if (self.now)().duration_since(since) < self.cooldown {
+                return Err(BreakerError::Open);
+            }
+            self.state = BreakerState::HalfOpen; // Cooldown over: allow one trial call
+        }
+
+        match op() {
+            Ok(value) => {
+                self.state = BreakerState::Closed { failures: 0 };
+                Ok(value)
+            }
+            Err(e) => {
+                self.state = match self.state {
+                    BreakerState::Closed { failures } if failures + 1 < self.failure_threshold => {
+                        BreakerState::Closed { failures: failures + 1 }
+                    }
+                    // Threshold reached, or the half-open trial failed
+                    _ => BreakerState::Open { since: (self.now)() },
+                };
+                Err(BreakerError::Inner(e))
+            }
+        }
+    }
+}
+
+// === ASCII Rendering ===
+
+/// Something that can draw itself as multi-line text.
+trait Render {
+    fn render(&self) -> String;
+}
+
+/// Default scale for rectangles: one `#` per 5x5 block of units.
+const RENDER_CELL_SIZE: u32 = 5;
+
+impl Rectangle {
+    /// Draws the rectangle as a block of `#`, one character per `cell_size` units (rounded up).
+    /// A rectangle with zero width or height renders as an empty string.
+    fn render_scaled(&self, cell_size: u32) -> String {
+        let cell_size = cell_size.max(1);
+        let (cols, rows) = (self.width.div_ceil(cell_size), self.height.div_ceil(cell_size));
+        if cols == 0 || rows == 0 {
+            return String::new();
+        }
+        vec!["#".repeat(cols as usize); rows as usize].join("\n")
+    }
+}
+
+impl Render for Rectangle {
+    fn render(&self) -> String {
+        self.render_scaled(RENDER_CELL_SIZE)
+    }
+}
+
+impl Render for Color {
+    fn render(&self) -> String {
+        format!("[###] {}", self)
+    }
+}
+
+/// Places renders next to each other, top-aligned, separated by two spaces.
+fn render_side_by_side(items: &[&dyn Render]) -> String {
+    let blocks: Vec<Vec<String>> = items.iter().map(|item| item.render().lines().map(String::from).collect()).collect();
+    let widths: Vec<usize> = blocks
+        .iter()
+        .map(|lines| lines.iter().map(|l| l.chars().count()).max().unwrap_or(0))
+        .collect();
+    let height = blocks.iter().map(Vec::len).max().unwrap_or(0);
+
+    (0..height)
+        .map(|row| {
+            let cells: Vec<String> = blocks
+                .iter()
+                .zip(&widths)
+                .map(|(lines, &width)| format!("{:<width$}", lines.get(row).map_or("", String::as_str), width = width))
+                .collect();
+            cells.join("  ").trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// === Observers with Weak References ===
+
+/// An observer callback; subjects only hold `Weak` pointers to it.
+type Observer<T> = dyn Fn(&T);
+
+/// Broadcasts events to observers without keeping them alive.
+/// Holding `Rc` here would leak observers (and could form cycles); `Weak` lets them drop freely.
+struct Subject<T> {
+    observers: Vec<Weak<Observer<T>>>,
+}
+
+impl<T> Subject<T> {
+    fn new() -> Self {
+        Subject { observers: Vec::new() }
+    }
+
+    /// Registers an observer; the caller keeps ownership through its `Rc`.
+    fn subscribe(&mut self, observer: &Rc<Observer<T>>) {
+        self.observers.push(Rc::downgrade(observer));
+    }
+
+    /// Calls every live observer in subscription order, pruning dropped ones.
+    /// Returns how many observers were notified.
+    fn notify(&mut self, event: &T) -> usize {
+        // `upgrade` yields None once the last `Rc` is gone
+        self.observers.retain(|weak| match weak.upgrade() {
+            Some(observer) => {
+                observer(event);
+                true
+            }
+            None => false,
+        });
+        self.observers.len()
+    }
+
+    fn observer_count(&self) -> usize {
+        self.observers.len()
+    }
+}
+
+// === Bits and Bitflags ===
+
+/// Unix-style `rwx` permission bits packed into a byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Permissions(u8);
+
+/// Why a string or byte could not be turned into `Permissions`.
+#[derive(Debug, PartialEq)]
+enum PermParseError {
+    WrongLength(usize),
+    InvalidChar { position: usize, found: char },
+    UnknownBits(u8),
+}
+
+impl fmt::Display for PermParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PermParseError::WrongLength(len) => write!(f, "expected 3 characters like \"rw-\", got {}", len),
+            PermParseError::InvalidChar { position, found } => {
+                write!(f, "unexpected '{}' at position {}", found, position)
+            }
+            PermParseError::UnknownBits(bits) => write!(f, "unknown permission bits {:#010b}", bits),
+        }
+    }
+}
+
+impl Permissions {
+    const NONE: Permissions = Permissions(0);
+    const READ: Permissions = Permissions(0b100);
+    const WRITE: Permissions = Permissions(0b010);
+    const EXECUTE: Permissions = Permissions(0b001);
+    const ALL: Permissions = Permissions(0b111);
+
+    /// Whether every bit set in `other` is also set in `self`.
+    fn contains(&self, other: Permissions) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    fn insert(&mut self, other: Permissions) {
+        self.0 |= other.0;
+    }
+
+    fn remove(&mut self, other: Permissions) {
+        self.0 &= !other.0;
+    }
+
+    /// Checked conversion from raw bits; anything outside `rwx` is rejected.
+    fn from_bits(bits: u8) -> Result<Permissions, PermParseError> {
+        if bits & !Permissions::ALL.0 != 0 {
+            return Err(PermParseError::UnknownBits(bits));
+        }
+        Ok(Permissions(bits))
+    }
+
+    /// Parses the `Display` form, e.g. `"r-x"`; the inverse of `to_string`.
+    fn parse(s: &str) -> Result<Permissions, PermParseError> {
+        let chars: Vec<char> = s.chars().collect();
+        if chars.len() != 3 {
+            return Err(PermParseError::WrongLength(chars.len()));
+        }
+        let slots = [('r', Permissions::READ), ('w', Permissions::WRITE), ('x', Permissions::EXECUTE)];
+        let mut perms = Permissions::NONE;
+        for (position, (&found, (letter, flag))) in chars.iter().zip(slots).enumerate() {
+            match found {
+                c if c == letter => perms.insert(flag),
+                '-' => {}
+                _ => return Err(PermParseError::InvalidChar { position, found }),
+            }
+        }
+        Ok(perms)
+    }
+}
+
+// The bit operators work on the raw byte and keep unknown high bits as they are;
+// only `Not` masks its result to the known `rwx` bits so `!READ` means "write + execute".
+impl BitOr for Permissions {
+    type Output = Permissions;
+
+    fn bitor(self, other: Permissions) -> Permissions {
+        Permissions(self.0 | other.0)
+    }
+}
+
+impl BitAnd for Permissions {
+    type Output = Permissions;
+
+    fn bitand(self, other: Permissions) -> Permissions {
+        Permissions(self.0 & other.0)
+    }
+}
+
+impl BitOrAssign for Permissions {
+    fn bitor_assign(&mut self, other: Permissions) {
+        self.0 |= other.0;
+    }
+}
+
+impl Not for Permissions {
+    type Output = Permissions;
+
+    fn not(self) -> Permissions {
+        Permissions(!self.0 & Permissions::ALL.0)
+    }
+}
+
+impl fmt::Display for Permissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = |p: Permissions, c: char| if self.contains(p) { c } else { '-' };
+        write!(f, "{}{}{}", flag(Permissions::READ, 'r'), flag(Permissions::WRITE, 'w'), flag(Permissions::EXECUTE, 'x'))
+    }
+}
+
+// === Snapshot Verification ===
+
+mod diffing {
+    /// One line of a line-based diff.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum DiffLine {
+        Same(String),
+        Added(String),
+        Removed(String),
+    }
+
+    /// Diffs two texts line by line using a longest-common-subsequence table.
+    /// A changed line shows up as a `Removed` followed by an `Added`.
+    pub fn diff_lines(a: &str, b: &str) -> Vec<DiffLine> {
+        let (old, new): (Vec<&str>, Vec<&str>) = (a.lines().collect(), b.lines().collect());
+        // lcs[i][j] = length of the LCS of old[i..] and new[j..]
+        let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                lcs[i][j] = if old[i] == new[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        let mut diff = Vec::new();
+        while i < old.len() && j < new.len() {
+            if old[i] == new[j] {
+                diff.push(DiffLine::Same(old[i].to_string()));
+                i += 1;
+                j += 1;
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                diff.push(DiffLine::Removed(old[i].to_string()));
+                i += 1;
+            } else {
+                diff.push(DiffLine::Added(new[j].to_string()));
+                j += 1;
+            }
+        }
+        diff.extend(old[i..].iter().map(|line| DiffLine::Removed(line.to_string())));
+        diff.extend(new[j..].iter().map(|line| DiffLine::Added(line.to_string())));
+        diff
+    }
+
+    /// Renders a diff with unified-style ` `, `-`, and `+` line prefixes.
+    pub fn render(diff: &[DiffLine]) -> String {
+        diff.iter()
+            .map(|line| match line {
+                DiffLine::Same(text) => format!(" {}", text),
+                DiffLine::Removed(text) => format!("-{}", text),
+                DiffLine::Added(text) => format!("+{}", text),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Expected output of each reported section, normalized with `normalize_output`.
+const SNAPSHOTS: &[(&str, &str)] = &[
+    ("Basic Syntax", include_str!("snapshots/basic_syntax.txt")),
+    ("Control Flow", include_str!("snapshots/control_flow.txt")),
+    ("Functions", include_str!("snapshots/functions.txt")),
+    ("Ownership & Borrowing", include_str!("snapshots/ownership_borrowing.txt")),
+    ("Structs", include_str!("snapshots/structs.txt")),
+    ("Enums", include_str!("snapshots/enums.txt")),
+    ("Methods", include_str!("snapshots/methods.txt")),
+    ("Traits", include_str!("snapshots/traits.txt")),
+    ("Generics", include_str!("snapshots/generics.txt")),
+    ("Error Handling", include_str!("snapshots/error_handling.txt")),
+    ("Collections", include_str!("snapshots/collections.txt")),
+    ("Strings", include_str!("snapshots/strings.txt")),
+    ("Modules", include_str!("snapshots/modules.txt")),
+    ("Macros", include_str!("snapshots/macros.txt")),
+    ("Lifetimes", include_str!("snapshots/lifetimes.txt")),
+    ("Closures", include_str!("snapshots/closures.txt")),
+    ("Graphs (BFS)", include_str!("snapshots/graphs_bfs.txt")),
+    ("Numeral Conversions", include_str!("snapshots/numeral_conversions.txt")),
+    ("Measurements", include_str!("snapshots/measurements.txt")),
+    ("Number Theory", include_str!("snapshots/number_theory.txt")),
+    ("Grouping", include_str!("snapshots/grouping.txt")),
+    ("Streaming Parsing", include_str!("snapshots/streaming_parsing.txt")),
+    ("Interior Mutability", include_str!("snapshots/interior_mutability.txt")),
+    ("Formatting", include_str!("snapshots/formatting.txt")),
+    ("Circuit Breaker", include_str!("snapshots/circuit_breaker.txt")),
+    ("Observers", include_str!("snapshots/observers.txt")),
+    ("Bitflags", include_str!("snapshots/bitflags.txt")),
+    ("Memoization", include_str!("snapshots/memoization.txt")),
+    ("Trait Object Lifetimes", include_str!("snapshots/trait_object_lifetimes.txt")),
+    ("Feed Digest", include_str!("snapshots/feed_digest.txt")),
+    ("Input Validation", include_str!("snapshots/input_validation.txt")),
+    ("Log Pipeline", include_str!("snapshots/log_pipeline.txt")),
+    ("Dispatch Comparison", include_str!("snapshots/dispatch_comparison.txt")),
+    ("Custom Hash Keys", include_str!("snapshots/custom_hash_keys.txt")),
+    ("Company Directory", include_str!("snapshots/company_directory.txt")),
+    ("Vec Manipulation", include_str!("snapshots/vec_manipulation.txt")),
+    ("String Interning", include_str!("snapshots/string_interning.txt")),
+    ("std::mem", include_str!("snapshots/std_mem.txt")),
+    ("Percentages and Probabilities", include_str!("snapshots/percentages_and_probabilities.txt")),
+    ("Parallel Map", include_str!("snapshots/parallel_map.txt")),
+    ("Leaderboard", include_str!("snapshots/leaderboard.txt")),
+    ("Custom Iterators", include_str!("snapshots/custom_iterators.txt")),
+    ("Templates", include_str!("snapshots/templates.txt")),
+    ("Sorting", include_str!("snapshots/sorting.txt")),
+    ("Shelf Packing", include_str!("snapshots/shelf_packing.txt")),
+    ("Object Pools", include_str!("snapshots/object_pools.txt")),
+    ("Text Editing", include_str!("snapshots/text_editing.txt")),
+    ("Units with PhantomData", include_str!("snapshots/units_with_phantomdata.txt")),
+    ("Unwind Safety", include_str!("snapshots/unwind_safety.txt")),
+    ("Generational Arenas", include_str!("snapshots/generational_arenas.txt")),
+];
+
+/// Sections `--verify` skips, and why. Their threads print while they run, so there is no
+/// report to compare; every `Output::Printed` section must be listed here.
+const UNVERIFIED: &[(&str, &str)] = &[
+    ("Concurrency (Basic Threads)", "spawned threads print as they run, in no fixed order"),
+    ("Progress Bars", "the bar is drawn from worker threads while they run"),
+];
+
+/// Replaces run-dependent values (elapsed durations such as `14.2µs`) with placeholders.
+fn normalize_output(text: &str) -> String {
+    let is_duration = |token: &str| {
+        let number_end = token.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(token.len());
+        number_end > 0
+            && token.starts_with(|c: char| c.is_ascii_digit())
+            && ["ns", "µs", "us", "ms", "s"].contains(&&token[number_end..])
+    };
+    text.lines()
+        .map(|line| {
+            line.split(' ')
+                .map(|token| if is_duration(token) { "<duration>" } else { token })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        + if text.ends_with('\n') { "\n" } else { "" }
+}
+
+/// Renders every section in registry order and compares each with its snapshot, printing
+/// a diff per mismatch. Sections in `UNVERIFIED` are listed as skipped, with the reason.
+/// Returns the process exit code: 0 if everything matched, 1 otherwise.
+fn verify_snapshots() -> i32 {
+    // Default settings, so the environment cannot change what is compared
+    let mut showcase = Showcase { config: config::ShowcaseConfig::default(), data: showcase_data() };
+    let mut checked = 0;
+    let mut mismatched = Vec::new();
+
+    for section in registry() {
+        let report = match section.output {
+            Output::Reported(report) => report,
+            Output::Printed(_) => {
+                match UNVERIFIED.iter().find(|(name, _)| *name == section.name) {
+                    Some((_, reason)) => println!("skipped  {} ({})", section.name, reason),
+                    None => {
+                        println!("UNLISTED {} (prints directly but is not in UNVERIFIED)", section.name);
+                        mismatched.push(section.name);
+                    }
+                }
+                continue;
+            }
+        };
+        let mut reporter = SectionReporter::new();
+        report(&mut reporter, &mut showcase);
+        let actual = normalize_output(&reporter.finish().to_string());
+        checked += 1;
+        match SNAPSHOTS.iter().find(|(name, _)| *name == section.name) {
+            Some((_, expected)) if *expected == actual => println!("ok       {}", section.name),
+            Some((_, expected)) => {
+                println!("MISMATCH {}", section.name);
+                println!("{}", diffing::render(&diffing::diff_lines(expected, &actual)));
+                mismatched.push(section.name);
+            }
+            None => {
+                println!("MISSING  {} (no snapshot)", section.name);
+                mismatched.push(section.name);
+            }
+        }
+    }
+    if mismatched.is_empty() {
+        println!("All {} checked sections match their snapshots.", checked);
+        0
+    } else {
+        println!("Sections out of date: {}", mismatched.join(", "));
+        1
+    }
+}
+
+// === Input Validation ===
+
+mod validation {
+    use super::Color;
+    use std::fmt;
+    use std::num::IntErrorKind;
+    use std::str::FromStr;
+
+    /// Oldest age `parse_age` accepts.
+    pub const MAX_AGE: u8 = 150;
+
+    /// Why an age could not be parsed.
+    #[derive(Debug, PartialEq)]
+    pub enum AgeError {
+        Empty,
+        NotANumber { original: String },
+        Negative,
+        OutOfRange { value: i64, max: u8 },
+    }
+
+    impl fmt::Display for AgeError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                AgeError::Empty => write!(f, "age is empty"),
+                AgeError::NotANumber { original } => write!(f, "\"{}\" is not a whole number", original),
+                AgeError::Negative => write!(f, "age cannot be negative"),
+                AgeError::OutOfRange { value, max } => write!(f, "age {} is above the maximum of {}", value, max),
+            }
+        }
+    }
+
+    /// Parses an age in 0..=`MAX_AGE`, ignoring surrounding whitespace.
+    /// Only plain decimal is accepted, so "0x1F" is `NotANumber`.
+    pub fn parse_age(input: &str) -> Result<u8, AgeError> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return Err(AgeError::Empty);
+        }
+        // Matching on the parse result (and the error kind) gives each failure its own variant
+        let value = match i64::from_str(trimmed) {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(match e.kind() {
+                    IntErrorKind::PosOverflow => AgeError::OutOfRange { value: i64::MAX, max: MAX_AGE },
+                    IntErrorKind::NegOverflow => AgeError::Negative,
+                    _ => AgeError::NotANumber { original: trimmed.to_string() },
+                });
+            }
+        };
+        match value {
+            v if v < 0 => Err(AgeError::Negative),
+            v if v > i64::from(MAX_AGE) => Err(AgeError::OutOfRange { value: v, max: MAX_AGE }),
+            v => Ok(v as u8), // 0..=MAX_AGE always fits
+        }
+    }
+
+    /// Why a color could not be parsed.
+    #[derive(Debug, PartialEq)]
+    pub enum RgbError {
+        Empty,
+        /// The comma form needs exactly three components.
+        ComponentCount { found: usize },
+        NotANumber { original: String },
+        OutOfRange { value: i64 },
+        /// The `#` form needs exactly six hex digits.
+        InvalidHex { original: String },
+    }
+
+    impl fmt::Display for RgbError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                RgbError::Empty => write!(f, "color is empty"),
+                RgbError::ComponentCount { found } => write!(f, "expected 3 components, found {}", found),
+                RgbError::NotANumber { original } => write!(f, "\"{}\" is not a whole number", original),
+                RgbError::OutOfRange { value } => write!(f, "component {} is outside 0..=255", value),
+                RgbError::InvalidHex { original } => write!(f, "\"{}\" is not a #RRGGBB color", original),
+            }
+        }
+    }
+
+    /// Parses `"255,128,0"` or `"#FF8000"` (either letter case), ignoring surrounding whitespace.
+    pub fn parse_rgb(input: &str) -> Result<Color, RgbError> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return Err(RgbError::Empty);
+        }
+        if let Some(hex) = trimmed.strip_prefix('#') {
+            // Checked up front: `from_str_radix` would also accept a leading '+'
+            if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(RgbError::InvalidHex { original: trimmed.to_string() });
+            }
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap(); // Validated above
+            return Ok(Color(channel(0), channel(2), channel(4)));
+        }
+        let parts: Vec<&str> = trimmed.split(',').map(str::trim).collect();
+        if parts.len() != 3 {
+            return Err(RgbError::ComponentCount { found: parts.len() });
+        }
+        let mut channels = [0u8; 3];
+        for (channel, part) in channels.iter_mut().zip(&parts) {
+            let value = i64::from_str(part).map_err(|_| RgbError::NotANumber { original: part.to_string() })?;
+            *channel = u8::try_from(value).map_err(|_| RgbError::OutOfRange { value })?;
+        }
+        Ok(Color(channels[0], channels[1], channels[2]))
+    }
+}
+
+// === Pipelines: Iterators vs Threads ===
+
+mod pipeline {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::str::FromStr;
+    use std::sync::mpsc;
+    use std::thread;
+
+    /// Log severity, least severe first.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub enum Level {
+        Debug,
+        Info,
+        Warn,
+        Error,
+    }
+
+    /// The level text was not one of DEBUG, INFO, WARN or ERROR.
+    #[derive(Debug, PartialEq)]
+    pub struct UnknownLevel(pub String);
+
+    impl fmt::Display for UnknownLevel {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "unknown log level \"{}\"", self.0)
+        }
+    }
+
+    impl FromStr for Level {
+        type Err = UnknownLevel;
+
+        fn from_str(s: &str) -> Result<Level, UnknownLevel> {
+            match s {
+                "DEBUG" => Ok(Level::Debug),
+                "INFO" => Ok(Level::Info),
+                "WARN" => Ok(Level::Warn),
+                "ERROR" => Ok(Level::Error),
+                other => Err(UnknownLevel(other.to_string())),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct LogEntry {
+        pub level: Level,
+        pub message: String,
+    }
+
+    /// Entries below this level are dropped by stage 2.
+    pub const MIN_LEVEL: Level = Level::Info;
+
+    /// Per-level counts of the entries that passed the filter, plus malformed lines skipped.
+    #[derive(Debug, Default, PartialEq)]
+    pub struct Stats {
+        pub counts: HashMap<Level, usize>,
+        pub skipped: usize,
+    }
+
+    /// Stage 1: parses `LEVEL message`; `None` for a malformed line.
+    fn parse(line: &str) -> Option<LogEntry> {
+        let (level, message) = line.trim().split_once(' ')?;
+        let level = level.parse().ok()?;
+        Some(LogEntry { level, message: message.trim().to_string() })
+    }
+
+    /// Stage 2.
+    fn keep(entry: &LogEntry) -> bool {
+        entry.level >= MIN_LEVEL
+    }
+
+    /// Stage 3.
+    fn tally(stats: &mut Stats, entry: LogEntry) {
+        *stats.counts.entry(entry.level).or_insert(0) += 1;
+    }
+
+    /// All three stages as one lazy iterator chain on the current thread.
+    pub fn process_iter(lines: &[&str]) -> Stats {
+        let mut skipped = 0;
+        let mut stats = lines
+            .iter()
+            .filter_map(|line| {
+                let entry = parse(line);
+                if entry.is_none() {
+                    skipped += 1;
+                }
+                entry
+            })
+            .filter(keep)
+            .fold(Stats::default(), |mut stats, entry| {
+                tally(&mut stats, entry);
+                stats
+            });
+        stats.skipped = skipped;
+        stats
+    }
+
+    /// The same stages, each on its own thread, connected by channels. A stage ends when
+    /// its input channel closes, i.e. when the previous stage drops its `Sender`.
+    pub fn process_threaded(lines: Vec<String>) -> Stats {
+        let (parsed_tx, parsed_rx) = mpsc::channel();
+        let (kept_tx, kept_rx) = mpsc::channel();
+
+        let parser = thread::spawn(move || {
+            let mut skipped = 0;
+            for line in &lines {
+                match parse(line) {
+                    Some(entry) => parsed_tx.send(entry).expect("filter stage hung up"),
+                    None => skipped += 1,
+                }
+            }
+            skipped
+        });
+        let filter = thread::spawn(move || {
+            for entry in parsed_rx.into_iter().filter(keep) {
+                kept_tx.send(entry).expect("tally stage hung up");
+            }
+        });
+        let tallier = thread::spawn(move || {
+            let mut stats = Stats::default();
+            for entry in kept_rx {
+                tally(&mut stats, entry);
+            }
+            stats
+        });
+
+        let skipped = parser.join().expect("parser stage panicked");
+        filter.join().expect("filter stage panicked");
+        let mut stats = tallier.join().expect("tally stage panicked");
+        stats.skipped = skipped;
+        stats
+    }
+}
+
+// === String Interning ===
+
+/// A handle to a string stored in an `Interner`; cheap to copy, compare and hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Symbol(u32);
+
+/// Stores each distinct string once and hands out `Symbol`s for them.
+struct Interner {
+    ids: HashMap<String, Symbol>,
+    strings: Vec<String>, // Indexed by `Symbol.0`
+}
+
+impl Interner {
+    fn new() -> Self {
+        Interner { ids: HashMap::new(), strings: Vec::new() }
+    }
+
+    /// Returns the existing symbol for `s`, or stores `s` and returns a new one.
+    fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(&sym) = self.ids.get(s) {
+            return sym;
+        }
+        let sym = Symbol(u32::try_from(self.strings.len()).expect("more than u32::MAX strings interned"));
+        self.strings.push(s.to_string());
+        self.ids.insert(s.to_string(), sym);
+        sym
+    }
+
+    /// The string behind `sym`, borrowed from the interner.
+    ///
+    /// Panics if `sym` came from a different interner (and is out of range here): a
+    /// `Symbol` can only be created by `intern`, so that is a bug, not an input error.
+    fn resolve(&self, sym: Symbol) -> &str {
+        &self.strings[sym.0 as usize]
+    }
+
+    /// Number of distinct strings stored.
+    fn len(&self) -> usize {
+        self.strings.len()
+    }
+}
+
+// Holding a resolved `&str` across a later `intern` does not compile:
+// let name = interner.resolve(sym);    // Immutable borrow of `interner`...
+// interner.intern("someone else");     // Compiler Error E0502: ...conflicts with this mutable borrow
+// println!("{}", name);                // ...because `name` is still used here
+//
+// Here the borrow checker is stricter than necessary: `strings` may reallocate when it
+// grows, but that moves only the `String` headers, not the heap bytes `name` points into.
+// It cannot know that, though, and an interner that stored all text in one growing
+// buffer really would leave `name` dangling. Copy the `Symbol` around instead and
+// resolve it again when needed.
+
+// === Moving Out of `&mut` with std::mem ===
+
+/// Moves the string out of `slot`, leaving `None` behind.
+fn rotate_option(slot: &mut Option<String>) -> Option<String> {
+    // `let old = *slot;` would not compile: it moves out of a borrow (E0507).
+    // `take` is `mem::replace(slot, None)`: something valid is always left in place.
+    slot.take()
+}
+
+/// A traffic light whose every state owns the intersection name (a non-`Copy` `String`).
+#[derive(Debug, PartialEq)]
+enum TrafficLight {
+    Red { intersection: String },
+    Green { intersection: String },
+    Yellow { intersection: String },
+}
+
+/// Red -> Green -> Yellow -> Red, carrying the intersection name along without cloning it.
+fn advance_state(light: &mut TrafficLight) {
+    // The naive version tries to move the String out from behind the `&mut`:
+    // *light = match *light {
+    //     TrafficLight::Red { intersection } => TrafficLight::Green { intersection }, // Compiler Error E0507: cannot move out of `light.intersection`
+    //     ...
+    // };
+    // `mem::replace` swaps in a cheap placeholder (an empty String does not allocate)
+    // and hands back the old state by value, so its fields can be moved.
+    let old = mem::replace(light, TrafficLight::Red { intersection: String::new() });
+    *light = match old {
+        TrafficLight::Red { intersection } => TrafficLight::Green { intersection },
+        TrafficLight::Green { intersection } => TrafficLight::Yellow { intersection },
+        TrafficLight::Yellow { intersection } => TrafficLight::Red { intersection },
+    };
+}
+
+/// Swaps the first and last elements; slices shorter than 2 are left alone.
+/// (`v.swap(0, len - 1)` does the same; this spells out how it is possible.)
+fn swap_ends(v: &mut [i32]) {
+    let len = v.len();
+    if len < 2 {
+        return;
+    }
+    // Two `&mut` into the same slice at once is rejected:
+    // mem::swap(&mut v[0], &mut v[len - 1]); // Compiler Error E0499: cannot borrow `*v` as mutable more than once
+    // `split_at_mut` proves to the compiler that the two halves do not overlap
+    let (head, tail) = v.split_at_mut(len - 1);
+    mem::swap(&mut head[0], &mut tail[0]);
+}
+
+// === Custom Iterators ===
+
+/// Counts from `start` up to (not including) `end`: a hand-written `start..end`.
+#[derive(Debug, Clone)]
+struct RangeCounter {
+    front: u32,
+    back: u32, // Exclusive; front == back means exhausted
+}
+
+impl RangeCounter {
+    fn new(start: u32, end: u32) -> Self {
+        RangeCounter { front: start, back: end.max(start) }
+    }
+}
+
+impl Iterator for RangeCounter {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.front - 1)
+    }
+
+    // Exact bounds let `collect` preallocate and are required for `ExactSizeIterator`
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.back - self.front) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+// Taking from the back enables `rev()`, `next_back()` and `rfind()`
+impl DoubleEndedIterator for RangeCounter {
+    fn next_back(&mut self) -> Option<u32> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.back)
+    }
+}
+
+// A marker on top of the exact `size_hint`: provides `len()`
+impl ExactSizeIterator for RangeCounter {}
+
+/// Alternately takes from the front and the back until the two ends meet, returning
+/// (sum of front picks, sum of back picks). An odd middle element counts as a front pick.
+fn sum_from_both_ends<I: DoubleEndedIterator<Item = u32>>(mut iter: I) -> (u32, u32) {
+    let (mut front, mut back) = (0, 0);
+    while let Some(value) = iter.next() {
+        front += value;
+        match iter.next_back() {
+            Some(value) => back += value,
+            None => break,
+        }
+    }
+    (front, back)
+}
+
+// === Templates ===
+
+mod template {
+    use std::collections::HashMap;
+    use std::fmt;
+
+    /// A template problem, located by the byte offset of the offending brace.
+    #[derive(Debug, PartialEq)]
+    pub enum TemplateError {
+        UnknownVariable { name: String, offset: usize },
+        Unterminated { offset: usize },
+        /// A lone `}` that is neither closing a placeholder nor escaped as `}}`.
+        UnmatchedClose { offset: usize },
+    }
+
+    impl fmt::Display for TemplateError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                TemplateError::UnknownVariable { name, offset } => {
+                    write!(f, "unknown variable {:?} at byte {}", name, offset)
+                }
+                TemplateError::Unterminated { offset } => write!(f, "unterminated placeholder at byte {}", offset),
+                TemplateError::UnmatchedClose { offset } => write!(f, "unmatched '}}' at byte {}", offset),
+            }
+        }
+    }
+
+    /// Replaces each `{name}` with its value; `{{` and `}}` produce literal braces.
+    /// Values are inserted as-is, never expanded again, so they may contain braces.
+    pub fn render(template: &str, vars: &HashMap<&str, String>) -> Result<String, TemplateError> {
+        expand(template, vars, false).map(|(output, _)| output)
+    }
+
+    /// Like `render`, but never fails: unknown placeholders stay in the output verbatim
+    /// (and their names are returned, in order of appearance), and malformed braces are
+    /// kept as literal text.
+    pub fn render_lenient(template: &str, vars: &HashMap<&str, String>) -> (String, Vec<String>) {
+        expand(template, vars, true).expect("lenient expansion does not fail")
+    }
+
+    fn expand(
+        template: &str,
+        vars: &HashMap<&str, String>,
+        lenient: bool,
+    ) -> Result<(String, Vec<String>), TemplateError> {
+        let mut output = String::with_capacity(template.len());
+        let mut missing = Vec::new();
+        let mut rest = template;
+        // `rest` always starts at a char boundary; `offset` is its position in `template`
+        while let Some(i) = rest.find(['{', '}']) {
+            let offset = template.len() - rest.len() + i;
+            output.push_str(&rest[..i]);
+            let (brace, after) = (&rest[i..i + 1], &rest[i + 1..]);
+            if after.starts_with(brace) {
+                output.push_str(brace); // `{{` or `}}`
+                rest = &after[1..];
+            } else if brace == "}" {
+                if !lenient {
+                    return Err(TemplateError::UnmatchedClose { offset });
+                }
+                output.push('}');
+                rest = after;
+            } else if let Some(end) = after.find('}') {
+                let name = &after[..end];
+                match vars.get(name) {
+                    Some(value) => output.push_str(value),
+                    None if lenient => {
+                        output.push_str(&rest[i..i + end + 2]);
+                        missing.push(name.to_string());
+                    }
+                    None => return Err(TemplateError::UnknownVariable { name: name.to_string(), offset }),
+                }
+                rest = &after[end + 1..];
+            } else {
+                if !lenient {
+                    return Err(TemplateError::Unterminated { offset });
+                }
+                output.push_str(&rest[i..]);
+                rest = "";
+            }
+        }
+        output.push_str(rest);
+        Ok((output, missing))
+    }
+}
+
+// === Object Pools: RAII Guards ===
+
+/// A thread-safe pool of reusable items. `checkout` hands out a guard that puts the
+/// item back when dropped, so returning can't be forgotten (or done twice).
+struct Pool<T> {
+    items: Mutex<Vec<T>>,
+    returned: Condvar, // Signalled whenever a guard puts its item back
+}
+
+/// An item on loan from a [`Pool`]. Derefs to the item; dropping it returns the item.
+struct PooledItem<'a, T> {
+    pool: &'a Pool<T>,
+    item: Option<T>, // Only `None` while `drop` is moving it back
+}
+
+impl<T> Pool<T> {
+    fn new(items: Vec<T>) -> Self {
+        Pool { items: Mutex::new(items), returned: Condvar::new() }
+    }
+
+    /// Takes an item without waiting, or `None` if every item is checked out.
+    fn checkout(&self) -> Option<PooledItem<'_, T>> {
+        let item = self.items.lock().unwrap().pop()?;
+        Some(PooledItem { pool: self, item: Some(item) })
+    }
+
+    /// Waits up to `timeout` for an item to be returned if none is available right now.
+    fn checkout_timeout(&self, timeout: Duration) -> Option<PooledItem<'_, T>> {
+        let items = self.items.lock().unwrap();
+        // `wait_timeout_while` re-checks the condition on spurious wakeups
+        let (mut items, _) = self.returned.wait_timeout_while(items, timeout, |items| items.is_empty()).unwrap();
+        let item = items.pop()?;
+        Some(PooledItem { pool: self, item: Some(item) })
+    }
+
+    fn available(&self) -> usize {
+        self.items.lock().unwrap().len()
+    }
+}
+
+impl<T> Deref for PooledItem<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.item.as_ref().expect("item is present until drop")
+    }
+}
+
+impl<T> DerefMut for PooledItem<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.item.as_mut().expect("item is present until drop")
+    }
+}
+
+impl<T> Drop for PooledItem<'_, T> {
+    fn drop(&mut self) {
+        if let Some(item) = self.item.take() {
+            // Don't panic in drop: a poisoned lock still guards a perfectly good Vec
+            let mut items = self.pool.items.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            items.push(item);
+            self.pool.returned.notify_one();
+        }
+    }
+}
+
+/// Single-threaded counterpart of [`Pool`]: `RefCell` instead of `Mutex`. There is no
+/// `checkout_timeout`, since nothing else could return an item while we wait.
+struct LocalPool<T> {
+    items: RefCell<Vec<T>>,
+}
+
+/// An item on loan from a [`LocalPool`].
+struct LocalPooledItem<'a, T> {
+    pool: &'a LocalPool<T>,
+    item: Option<T>,
+}
+
+impl<T> LocalPool<T> {
+    fn new(items: Vec<T>) -> Self {
+        LocalPool { items: RefCell::new(items) }
+    }
+
+    fn checkout(&self) -> Option<LocalPooledItem<'_, T>> {
+        let item = self.items.borrow_mut().pop()?;
+        Some(LocalPooledItem { pool: self, item: Some(item) })
+    }
+
+    fn available(&self) -> usize {
+        self.items.borrow().len()
+    }
+}
+
+impl<T> Deref for LocalPooledItem<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.item.as_ref().expect("item is present until drop")
+    }
+}
+
+impl<T> DerefMut for LocalPooledItem<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.item.as_mut().expect("item is present until drop")
+    }
+}
+
+impl<T> Drop for LocalPooledItem<'_, T> {
+    fn drop(&mut self) {
+        if let Some(item) = self.item.take() {
+            self.pool.items.borrow_mut().push(item); // No borrow is held across a checkout
+        }
+    }
+}
+
+// === Human-Readable Numbers ===
+
+mod humanize {
+    /// Formats `n` with commas between thousands: `-1234567` becomes `"-1,234,567"`.
+    pub fn with_thousands(n: i64) -> String {
+        // `unsigned_abs` can represent |i64::MIN|, where `abs` would overflow
+        let digits = n.unsigned_abs().to_string();
+        let mut out = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+        if n < 0 {
+            out.push('-');
+        }
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(',');
+            }
+            out.push(digit);
+        }
+        out
+    }
+
+    const BYTE_UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    /// Formats a byte count in 1024-based units with one decimal: `"1.5 KiB"`, `"3.2 MiB"`.
+    /// Counts under 1 KiB are exact (`"1023 B"`); anything past 1024 PiB stays in PiB.
+    pub fn human_bytes(bytes: u64) -> String {
+        if bytes < 1024 {
+            return format!("{} B", bytes);
+        }
+        let mut value = bytes as f64 / 1024.0;
+        let mut unit = 0;
+        // Compare the rounded value, so 1048575 bytes is "1.0 MiB" rather than "1024.0 KiB"
+        while (value * 10.0).round() >= 10240.0 && unit < BYTE_UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        format!("{:.1} {}", value, BYTE_UNITS[unit])
+    }
+
+    /// English ordinal: `1st`, `2nd`, `3rd`, `4th`, with `11th`-`13th` as the exceptions.
+    pub fn ordinal(n: u64) -> String {
+        let suffix = match (n % 10, n % 100) {
+            (_, 11..=13) => "th",
+            (1, _) => "st",
+            (2, _) => "nd",
+            (3, _) => "rd",
+            _ => "th",
+        };
+        format!("{}{}", n, suffix)
+    }
+}
+
+// === Custom Containers ===
+
+/// A last-in, first-out stack: a thin wrapper that exposes only the `Vec` operations a stack needs.
+#[derive(Debug, Clone, Default)]
+struct Stack<T> {
+    items: Vec<T>, // Top of the stack is the end of the Vec, so push and pop are O(1)
+}
+
+impl<T> Stack<T> {
+    fn new() -> Self {
+        Stack { items: Vec::new() }
+    }
+
+    fn push(&mut self, item: T) {
+        self.items.push(item);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.items.pop()
+    }
+
+    /// The top item without removing it.
+    fn peek(&self) -> Option<&T> {
+        self.items.last()
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Runs `f` on every item, bottom to top, in place. If `f` panics on the item at index
+    /// `i`, items `0..i` keep their changes, item `i` keeps whatever `f` did before panicking,
+    /// and the rest are untouched: the stack is still valid, just half-updated.
+    fn apply_all<F: FnMut(&mut T)>(&mut self, f: F) {
+        self.items.iter_mut().for_each(f);
+    }
+
+    /// Like `apply_all`, but all-or-nothing: `f` runs on clones, which replace the items
+    /// only once every call has returned. A panic drops the clones and leaves `self` as it was.
+    fn apply_all_transactional<F: FnMut(&mut T)>(&mut self, f: F)
+    where
+        T: Clone,
+    {
+        let mut staged = self.items.clone();
+        staged.iter_mut().for_each(f);
+        self.items = staged; // The commit point: only reached if no call panicked
+    }
+}
+
+/// Runs `f`, turning a panic into `Err` with its message instead of unwinding further.
+///
+/// `catch_unwind` requires `UnwindSafe`, which rules out closures holding `&mut`
+/// references: after a panic the referent may be half-modified. `AssertUnwindSafe` is our
+/// promise that the caller inspects or tolerates that state, as `unwind_safety_demo` does.
+fn catch_panic<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        // `panic!` with a literal carries a `&str`, with format arguments a `String`
+        match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => payload.downcast_ref::<&str>().map_or("unknown panic", |s| s).to_string(),
+        }
+    })
+}
+
+/// Panics halfway through updating a stack, once in place and once transactionally,
+/// and shows the stack is still usable either way. The panics are reported by the panic
+/// hook as usual; the caller decides whether to silence them.
+fn unwind_safety_demo(r: &mut SectionReporter) {
+    let double_below_40 = |n: &mut i32| {
+        if *n >= 40 {
+            panic!("refusing to double {}", n);
+        }
+        *n *= 2;
+    };
+
+    let mut stack: Stack<i32> = vec![10, 20, 40, 30].into_iter().collect();
+    r.line(format!("Before: {:?}", stack.items));
+    let result = catch_panic(|| stack.apply_all(double_below_40));
+    r.line(format!("apply_all: {:?}, left {:?}", result, stack.items));
+
+    let mut stack: Stack<i32> = vec![10, 20, 40, 30].into_iter().collect();
+    let result = catch_panic(|| stack.apply_all_transactional(double_below_40));
+    r.line(format!("apply_all_transactional: {:?}, left {:?}", result, stack.items));
+
+    stack.push(5);
+    stack.apply_all(|n| *n += 1);
+    r.line(format!("Still usable after the panic: {:?}", stack.items));
+}
+
+// Consuming a stack yields items in pop order: last pushed first
+impl<T> IntoIterator for Stack<T> {
+    type Item = T;
+    type IntoIter = std::iter::Rev<std::vec::IntoIter<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter().rev()
+    }
+}
+
+// Collecting pushes in iteration order, so the last item ends up on top
+impl<T> FromIterator<T> for Stack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Stack { items: iter.into_iter().collect() }
+    }
+}
+
+// === Text Editing: A Two-Stack Buffer ===
+
+/// Editable text with a cursor, as two stacks of `char`s meeting at the cursor: `before`
+/// in order, `after` reversed. Typing, deleting and stepping the cursor are all O(1) pushes
+/// and pops, and jumping costs O(distance) — the same tradeoff as a gap buffer.
+///
+/// Storing `char`s rather than UTF-8 bytes means the cursor can never land inside a
+/// multibyte character and positions count characters, at 4 bytes per char and a
+/// conversion whenever a `String` is needed. (A `char` is still not a grapheme: an emoji
+/// with a skin-tone modifier is two positions.)
+#[derive(Debug, Default)]
+struct TextBuffer {
+    before: Vec<char>, // Text left of the cursor, in order
+    after: Vec<char>,  // Text right of the cursor, reversed so the next char is at the end
+}
+
+impl TextBuffer {
+    fn new() -> Self {
+        TextBuffer::default()
+    }
+
+    /// Types `c` at the cursor, leaving the cursor after it.
+    fn insert_char(&mut self, c: char) {
+        self.before.push(c);
+    }
+
+    /// Deletes the char before the cursor, like Backspace. A no-op at position 0.
+    fn delete_backward(&mut self) -> Option<char> {
+        self.before.pop()
+    }
+
+    /// Moves one char left; returns `false` (and stays put) at the start.
+    fn move_left(&mut self) -> bool {
+        match self.before.pop() {
+            Some(c) => {
+                self.after.push(c);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves one char right; returns `false` (and stays put) at the end.
+    fn move_right(&mut self) -> bool {
+        match self.after.pop() {
+            Some(c) => {
+                self.before.push(c);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the cursor to char index `pos`, clamped to the end of the text.
+    fn move_to(&mut self, pos: usize) {
+        while self.cursor_position() > pos && self.move_left() {}
+        while self.cursor_position() < pos && self.move_right() {}
+    }
+
+    /// The cursor's position in chars from the start.
+    fn cursor_position(&self) -> usize {
+        self.before.len()
+    }
+
+    fn as_string(&self) -> String {
+        self.before.iter().chain(self.after.iter().rev()).collect()
+    }
+}
+
+// === Progress Reporting ===
+
+mod progress {
+    use std::io::{self, IsTerminal, Write};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+    /// Draws `[#####.....] 52%` with `width` cells. Both the fill and the percentage round
+    /// down, so 100% only ever means done. An empty job (`total == 0`) counts as done.
+    pub fn render_bar(done: usize, total: usize, width: usize) -> String {
+        let (done, total) = if total == 0 { (1, 1) } else { (done.min(total), total) };
+        let filled = done * width / total;
+        let percent = done * 100 / total;
+        format!("[{}{}] {}%", "#".repeat(filled), ".".repeat(width - filled), percent)
+    }
+
+    /// Calls `f` once for every index in `0..total`, split into contiguous shares across
+    /// `workers` threads (0 means `default_thread_count()`), and redraws a progress bar on
+    /// stdout while they run (only the final bar when stdout is not a terminal). Returns how
+    /// long the whole run took. A panic in `f` is re-raised once every worker has stopped.
+    pub fn run_with_progress<F: Fn(usize) + Sync>(total: usize, workers: usize, f: F) -> Duration {
+        let workers = if workers == 0 { super::default_thread_count() } else { workers };
+        let done = AtomicUsize::new(0);
+        let start = Instant::now();
+        let animate = io::stdout().is_terminal();
+        thread::scope(|scope| {
+            let (done, f) = (&done, &f);
+            let handles: Vec<_> = (0..workers)
+                .map(|worker| {
+                    // Integer boundaries give every index to exactly one worker, even when uneven
+                    let share = worker * total / workers..(worker + 1) * total / workers;
+                    scope.spawn(move || {
+                        for index in share {
+                            f(index);
+                            done.fetch_add(1, Ordering::Relaxed); // Only a count; no data is published through it
+                        }
+                    })
+                })
+                .collect();
+            let mut last_drawn = String::new();
+            let mut draw = |bar: String| {
+                if bar != last_drawn {
+                    print!("\r{}", bar); // `\r` returns to the start of the line to draw over it
+                    io::stdout().flush().ok();
+                    last_drawn = bar;
+                }
+            };
+            // Meanwhile this thread polls the counter, redrawing only when the bar changes. It waits
+            // for every worker to finish rather than for `total`, which a panicked one never reaches.
+            while !handles.iter().all(|handle| handle.is_finished()) {
+                if animate {
+                    draw(render_bar(done.load(Ordering::Relaxed), total, 30));
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+            // Joining makes every worker's last increment visible to the final draw
+            let panics: Vec<_> = handles.into_iter().filter_map(|handle| handle.join().err()).collect();
+            draw(render_bar(done.load(Ordering::Relaxed), total, 30));
+            println!();
+            if let Some(panic) = panics.into_iter().next() {
+                std::panic::resume_unwind(panic);
+            }
+        });
+        start.elapsed()
+    }
+}
+
+// === Units in the Type System: PhantomData ===
+
+mod units {
+    use std::marker::PhantomData;
+
+    /// Unit marker: screen pixels. Markers are never constructed as values on a `Rect`;
+    /// they only exist in its type. (Unrelated to the top-level `Millimeters` length.)
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Pixels;
+
+    /// Unit marker: physical millimeters.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Millimeters;
+
+    /// A size tagged with its unit, so `Rect<Pixels>` and `Rect<Millimeters>` are distinct
+    /// types. `PhantomData<U>` uses `U` without storing anything: it is zero-sized.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Rect<U> {
+        pub width: u32,
+        pub height: u32,
+        _unit: PhantomData<U>,
+    }
+
+    /// Scaling from one unit to another at a given resolution in dots per inch.
+    pub trait UnitConvert<To> {
+        fn convert_length(length: u32, dpi: u32) -> u32;
+    }
+
+    // One inch is 25.4 mm, so everything below works in tenths of a millimeter to stay in integers.
+    // Rounding is to the nearest whole unit, half up; results past `u32::MAX` saturate.
+
+    impl UnitConvert<Millimeters> for Pixels {
+        fn convert_length(px: u32, dpi: u32) -> u32 {
+            let dpi = u64::from(dpi);
+            ((u64::from(px) * 254 + 5 * dpi) / (10 * dpi)).min(u64::from(u32::MAX)) as u32
+        }
+    }
+
+    impl UnitConvert<Pixels> for Millimeters {
+        fn convert_length(mm: u32, dpi: u32) -> u32 {
+            ((u64::from(mm) * u64::from(dpi) * 10 + 127) / 254).min(u64::from(u32::MAX)) as u32
+        }
+    }
+
+    impl<U> Rect<U> {
+        pub fn new(width: u32, height: u32) -> Self {
+            Rect { width, height, _unit: PhantomData }
+        }
+
+        /// Strictly larger in both dimensions, like `Rectangle::can_hold`, but only against
+        /// a rect in the same unit.
+        pub fn can_hold(&self, other: &Rect<U>) -> bool {
+            self.width > other.width && self.height > other.height
+        }
+
+        /// Rescales into unit `To`, e.g. `px.convert::<Millimeters>(96)`. Only compiles for unit
+        /// pairs with a `UnitConvert` impl. Each conversion rounds, so a round trip can be off
+        /// by up to one of the coarser unit. Panics if `dpi` is 0.
+        pub fn convert<To>(&self, dpi: u32) -> Rect<To>
+        where
+            U: UnitConvert<To>,
+        {
+            Rect::new(U::convert_length(self.width, dpi), U::convert_length(self.height, dpi))
+        }
+    }
+
+    // The untyped `Rectangle` has always meant screen sizes, so it converts to pixels
+    impl From<super::Rectangle> for Rect<Pixels> {
+        fn from(rect: super::Rectangle) -> Self {
+            Rect::new(rect.width, rect.height)
+        }
+    }
+}
+
+// === Generational Arenas ===
+
+/// A reference into an `Arena`: a slot index plus the generation of the value it was
+/// issued for. Copyable and ownership-free, unlike `Rc`, and it can't keep a value alive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Handle {
+    index: usize,
+    generation: u32,
+}
+
+/// One arena slot. `generation` is bumped whenever the value is removed, so handles
+/// issued before the removal no longer match once the slot is reused.
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Values stored in a `Vec` and addressed by `Handle`. Removed slots are reused, and a
+/// stale handle is detected by its generation rather than silently reaching the new value.
+/// (After 2^32 reuses of one slot the generation wraps, and a very old handle would match again.)
+struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>, // Indices of empty slots, reused last-freed first
+}
+
+impl<T> Arena<T> {
+    fn new() -> Self {
+        Arena { slots: Vec::new(), free: Vec::new() }
+    }
+
+    fn insert(&mut self, value: T) -> Handle {
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                slot.value = Some(value);
+                Handle { index, generation: slot.generation }
+            }
+            None => {
+                self.slots.push(Slot { generation: 0, value: Some(value) });
+                Handle { index: self.slots.len() - 1, generation: 0 }
+            }
+        }
+    }
+
+    /// Takes the value out, or `None` if the handle is stale (or already removed).
+    fn remove(&mut self, h: Handle) -> Option<T> {
+        let slot = self.slots.get_mut(h.index).filter(|slot| slot.generation == h.generation)?;
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(h.index);
+        Some(value)
+    }
+
+    fn get(&self, h: Handle) -> Option<&T> {
+        self.slots.get(h.index).filter(|slot| slot.generation == h.generation)?.value.as_ref()
+    }
+
+    fn get_mut(&mut self, h: Handle) -> Option<&mut T> {
+        self.slots.get_mut(h.index).filter(|slot| slot.generation == h.generation)?.value.as_mut()
+    }
+
+    fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    /// The live entries with their current handles, in slot order.
+    fn iter(&self) -> impl Iterator<Item = (Handle, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.value.as_ref().map(|value| (Handle { index, generation: slot.generation }, value))
+        })
+    }
+}
+
+/// A node in a scene graph whose links are arena handles rather than `Rc`/`Weak` pointers,
+/// so there are no reference cycles to worry about and no `RefCell` borrows to juggle.
+#[derive(Debug)]
+struct SceneNode {
+    name: String,
+    children: Vec<Handle>,
+}
+
+impl SceneNode {
+    fn new(name: &str) -> Self {
+        SceneNode { name: name.to_string(), children: Vec::new() }
+    }
+}
+
+/// Creates a node named `name` under `parent`, or returns `None` if `parent` is stale.
+fn add_child(arena: &mut Arena<SceneNode>, parent: Handle, name: &str) -> Option<Handle> {
+    arena.get(parent)?;
+    let child = arena.insert(SceneNode::new(name));
+    arena.get_mut(parent)?.children.push(child);
+    Some(child)
+}
+
+/// Removes `node` and, recursively, all of its descendants, and unlinks it from `parent`
+/// (`None` for a root). Returns how many nodes were removed (0 for a stale handle).
+/// Children can't outlive their parent here, since nothing else would point at them.
+fn remove_subtree(arena: &mut Arena<SceneNode>, parent: Option<Handle>, node: Handle) -> usize {
+    if let Some(parent_node) = parent.and_then(|parent| arena.get_mut(parent)) {
+        parent_node.children.retain(|&child| child != node);
+    }
+    remove_descendants(arena, node)
+}
+
+// The descendants' parents are removed along with them, so only the top needs unlinking
+fn remove_descendants(arena: &mut Arena<SceneNode>, node: Handle) -> usize {
+    match arena.remove(node) {
+        Some(removed) => 1 + removed.children.into_iter().map(|child| remove_descendants(arena, child)).sum::<usize>(),
+        None => 0,
+    }
+}
+
+/// Depth-first, pre-order walk from `node`, calling `f` with each live node and its depth.
+/// Only borrows the arena, so handles are followed without any ownership bookkeeping.
+fn visit(arena: &Arena<SceneNode>, node: Handle, depth: usize, f: &mut impl FnMut(&SceneNode, usize)) {
+    if let Some(scene_node) = arena.get(node) {
+        f(scene_node, depth);
+        for &child in &scene_node.children {
+            visit(arena, child, depth + 1, f);
+        }
+    }
+}
+
+// === Modules ===
+
+mod my_module {
+    // Items are private by default
+    use super::SectionReporter; // Private items of an ancestor module are visible here
+
+    /// This function is public and can be called from outside `my_module`.
+    pub fn public_function(r: &mut SectionReporter) {
+        r.line("Called my_module::public_function()");
+        private_function(r); // Can call private functions within the same module
+    }
+
+    /// This function is private.
+    fn private_function(r: &mut SectionReporter) {
+        r.line("Called my_module::private_function()");
+    }
+
+    // Nested module
+    pub mod nested {
+        use super::SectionReporter;
+
+         /// Public function in a nested module.
+        pub fn nested_function(r: &mut SectionReporter) {
+            r.line("Called my_module::nested::nested_function()");
+        }
+    }
+
+    // You can also have structs, enums, traits, etc., inside modules
+    // Use `pub` to make them accessible outside.
     pub struct PublicStruct {
         pub field: i32, // Fields can also be public or private
         private_field: bool,
     }
 
-    impl PublicStruct {
-         pub fn new(val: i32) -> Self {
-             PublicStruct { field: val, private_field: false }
-         }
+    impl PublicStruct {
+         pub fn new(val: i32) -> Self {
+             PublicStruct { field: val, private_field: false }
+         }
+    }
+
+    /// Returned by `call` when no public callable has the requested name.
+    #[derive(Debug, PartialEq)]
+    pub struct UnknownItem(pub String);
+
+    impl std::fmt::Display for UnknownItem {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "my_module has no public callable named '{}'", self.0)
+        }
+    }
+
+    // Name -> function table; plain `fn` pointers work because they all take the same arguments
+    type Callable = fn(&mut SectionReporter);
+    const CALLABLES: [(&str, Callable); 2] = [
+        ("public_function", public_function),
+        ("nested::nested_function", nested::nested_function),
+    ];
+
+    /// Lists the names of the public callables this module offers.
+    pub fn registry() -> Vec<&'static str> {
+        CALLABLES.iter().map(|&(name, _)| name).collect()
+    }
+
+    /// Dispatches to a public callable by its registry name.
+    pub fn call(name: &str, r: &mut SectionReporter) -> Result<(), UnknownItem> {
+        match CALLABLES.iter().find(|&&(candidate, _)| candidate == name) {
+            Some(&(_, function)) => {
+                function(r);
+                Ok(())
+            }
+            None => Err(UnknownItem(name.to_string())),
+        }
+    }
+}
+
+// === Tests ===
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn grid_cell_round_trips_a_point() {
+        let board = Rectangle { width: 40, height: 40 };
+        let (col, row) = board.cell_containing(&Point { x: 25, y: 12 }, 4, 4).unwrap();
+        assert_eq!((col, row), (2, 1));
+        let cell = board.cell_at(col, row, 4, 4).unwrap();
+        assert_eq!((cell.width, cell.height), (10, 10));
+        // The cell's top-left corner maps back to the same cell
+        assert_eq!(board.cell_containing(&Point { x: 20, y: 10 }, 4, 4), Some((col, row)));
+    }
+
+    #[test]
+    fn grid_rejects_out_of_range_cells_and_points() {
+        let board = Rectangle { width: 40, height: 40 };
+        assert!(board.cell_at(4, 0, 4, 4).is_none());
+        assert!(board.cell_containing(&Point { x: 40, y: 0 }, 4, 4).is_none());
+        assert!(board.cell_containing(&Point { x: -1, y: 0 }, 4, 4).is_none());
+    }
+
+    #[test]
+    fn grid_uneven_division_still_covers_the_width() {
+        let board = Rectangle { width: 10, height: 10 };
+        for x in 0..10 {
+            let (col, _) = board.cell_containing(&Point { x, y: 0 }, 3, 3).unwrap();
+            assert!(col < 3);
+        }
+        let total: u32 = (0..3).map(|col| board.cell_at(col, 0, 3, 3).unwrap().width).sum();
+        assert_eq!(total, 10);
+    }
+
+    fn sample_graph() -> graph::Graph<&'static str> {
+        let mut graph = graph::Graph::new();
+        graph.add_edge("a", "b");
+        graph.add_edge("b", "c");
+        graph.add_edge("x", "y");
+        graph.add_node("lonely");
+        graph
+    }
+
+    #[test]
+    fn bfs_path_to_self_is_one_node() {
+        assert_eq!(sample_graph().bfs_shortest_path(&"a", &"a"), Some(vec!["a"]));
+    }
+
+    #[test]
+    fn bfs_finds_shortest_path() {
+        let mut graph = sample_graph();
+        graph.add_edge("a", "d");
+        graph.add_edge("d", "e");
+        graph.add_edge("e", "c"); // A longer way round
+        assert_eq!(graph.bfs_shortest_path(&"a", &"c"), Some(vec!["a", "b", "c"]));
+    }
+
+    #[test]
+    fn bfs_unreachable_or_unknown_is_none() {
+        let graph = sample_graph();
+        assert_eq!(graph.bfs_shortest_path(&"a", &"y"), None);
+        assert_eq!(graph.bfs_shortest_path(&"a", &"nope"), None);
+    }
+
+    #[test]
+    fn node_without_edges_has_no_neighbors() {
+        assert!(sample_graph().neighbors(&"lonely").is_empty());
+    }
+
+    #[test]
+    fn components_cover_every_node_once() {
+        let components = sample_graph().connected_components();
+        assert_eq!(components.len(), 3);
+        let mut nodes: Vec<&str> = components.into_iter().flatten().collect();
+        nodes.sort();
+        assert_eq!(nodes, ["a", "b", "c", "lonely", "x", "y"]);
+    }
+
+    #[test]
+    fn color_add_saturates_at_255() {
+        assert_eq!(Color(200, 0, 255) + Color(100, 0, 1), Color(255, 0, 255));
+        assert_eq!(Color(1, 2, 3).saturating_add(Color(1, 1, 1)), Color(2, 3, 4));
+        assert_eq!(Color(250, 0, 0).saturating_add(Color(10, 0, 0)), Color(255, 0, 0));
+    }
+
+    #[test]
+    fn color_sub_saturates_at_0() {
+        assert_eq!(Color(10, 0, 5) - Color(20, 0, 5), Color(0, 0, 0));
+        assert_eq!(Color(30, 20, 10) - Color(10, 10, 10), Color(20, 10, 0));
+    }
+
+    #[test]
+    fn color_wrapping_add_wraps_around() {
+        assert_eq!(Color(200, 1, 255).wrapping_add(Color(100, 1, 1)), Color(44, 2, 0));
+    }
+
+    fn logged_messages(cursor: &Cursor) -> Vec<Message> {
+        cursor.history().iter().map(|entry| entry.message.clone()).collect()
+    }
+
+    #[test]
+    fn cursor_logs_every_applied_message() {
+        let mut cursor = Cursor::new();
+        cursor.apply(Message::Move { x: 1, y: 2 });
+        cursor.apply(Message::Write(String::from("a")));
+        assert_eq!(logged_messages(&cursor), [Message::Move { x: 1, y: 2 }, Message::Write(String::from("a"))]);
+    }
+
+    #[test]
+    fn cursor_undo_restores_moves_and_colors() {
+        let mut cursor = Cursor::new();
+        cursor.apply(Message::Move { x: 1, y: 2 });
+        cursor.apply(Message::ChangeColor(1, 2, 3));
+        cursor.apply(Message::Write(String::from("a")));
+        cursor.apply(Message::ChangeColor(9, 9, 9));
+
+        assert_eq!(cursor.undo(), Some(Message::ChangeColor(9, 9, 9)));
+        assert_eq!((cursor.color, cursor.text.as_str()), (Color(1, 2, 3), "a"));
+        assert_eq!(cursor.undo(), Some(Message::ChangeColor(1, 2, 3)));
+        assert_eq!((cursor.color, cursor.x, cursor.y), (Color(0, 0, 0), 1, 2));
+        assert_eq!(cursor.undo(), Some(Message::Move { x: 1, y: 2 }));
+        assert_eq!((cursor.x, cursor.y), (0, 0));
+        // Only the Write is left, and it can't be undone
+        assert_eq!(cursor.history().len(), 1);
+        assert_eq!(cursor.undo(), None);
+        assert_eq!(cursor.text, "a");
+    }
+
+    #[test]
+    fn any_registry_missing_type_is_empty() {
+        let mut registry = Registry::new();
+        registry.insert(5u32);
+        assert!(registry.get_all::<String>().is_empty());
+        assert!(registry.take_first::<String>().is_none());
+    }
+
+    #[test]
+    fn any_registry_returns_every_value_of_a_type() {
+        let mut registry = Registry::new();
+        registry.insert(Rectangle { width: 1, height: 1 });
+        registry.insert(5u32);
+        registry.insert(Rectangle { width: 2, height: 2 });
+        let widths: Vec<u32> = registry.get_all::<Rectangle>().iter().map(|rect| rect.width).collect();
+        assert_eq!(widths, [1, 2]);
+    }
+
+    #[test]
+    fn any_registry_take_first_removes_it() {
+        let mut registry = Registry::new();
+        registry.insert(Rectangle { width: 1, height: 1 });
+        registry.insert(5u32);
+        registry.insert(Rectangle { width: 2, height: 2 });
+        assert_eq!(registry.take_first::<u32>(), Some(5));
+        assert!(registry.get_all::<u32>().is_empty());
+        assert_eq!(registry.take_first::<Rectangle>().map(|rect| rect.width), Some(1));
+        assert_eq!(registry.get_all::<Rectangle>().len(), 1);
+    }
+
+    #[test]
+    fn roman_round_trips_full_range() {
+        for n in 1..=3999 {
+            assert_eq!(numerals::from_roman(&numerals::to_roman(n).unwrap()), Ok(n));
+        }
+        assert_eq!(numerals::to_roman(1994).unwrap(), "MCMXCIV");
+    }
+
+    #[test]
+    fn roman_rejects_out_of_range_numbers() {
+        assert_eq!(numerals::to_roman(0), Err(numerals::RangeError { value: 0, min: 1, max: 3999 }));
+        assert!(numerals::to_roman(4000).is_err());
+    }
+
+    #[test]
+    fn roman_rejects_malformed_and_lowercase() {
+        assert!(matches!(numerals::from_roman("IIII"), Err(numerals::RomanParseError::Malformed(_))));
+        assert!(matches!(numerals::from_roman("IC"), Err(numerals::RomanParseError::Malformed(_))));
+        assert_eq!(numerals::from_roman(""), Err(numerals::RomanParseError::Empty));
+        // Lowercase is rejected by design, not accepted case-insensitively
+        assert_eq!(numerals::from_roman("iv"), Err(numerals::RomanParseError::InvalidChar('i')));
+    }
+
+    #[test]
+    fn to_base_in_common_bases() {
+        assert_eq!(numerals::to_base(5, 2).unwrap(), "101");
+        assert_eq!(numerals::to_base(u64::MAX, 2).unwrap().len(), 64);
+        assert_eq!(numerals::to_base(255, 16).unwrap(), "ff");
+        assert_eq!(numerals::to_base(35, 36).unwrap(), "z");
+        assert_eq!(numerals::to_base(0, 2).unwrap(), "0");
+    }
+
+    #[test]
+    fn to_base_rejects_base_1_and_37() {
+        assert_eq!(numerals::to_base(1, 1), Err(numerals::RangeError { value: 1, min: 2, max: 36 }));
+        assert_eq!(numerals::to_base(1, 37), Err(numerals::RangeError { value: 37, min: 2, max: 36 }));
+    }
+
+    #[test]
+    fn pair_holds_two_types_and_maps_y() {
+        let sample: Pair<u64, f64> = Pair { x: 10, y: 2.0 };
+        assert_eq!((*sample.x(), *sample.y()), (10, 2.0));
+        assert_eq!(sample.clone().map_y(|y| y * 3.0), Pair { x: 10, y: 6.0 });
+        assert_eq!(sample.map_x(|x| x as i32 - 20), Pair { x: -10, y: 2.0 });
+    }
+
+    fn cart_item(name: &str, price_cents: u64) -> Item {
+        Item { name: name.to_string(), price_cents }
+    }
+
+    #[test]
+    fn local_and_concurrent_carts_agree() {
+        let local = LocalCart::new();
+        let concurrent = ConcurrentCart::new();
+        for (name, price) in [("apple", 1), ("bread", 20)] {
+            local.add_item(cart_item(name, price));
+            concurrent.add_item(cart_item(name, price));
+        }
+        assert_eq!((local.total_cents(), local.item_count()), (21, 2));
+        assert_eq!((concurrent.total_cents(), concurrent.item_count()), (21, 2));
+    }
+
+    #[test]
+    fn concurrent_cart_survives_eight_threads() {
+        let cart = ConcurrentCart::new();
+        let handles: Vec<_> = (0..8)
+            .map(|thread_number| {
+                let cart = cart.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        cart.add_item(cart_item(&thread_number.to_string(), 1));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!((cart.item_count(), cart.total_cents()), (800, 800));
+    }
+
+    #[test]
+    fn tweet_at_the_limit_is_accepted() {
+        let tweet = Tweet::new("a", &"x".repeat(280), false, false).unwrap();
+        assert_eq!(tweet.remaining_chars(), 0);
+    }
+
+    #[test]
+    fn tweet_over_the_limit_is_rejected() {
+        assert_eq!(Tweet::new("a", &"x".repeat(281), false, false).err(), Some(TweetError::TooLong { chars: 281 }));
+    }
+
+    #[test]
+    fn tweet_length_counts_chars_not_bytes() {
+        let tweet = Tweet::new("a", &"🐧".repeat(100), false, false).unwrap(); // 400 bytes
+        assert_eq!(tweet.remaining_chars(), 180);
+    }
+
+    fn config_from(vars: &[(&str, &str)]) -> Result<config::ShowcaseConfig, config::ConfigError> {
+        config::ShowcaseConfig::from_iter(vars.iter().map(|(key, value)| (key.to_string(), value.to_string())))
+    }
+
+    #[test]
+    fn config_defaults_when_unset() {
+        assert_eq!(config_from(&[]).unwrap(), config::ShowcaseConfig::default());
+    }
+
+    #[test]
+    fn config_reads_every_variable() {
+        let vars = [("SHOWCASE_MAX_ITEMS", "2"), ("SHOWCASE_NAME", "x"), ("SHOWCASE_VERBOSE", "1"), ("PATH", "/")];
+        let config = config_from(&vars).unwrap();
+        assert_eq!((config.max_items, config.name.as_str(), config.verbose), (2, "x", true));
+    }
+
+    #[test]
+    fn config_rejects_yes_as_a_boolean() {
+        assert_eq!(config_from(&[("SHOWCASE_VERBOSE", "yes")]).unwrap_err().variable, "SHOWCASE_VERBOSE");
+    }
+
+    #[test]
+    fn config_rejects_zero_max_items() {
+        assert_eq!(config_from(&[("SHOWCASE_MAX_ITEMS", "0")]).unwrap_err().variable, "SHOWCASE_MAX_ITEMS");
+    }
+
+    #[test]
+    fn localized_author_en_matches_default() {
+        let tweet = Tweet::new("bob", "x", false, false).unwrap();
+        assert_eq!(tweet.summarize_author_localized("en"), tweet.summarize_author());
+    }
+
+    #[test]
+    fn localized_author_ja_adds_label() {
+        let tweet = Tweet::new("bob", "x", false, false).unwrap();
+        assert_eq!(tweet.summarize_author_localized("ja"), "著者: @bob");
+    }
+
+    #[test]
+    fn localized_author_unknown_locale_falls_back() {
+        let tweet = Tweet::new("bob", "x", false, false).unwrap();
+        assert_eq!(tweet.summarize_author_localized("zz"), "@bob");
+    }
+
+    #[test]
+    fn sort_points_pushes_nan_to_the_end() {
+        let mut points = vec![
+            Point { x: f64::NAN, y: 0.0 },
+            Point { x: 1.0, y: f64::NAN },
+            Point { x: 1.0, y: 2.0 },
+            Point { x: -f64::NAN, y: 0.0 },
+            Point { x: f64::NEG_INFINITY, y: 0.0 },
+        ];
+        sort_points(&mut points);
+        assert_eq!(points[0].x, f64::NEG_INFINITY);
+        assert_eq!((points[1].x, points[1].y), (1.0, 2.0));
+        assert!(points[2].y.is_nan());
+        assert!(points[3].x.is_nan() && points[4].x.is_nan());
+        assert_eq!(Point::total_cmp_by_x(&Point { x: 1.0, y: 0.0 }, &Point { x: f64::NAN, y: 0.0 }), Ordering::Less);
+    }
+
+    #[test]
+    fn equal_points_compare_equal() {
+        assert!(Point { x: 1, y: 2 } == Point { x: 1, y: 2 });
+        assert_eq!(Point { x: 1.0, y: 2.0 }.partial_cmp(&Point { x: 1.0, y: 2.0 }), Some(Ordering::Equal));
+        assert_eq!(Point { x: f64::NAN, y: 0.0 }.partial_cmp(&Point { x: 1.0, y: 0.0 }), None);
+    }
+
+    #[test]
+    fn integer_points_order_like_tuples() {
+        for (a, b, c, d) in (0..625).map(|i| (i % 5 - 2, i / 5 % 5 - 2, i / 25 % 5 - 2, i / 125 - 2)) {
+            assert_eq!(Point { x: a, y: b }.partial_cmp(&Point { x: c, y: d }), (a, b).partial_cmp(&(c, d)));
+        }
+    }
+
+    #[test]
+    fn my_module_lists_and_calls_its_functions() {
+        assert_eq!(my_module::registry().len(), 2);
+        let mut r = SectionReporter::new();
+        assert_eq!(my_module::call("public_function", &mut r), Ok(()));
+        assert_eq!(my_module::call("nested::nested_function", &mut r), Ok(()));
+        let called = [
+            "Called my_module::public_function()",
+            "Called my_module::private_function()",
+            "Called my_module::nested::nested_function()",
+        ];
+        assert_eq!(r.finish().sections[0].lines, called);
+    }
+
+    #[test]
+    fn my_module_unknown_name_is_an_error() {
+        let unknown = my_module::call("nope", &mut SectionReporter::new());
+        assert_eq!(unknown, Err(my_module::UnknownItem(String::from("nope"))));
+    }
+
+    #[test]
+    fn wrap_empty_text_has_no_lines() {
+        assert!(textwrap::wrap("", 10).is_empty());
+        assert!(textwrap::wrap("  \n\n ", 10).is_empty());
+    }
+
+    #[test]
+    fn wrap_hard_splits_words_longer_than_width() {
+        assert_eq!(textwrap::wrap("abcdefghij xy", 4), ["abcd", "efgh", "ij", "xy"]);
+        assert_eq!(textwrap::wrap("abcdefgh ab", 4), ["abcd", "efgh", "ab"]);
+        assert_eq!(textwrap::wrap("abc", 0), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn wrap_keeps_exact_width_words_whole() {
+        assert_eq!(textwrap::wrap("abcd efgh", 4), ["abcd", "efgh"]);
+        assert_eq!(textwrap::wrap("ab cd ef", 5), ["ab cd", "ef"]);
+    }
+
+    #[test]
+    fn wrap_counts_multibyte_chars_as_one() {
+        assert_eq!(textwrap::wrap("héllo wörld", 5), ["héllo", "wörld"]);
+    }
+
+    #[test]
+    fn wrap_preserves_paragraph_breaks() {
+        assert_eq!(textwrap::wrap("a b\n\n\nc", 10), ["a b", "", "c"]);
+        assert_eq!(textwrap::wrap("a\nb", 10), ["a b"]);
+    }
+
+    #[test]
+    fn indent_skips_blank_lines() {
+        assert_eq!(textwrap::indent("a\n\nb", "> "), "> a\n\n> b");
+    }
+
+    #[test]
+    fn par_map_matches_sequential_map() {
+        let numbers: Vec<u64> = (0..1000).collect();
+        let squares: Vec<u64> = numbers.iter().map(|n| n * n).collect();
+        for threads in [0, 1, 2, 3, 7, 16, 2000] {
+            assert_eq!(par_map(&numbers, threads, |n| n * n), squares, "{} threads", threads);
+        }
+    }
+
+    #[test]
+    fn par_map_of_nothing_is_empty() {
+        assert!(par_map(&Vec::<u8>::new(), 4, |n| *n).is_empty());
+    }
+
+    #[test]
+    fn adding_meters_to_millimeters_converts_units() {
+        assert_eq!(Millimeters(500) + Meters(1), Millimeters(1500));
+        assert_eq!(Millimeters(1) + Millimeters(2), Millimeters(3));
+    }
+
+    #[test]
+    fn total_length_sums_parts() {
+        assert_eq!(total_length(&[]), Millimeters(0));
+        assert_eq!(total_length(&[Millimeters(1), Millimeters(2)]), Millimeters(3));
+    }
+
+    #[test]
+    fn lengths_display_with_unit_suffix() {
+        assert_eq!(Millimeters(1500).to_string(), "1500 mm");
+        assert_eq!(Meters(2).to_string(), "2 m");
+    }
+
+    #[test]
+    fn warm_colors_are_redder_and_cool_colors_bluer() {
+        let warm = Color::from_kelvin(2000.0);
+        let cool = Color::from_kelvin(12000.0);
+        assert!(warm.0 > warm.2);
+        assert!(cool.2 > cool.0);
+        assert!(warm.0 >= cool.0);
+        assert!(cool.2 > warm.2);
+    }
+
+    #[test]
+    fn daylight_is_roughly_neutral() {
+        let daylight = Color::from_kelvin(6500.0);
+        assert!(daylight.0 >= 245 && daylight.1 >= 245 && daylight.2 >= 245, "{:?}", daylight);
+    }
+
+    #[test]
+    fn kelvin_outside_the_fitted_range_is_clamped() {
+        assert_eq!(Color::from_kelvin(10.0), Color::from_kelvin(1000.0));
+        assert_eq!(Color::from_kelvin(1e9), Color::from_kelvin(40000.0));
+    }
+
+    #[test]
+    fn traits_section_reports_tweet_summary() {
+        let data = showcase_data();
+        let mut reporter = SectionReporter::new();
+        traits_section(&mut reporter, &data.tweet, &data.article, &data.rect);
+        let report = reporter.finish();
+        let lines = &report.sections[0].lines;
+        assert!(lines.contains(&format!("Tweet summary: {}", data.tweet.summarize())));
+        assert!(lines.contains(&notify_line(&data.tweet)));
+    }
+
+    #[test]
+    fn rectangle_perimeter_and_diagonal() {
+        let rect = Rectangle { width: 30, height: 50 };
+        assert_eq!(rect.perimeter(), 160);
+        assert!((rect.diagonal() - 58.3095).abs() < 1e-3);
+        assert!(!rect.is_square());
+    }
+
+    #[test]
+    fn square_constructor_builds_a_square() {
+        let square = Rectangle::square(3);
+        assert!(square.is_square());
+        assert_eq!(square.perimeter(), 12);
+        assert!((square.diagonal() - 18f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fibonacci_starts_correctly_and_stops_before_overflow() {
+        let first: Vec<u128> = numbers::fibonacci().take(15).collect();
+        assert_eq!(first, [0, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55, 89, 144, 233, 377]);
+        // F(186) is the largest Fibonacci number that fits in a u128
+        assert_eq!(numbers::fibonacci().take(1000).count(), 187);
+        assert_eq!(
+            numbers::fibonacci().last(),
+            Some(332_825_110_087_067_562_321_196_029_789_634_457_848)
+        );
+    }
+
+    #[test]
+    fn primes_below_fifty() {
+        let small: Vec<u64> = numbers::primes().take_while(|&p| p < 50).collect();
+        assert_eq!(small, [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47]);
+    }
+
+    #[test]
+    fn is_prime_edge_cases() {
+        assert!(!numbers::is_prime(0));
+        assert!(!numbers::is_prime(1));
+        assert!(numbers::is_prime(2));
+        assert!(!numbers::is_prime(561)); // Carmichael number
+        assert!(numbers::is_prime(1_000_000_007));
+    }
+
+    #[test]
+    fn collatz_steps_handles_zero_and_overflow() {
+        assert_eq!(numbers::collatz_steps(27), Some(111));
+        assert_eq!(numbers::collatz_steps(1), Some(0));
+        assert_eq!(numbers::collatz_steps(0), None);
+        assert_eq!(numbers::collatz_steps(u64::MAX), None);
+    }
+
+    #[test]
+    fn variant_names_lists_every_variant() {
+        assert_eq!(Message::variant_names(), ["Quit", "Move", "Write", "ChangeColor"]);
+    }
+
+    #[test]
+    fn variant_name_matches_each_constructed_variant() {
+        let messages = [
+            Message::Quit,
+            Message::Move { x: 0, y: 0 },
+            Message::Write(String::new()),
+            Message::ChangeColor(0, 0, 0),
+        ];
+        for (message, name) in messages.iter().zip(Message::variant_names()) {
+            assert_eq!(message.variant_name(), *name);
+        }
+    }
+
+    #[test]
+    fn grouping_empty_input_gives_empty_maps() {
+        assert!(group_by_key(Vec::<u32>::new(), |n| *n).is_empty());
+        assert!(count_by_key(&Vec::<u32>::new(), |n| *n).is_empty());
+    }
+
+    #[test]
+    fn group_by_key_with_a_single_key_keeps_everything_together() {
+        let groups = group_by_key(vec![5, 3, 9, 1], |_| 0);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[&0], [5, 3, 9, 1]);
+    }
+
+    #[test]
+    fn group_by_key_preserves_order_within_groups() {
+        let groups = group_by_key(vec![1, 2, 3, 4, 5, 6, 7], |n| n % 2);
+        assert_eq!(groups[&1], [1, 3, 5, 7]);
+        assert_eq!(groups[&0], [2, 4, 6]);
+        assert_eq!(count_by_key(&[1, 2, 3], |n| n % 2)[&1], 2);
+    }
+
+    #[test]
+    fn upsert_score_inserts_then_adjusts() {
+        let mut scores = HashMap::new();
+        upsert_score(&mut scores, "Blue", 3);
+        upsert_score(&mut scores, "Blue", -1);
+        assert_eq!(scores["Blue"], 2);
+    }
+
+    #[test]
+    fn color_from_in_range_i64_triple() {
+        assert_eq!(Color::try_from((0i64, 128, 255)), Ok(Color(0, 128, 255)));
+        assert_eq!(Color::from((1u8, 2, 3)), Color(1, 2, 3));
+    }
+
+    #[test]
+    fn color_from_out_of_range_i64_names_the_channel() {
+        assert_eq!(
+            Color::try_from((0i64, -1, 255)),
+            Err(ColorRangeError { channel: "green", value: -1 })
+        );
+        assert_eq!(
+            Color::try_from((0i64, 1, 256)),
+            Err(ColorRangeError { channel: "blue", value: 256 })
+        );
+    }
+
+    /// Counts how many times `summarize` runs.
+    struct CountingSummary {
+        calls: Cell<u32>,
+    }
+
+    impl Summary for CountingSummary {
+        fn summarize_author(&self) -> String {
+            String::new()
+        }
+
+        fn summarize(&self) -> String {
+            self.calls.set(self.calls.get() + 1);
+            format!("call {}", self.calls.get())
+        }
+    }
+
+    #[test]
+    fn cached_summary_computes_once_until_invalidated() {
+        let mut cached = CachedSummary::new(CountingSummary { calls: Cell::new(0) });
+        assert_eq!(cached.summary(), "call 1");
+        assert_eq!(cached.summary(), "call 1");
+        assert_eq!(cached.inner.calls.get(), 1);
+
+        cached.invalidate();
+        assert_eq!(cached.summary(), "call 2");
+        assert_eq!(cached.inner.calls.get(), 2);
+    }
+
+    fn user_with_email(email: &str) -> User {
+        User { username: String::from("pat"), email: email.to_string(), sign_in_count: 0, active: true }
+    }
+
+    #[test]
+    fn mutating_a_clone_leaves_the_original_alone() {
+        let original = user_with_email("pat@example.com");
+        let mut copy = original.clone();
+        copy.username.push_str("-copy");
+        copy.sign_in_count = 9;
+        copy.active = false;
+
+        assert_eq!(original.username, "pat");
+        assert_eq!(original.sign_in_count, 0);
+        assert!(original.active);
+    }
+
+    #[test]
+    fn copy_struct_is_usable_after_pass_by_value() {
+        let dims = Dimensions { w: 2, h: 5 };
+        assert_eq!(area_of(dims), 10);
+        assert_eq!(dims, Dimensions { w: 2, h: 5 });
+    }
+
+    fn parse_all(text: &str) -> Vec<Result<records::Record, records::RecordError>> {
+        records::parse_records(std::io::Cursor::new(text)).collect()
+    }
+
+    #[test]
+    fn parse_records_skips_comments_and_blank_lines() {
+        let parsed = parse_all("# header\n\na,1,x;y\n  # indented comment\n");
+        let tags = vec![String::from("x"), String::from("y")];
+        assert_eq!(parsed, [Ok(records::Record { name: String::from("a"), score: 1, tags })]);
+    }
+
+    #[test]
+    fn parse_records_reports_errors_in_position_and_keeps_going() {
+        use records::{RecordError, RecordErrorKind};
+
+        let parsed = parse_all("a,1\nb\nc,zz\nd,4\n");
+        assert_eq!(parsed.len(), 4);
+        assert!(parsed[0].is_ok());
+        assert_eq!(parsed[1], Err(RecordError { line: 2, kind: RecordErrorKind::MissingField("score") }));
+        assert_eq!(parsed[2], Err(RecordError { line: 3, kind: RecordErrorKind::BadInteger(String::from("zz")) }));
+        assert!(parsed[3].is_ok());
+    }
+
+    #[test]
+    fn parse_records_is_lazy() {
+        let mut records = records::parse_records(std::io::Cursor::new("x\ny,2"));
+        assert!(records.next().unwrap().is_err());
+        assert!(records.next().unwrap().is_ok());
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn record_tags_are_optional() {
+        for line in ["d,4", "e,5,"] {
+            let record = parse_all(line).remove(0).unwrap();
+            assert!(record.tags.is_empty(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn srgb_round_trip_is_within_one_per_channel() {
+        for v in 0..=255u8 {
+            let color = Color(v, 255 - v, v / 2);
+            let back = Color::from_linear(color.to_linear());
+            let close = |a: u8, b: u8| (i32::from(a) - i32::from(b)).abs() <= 1;
+            assert!(close(color.0, back.0) && close(color.1, back.1) && close(color.2, back.2), "{:?}", color);
+        }
+    }
+
+    #[test]
+    fn mid_gray_linearizes_to_about_a_fifth() {
+        let linear = Color(128, 128, 128).to_linear();
+        assert!((linear[0] - 0.2158).abs() < 1e-3, "{:?}", linear);
+    }
+
+    #[test]
+    fn gamma_of_one_is_identity() {
+        assert_eq!(Color(0, 255, 128).gamma(1.0), Color(0, 255, 128));
+        assert!(Color(128, 0, 0).gamma(2.2).0 > 128);
+    }
+
+    #[test]
+    fn counters_count_single_threaded() {
+        use counters::Counter;
+        let cell = counters::CellCounter::new();
+        let refcell = counters::RefCellCounter::new();
+        let mutex = counters::MutexCounter::new();
+        for _ in 0..3 {
+            cell.increment();
+            refcell.increment();
+            mutex.increment();
+        }
+        assert_eq!((cell.value(), refcell.value(), mutex.value()), (3, 3, 3));
+        assert_eq!(refcell.history(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn refcell_counter_refuses_double_borrow() {
+        assert!(counters::RefCellCounter::new().try_double_borrow().is_err());
+    }
+
+    #[test]
+    fn mutex_counter_counts_across_threads() {
+        use counters::Counter;
+        let counter = Arc::new(counters::MutexCounter::new());
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let counter = Arc::clone(&counter);
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        counter.increment();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(counter.value(), 8000);
+    }
+
+    #[test]
+    fn matches_yields_every_index_in_order() {
+        assert_eq!(matches(&[1, 2, 1, 3, 1], &1).collect::<Vec<_>>(), [0, 2, 4]);
+        assert_eq!(matches(&[1, 2, 3], &7).count(), 0);
+    }
+
+    #[test]
+    fn matches_stops_at_the_hits_taken() {
+        // Every third element is a hit; taking two must not need the rest of the haystack
+        let haystack: Vec<u32> = (0..100_000).map(|i| i % 3).collect();
+        assert_eq!(matches(&haystack, &2).take(2).collect::<Vec<_>>(), [2, 5]);
+    }
+
+    fn cells(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn format_table_pads_to_widest_cell() {
+        // "name" is wider than any cell below it; "100" is wider than the header "n"
+        let table = format_table(&["name", "n"], &[cells(&["ab", "100"]), cells(&["é", "7"])]);
+        assert_eq!(table, "name | n\n-----+----\nab   | 100\né    | 7");
+    }
+
+    #[test]
+    fn format_table_without_rows_is_just_the_header() {
+        assert_eq!(format_table(&["a"], &[]), "a\n-");
+    }
+
+    #[test]
+    fn format_table_allows_rows_wider_than_the_header() {
+        assert_eq!(format_table(&["a"], &[cells(&["x", "y"])]), "a |\n--+--\nx | y");
+    }
+
+    #[test]
+    fn equal_rectangles_contain_but_cannot_hold_each_other() {
+        let a = Rectangle { width: 30, height: 50 };
+        let b = Rectangle { width: 30, height: 50 };
+        assert!(!a.can_hold(&b)); // `can_hold` is strict
+        assert!(a.contains_rect(&b)); // Touching edges still count as inside
+    }
+
+    #[test]
+    fn contains_rect_rejects_anything_wider() {
+        let a = Rectangle { width: 30, height: 50 };
+        assert!(!a.contains_rect(&Rectangle { width: 31, height: 1 }));
+        assert!(a.contains_rect(&Rectangle { width: 0, height: 0 }));
+    }
+
+    /// A breaker (threshold 3, cooldown 10s) whose clock reads `start + offset` seconds.
+    fn breaker_with_clock() -> (CircuitBreaker, Rc<Cell<u64>>) {
+        let start = Instant::now();
+        let offset = Rc::new(Cell::new(0));
+        let clock = Rc::clone(&offset);
+        let now = Box::new(move || start + Duration::from_secs(clock.get()));
+        (CircuitBreaker::with_clock(3, Duration::from_secs(10), now), offset)
+    }
+
+    fn failing() -> Result<(), &'static str> {
+        Err("boom")
+    }
+
+    #[test]
+    fn breaker_opens_exactly_at_the_threshold() {
+        let (mut breaker, _) = breaker_with_clock();
+        assert_eq!(breaker.call(failing), Err(BreakerError::Inner("boom")));
+        assert_eq!(breaker.call(failing), Err(BreakerError::Inner("boom")));
+        assert_eq!(breaker.state(), BreakerState::Closed { failures: 2 });
+
+        assert_eq!(breaker.call(failing), Err(BreakerError::Inner("boom")));
+        assert!(matches!(breaker.state(), BreakerState::Open { .. }));
+    }
+
+    #[test]
+    fn open_breaker_rejects_without_running() {
+        let (mut breaker, offset) = breaker_with_clock();
+        for _ in 0..3 {
+            let _ = breaker.call(failing);
+        }
+        let mut ran = false;
+        let result = breaker.call(|| {
+            ran = true;
+            Ok::<_, &str>(1)
+        });
+        assert_eq!(result, Err(BreakerError::Open));
+        assert!(!ran);
+
+        offset.set(9); // Still inside the cooldown
+        assert_eq!(breaker.call(|| Ok::<_, &str>(2)), Err(BreakerError::Open));
+    }
+
+    #[test]
+    fn half_open_failure_reopens() {
+        let (mut breaker, offset) = breaker_with_clock();
+        for _ in 0..3 {
+            let _ = breaker.call(failing);
+        }
+        offset.set(10);
+        assert_eq!(breaker.call(failing), Err(BreakerError::Inner("boom")));
+        assert!(matches!(breaker.state(), BreakerState::Open { .. }));
+
+        offset.set(15); // The cooldown restarted at 10s
+        assert_eq!(breaker.call(|| Ok::<_, &str>(3)), Err(BreakerError::Open));
+    }
+
+    #[test]
+    fn half_open_success_closes() {
+        let (mut breaker, offset) = breaker_with_clock();
+        for _ in 0..3 {
+            let _ = breaker.call(failing);
+        }
+        offset.set(10);
+        assert_eq!(breaker.call(|| Ok::<_, &str>(4)), Ok(4));
+        assert_eq!(breaker.state(), BreakerState::Closed { failures: 0 });
+        assert_eq!(breaker.call(|| Ok::<_, &str>(5)), Ok(5));
+    }
+
+    #[test]
+    fn average_of_black_and_white_is_mid_gray() {
+        let average = Color::average(&[Color(0, 0, 0), Color(255, 255, 255)]);
+        assert_eq!(average, Some(Color(128, 128, 128))); // 127.5 rounds half up
+    }
+
+    #[test]
+    fn average_of_nothing_is_none() {
+        assert_eq!(Color::average(&[]), None);
+    }
+
+    #[test]
+    fn average_of_many_bright_colors_does_not_overflow() {
+        assert_eq!(Color::average(&vec![Color(255, 1, 0); 1000]), Some(Color(255, 1, 0)));
+    }
+
+    #[test]
+    fn rectangle_render_is_scaled_down() {
+        let rendered = Rectangle { width: 30, height: 50 }.render();
+        assert_eq!(rendered.lines().count(), 10);
+        assert!(rendered.lines().all(|line| line.len() == 6), "{}", rendered);
+    }
+
+    #[test]
+    fn degenerate_rectangle_renders_as_empty() {
+        assert_eq!(Rectangle { width: 0, height: 9 }.render(), "");
+        assert_eq!(Rectangle { width: 1, height: 1 }.render_scaled(0), "#"); // Cell size 0 is treated as 1
+    }
+
+    #[test]
+    fn color_renders_as_swatch_and_hex() {
+        assert_eq!(Color(255, 128, 0).render(), "[###] #ff8000");
+    }
+
+    #[test]
+    fn side_by_side_pads_shorter_blocks() {
+        let joined = render_side_by_side(&[
+            &Rectangle { width: 10, height: 15 },
+            &Rectangle { width: 5, height: 5 },
+        ]);
+        assert_eq!(joined, "##  #\n##\n##");
+        assert_eq!(render_side_by_side(&[]), "");
+    }
+
+    #[test]
+    fn color_round_trips_through_change_color() {
+        let color = Color(9, 8, 7);
+        let message: Message = color.into();
+        assert_eq!(message, Message::ChangeColor(9, 8, 7));
+        assert_eq!(message.as_color(), Some(color));
+    }
+
+    #[test]
+    fn as_color_is_none_for_other_variants() {
+        assert_eq!(Message::Write(String::from("x")).as_color(), None);
+        assert_eq!(Message::Quit.as_color(), None);
+    }
+
+    /// An observer that records `(name, event)` into the shared log.
+    fn recording_observer(name: &'static str, log: &Rc<RefCell<Vec<(&'static str, i32)>>>) -> Rc<Observer<i32>> {
+        let log = Rc::clone(log);
+        Rc::new(move |event: &i32| log.borrow_mut().push((name, *event)))
+    }
+
+    #[test]
+    fn observers_fire_in_subscription_order_across_subjects() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let a = recording_observer("a", &log);
+        let b = recording_observer("b", &log);
+        let (mut first, mut second) = (Subject::new(), Subject::new());
+        first.subscribe(&a);
+        first.subscribe(&b);
+        second.subscribe(&a); // The same observer on two subjects
+
+        assert_eq!(first.notify(&1), 2);
+        assert_eq!(second.notify(&2), 1);
+        assert_eq!(*log.borrow(), [("a", 1), ("b", 1), ("a", 2)]);
+    }
+
+    #[test]
+    fn dropped_observers_are_pruned_on_notify() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let a = recording_observer("a", &log);
+        let b = recording_observer("b", &log);
+        let mut subject = Subject::new();
+        subject.subscribe(&a);
+        subject.subscribe(&b);
+
+        drop(a);
+        assert_eq!(subject.observer_count(), 2); // Pruning happens lazily
+        assert_eq!(subject.notify(&3), 1);
+        assert_eq!(subject.observer_count(), 1);
+        assert_eq!(*log.borrow(), [("b", 3)]);
+    }
+
+    #[test]
+    fn join_timeout_returns_a_fast_threads_result() {
+        let fast = thread::spawn(|| 7);
+        assert_eq!(join_timeout(fast, Duration::from_secs(2)), Ok(7));
+    }
+
+    #[test]
+    fn join_timeout_gives_up_on_a_slow_thread() {
+        let slow = thread::spawn(|| thread::sleep(Duration::from_millis(300)));
+        let waited = Duration::from_millis(20);
+        assert_eq!(join_timeout(slow, waited), Err(JoinTimeout { waited }));
+    }
+
+    #[test]
+    fn permissions_display_and_parse_round_trip() {
+        assert_eq!(Permissions::NONE.to_string(), "---");
+        assert_eq!(Permissions::ALL.to_string(), "rwx");
+        assert_eq!(Permissions::parse("---"), Ok(Permissions::NONE));
+        assert_eq!(Permissions::parse("rwx"), Ok(Permissions::ALL));
+        assert_eq!(Permissions::parse("r-x"), Ok(Permissions::READ | Permissions::EXECUTE));
+    }
+
+    #[test]
+    fn permissions_parse_rejects_bad_input() {
+        assert_eq!(Permissions::parse("rwz"), Err(PermParseError::InvalidChar { position: 2, found: 'z' }));
+        assert_eq!(Permissions::parse("xw-"), Err(PermParseError::InvalidChar { position: 0, found: 'x' }));
+        assert_eq!(Permissions::parse("rw"), Err(PermParseError::WrongLength(2)));
+        assert_eq!(Permissions::from_bits(0x80), Err(PermParseError::UnknownBits(0x80)));
+    }
+
+    #[test]
+    fn bit_operators_preserve_unknown_bits() {
+        let high = Permissions(0x80) | Permissions::READ;
+        assert_eq!(high.0, 0x84);
+        assert_eq!(high & Permissions::ALL, Permissions::READ);
+        assert_eq!((!high).0, 0b011); // `Not` only flips the known bits
+    }
+
+    #[test]
+    fn permissions_insert_remove_and_contains() {
+        let mut perms = Permissions::NONE;
+        perms.insert(Permissions::WRITE);
+        assert!(perms.contains(Permissions::WRITE));
+        perms.remove(Permissions::WRITE);
+        assert_eq!(perms, Permissions::NONE);
+        assert!(Permissions::ALL.contains(Permissions::NONE));
+    }
+
+    #[test]
+    fn rectangle_summarizes_via_display() {
+        let rect = Rectangle { width: 30, height: 50 };
+        assert_eq!(rect.summarize(), rect.to_string());
+        assert_eq!(rect.summarize(), "Rectangle(30x50)");
+    }
+
+    fn short_tweet(username: &str, content: &str) -> Tweet {
+        Tweet::new(username, content, false, false).unwrap()
+    }
+
+    #[test]
+    fn summarize_and_compare_prefers_first_on_ties() {
+        let (a, b) = (short_tweet("ab", "x"), short_tweet("ba", "y"));
+        let compared = summarize_and_compare(&a, &b);
+        assert!(compared.starts_with("first"), "{}", compared);
+        assert!(compared.ends_with("@ab: x"), "{}", compared);
+    }
+
+    #[test]
+    fn summarize_and_compare_mixes_concrete_types() {
+        let tweet = short_tweet("ab", "x");
+        let article = NewsArticle {
+            headline: String::from("h"),
+            location: String::from("l"),
+            author: String::from("z"),
+            content: String::from("c"),
+        };
+        assert!(summarize_and_compare(&tweet, &article).starts_with("second"));
+        let dynamic: &dyn Summary = &article; // `?Sized` allows trait objects too
+        assert!(summarize_and_compare(dynamic, &tweet).starts_with("first"));
+    }
+
+    #[test]
+    fn largest_summary_picks_the_earliest_longest() {
+        assert!(largest_summary::<Tweet>(&[]).is_none());
+        let tweets = [short_tweet("ab", "x"), short_tweet("ba", "y")];
+        assert_eq!(largest_summary(&tweets).unwrap().username, "ab");
+    }
+
+    #[test]
+    fn print_all_shows_display_and_debug() {
+        let mut r = SectionReporter::new();
+        print_all(&mut r, &[1, 2]);
+        assert_eq!(r.finish().to_string(), "1 / 1\n2 / 2\n");
+    }
+
+    #[test]
+    fn clamp_keeps_points_inside_and_pulls_outside_ones_in() {
+        let (lo, hi) = (Point { x: 0, y: 0 }, Point { x: 10, y: 10 });
+        assert!(Point { x: 5, y: 6 }.clamp(lo, hi) == Point { x: 5, y: 6 });
+        assert!(Point { x: -5, y: 60 }.clamp(lo, hi) == Point { x: 0, y: 10 });
+    }
+
+    #[test]
+    fn clamp_with_swapped_bounds_uses_the_same_box() {
+        let clamped = Point { x: -5, y: 60 }.clamp(Point { x: 10, y: 10 }, Point { x: 0, y: 0 });
+        assert!(clamped == Point { x: 0, y: 10 });
+    }
+
+    #[test]
+    fn normalized_has_unit_length() {
+        let unit = Point { x: 3.0, y: 4.0 }.normalized().unwrap();
+        assert!((unit.x.hypot(unit.y) - 1.0).abs() < 1e-12);
+        assert!((unit.x - 0.6).abs() < 1e-12 && (unit.y - 0.8).abs() < 1e-12);
+        assert!(Point { x: 0.0, y: 0.0 }.normalized().is_none());
+    }
+
+    #[test]
+    fn run_with_timeout_returns_fast_result() {
+        assert_eq!(run_with_timeout(|| 5, Duration::from_secs(2)), Ok(5));
+    }
+
+    #[test]
+    fn run_with_timeout_gives_up_without_blocking() {
+        let start = Instant::now();
+        let slow = || {
+            thread::sleep(Duration::from_secs(3));
+            1
+        };
+        let result = run_with_timeout(slow, Duration::from_millis(30));
+        assert_eq!(result, Err(TimeoutError::TimedOut { after: Duration::from_millis(30) }));
+        // The caller got control back long before the worker finishes
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn run_with_timeout_reports_panicked_worker() {
+        let result: Result<i32, _> = run_with_timeout(|| panic!("worker failed"), Duration::from_secs(2));
+        assert_eq!(result, Err(TimeoutError::WorkerPanicked));
+    }
+
+    #[test]
+    fn cancellable_timeout_sets_flag_seen_by_worker() {
+        let (sender, receiver) = mpsc::channel();
+        let result = run_with_timeout_cancellable(
+            move |cancelled| {
+                while !cancelled.load(AtomicOrdering::Relaxed) {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                sender.send(true).unwrap();
+            },
+            Duration::from_millis(20),
+        );
+        assert!(matches!(result, Err(TimeoutError::TimedOut { .. })));
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(2)), Ok(true));
+    }
+
+    #[test]
+    fn cancellable_timeout_leaves_flag_of_panicked_worker() {
+        let (sender, receiver) = mpsc::channel();
+        let result: Result<(), _> = run_with_timeout_cancellable(
+            move |cancelled| {
+                sender.send(cancelled).unwrap();
+                panic!("worker failed");
+            },
+            Duration::from_secs(2),
+        );
+        assert_eq!(result, Err(TimeoutError::WorkerPanicked));
+        let flag = receiver.recv().unwrap();
+        assert!(!flag.load(AtomicOrdering::Relaxed));
+    }
+
+    #[test]
+    fn divide_slices_divides_pairwise() {
+        assert_eq!(divide_slices(&[4.0, 9.0], &[2.0, 3.0]), Ok(vec![2.0, 3.0]));
+        assert_eq!(divide_slices(&[], &[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn divide_slices_reports_the_first_failing_index() {
+        let result = divide_slices(&[1.0, 1.0, 1.0, 1.0], &[1.0, 1.0, 0.0, 0.0]);
+        assert!(matches!(result, Err(DivideError::AtIndex { index: 2, .. })), "{:?}", result);
+    }
+
+    #[test]
+    fn divide_slices_rejects_unequal_lengths() {
+        assert_eq!(
+            divide_slices(&[1.0], &[]),
+            Err(DivideError::LengthMismatch { numerators: 1, denominators: 0 })
+        );
+    }
+
+    fn same(text: &str) -> diffing::DiffLine {
+        diffing::DiffLine::Same(text.to_string())
+    }
+
+    fn added(text: &str) -> diffing::DiffLine {
+        diffing::DiffLine::Added(text.to_string())
+    }
+
+    fn removed(text: &str) -> diffing::DiffLine {
+        diffing::DiffLine::Removed(text.to_string())
+    }
+
+    #[test]
+    fn diff_of_identical_texts_is_all_same() {
+        assert_eq!(diffing::diff_lines("a\nb", "a\nb"), vec![same("a"), same("b")]);
+    }
+
+    #[test]
+    fn diff_shows_inserted_line() {
+        assert_eq!(diffing::diff_lines("a\nc", "a\nb\nc"), vec![same("a"), added("b"), same("c")]);
+        assert_eq!(diffing::diff_lines("", "x"), vec![added("x")]);
+    }
+
+    #[test]
+    fn diff_shows_deleted_line() {
+        assert_eq!(diffing::diff_lines("a\nb\nc", "a\nc"), vec![same("a"), removed("b"), same("c")]);
+        assert_eq!(diffing::diff_lines("x", ""), vec![removed("x")]);
+    }
+
+    #[test]
+    fn diff_shows_replaced_line_as_removed_then_added() {
+        let diff = diffing::diff_lines("a\nx\nc", "a\ny\nc");
+        assert_eq!(diff, vec![same("a"), removed("x"), added("y"), same("c")]);
+        assert_eq!(diffing::render(&diff), " a\n-x\n+y\n c");
+    }
+
+    #[test]
+    fn normalize_output_masks_durations() {
+        assert_eq!(normalize_output("took 1.5ms"), "took <duration>");
+        assert_eq!(normalize_output("x 12µs y 30ns z 2us"), "x <duration> y <duration> z <duration>");
+        assert_eq!(normalize_output("waited 3s\n"), "waited <duration>\n");
+    }
+
+    #[test]
+    fn normalize_output_keeps_other_tokens() {
+        assert_eq!(normalize_output("20 items, ms and s"), "20 items, ms and s");
+        // Only whole space-separated tokens are durations
+        assert_eq!(normalize_output("3s, 1.5ms."), "3s, 1.5ms.");
+    }
+
+    #[test]
+    fn snapshots_match() {
+        assert_eq!(verify_snapshots(), 0);
+    }
+
+    fn dims(rect: Rectangle) -> (u32, u32) {
+        (rect.width, rect.height)
+    }
+
+    #[test]
+    fn inflate_grows_both_sides() {
+        assert_eq!(dims(Rectangle { width: 30, height: 50 }.inflate(5, 2)), (40, 54));
+    }
+
+    #[test]
+    fn deflate_clamps_at_zero() {
+        assert_eq!(dims(Rectangle { width: 30, height: 50 }.inflate(-20, -10)), (0, 30));
+        assert_eq!(dims(Rectangle { width: u32::MAX, height: 0 }.inflate(1, i32::MIN)), (u32::MAX, 0));
+    }
+
+    #[test]
+    fn inflate_by_zero_is_a_no_op() {
+        assert_eq!(dims(Rectangle { width: 30, height: 50 }.inflate(0, 0)), (30, 50));
+    }
+
+    #[test]
+    fn sparse_table_computes_each_pair_once() {
+        let mut table: SparseTable<u32, u32, u32> = SparseTable::default();
+        let mut calls = 0;
+        for _ in 0..3 {
+            for a in 0..2 {
+                for b in 0..3 {
+                    table.get_or_compute(a, b, |x, y| {
+                        calls += 1;
+                        x * 10 + y
+                    });
+                }
+            }
+        }
+        assert_eq!(calls, 6);
+        assert_eq!(table.len(), 6);
+        assert_eq!((table.hits(), table.misses()), (12, 6));
+        assert_eq!(*table.get_or_compute(1, 2, |_, _| 0), 12);
+    }
+
+    #[test]
+    fn sparse_table_from_iter_keeps_the_last_duplicate() {
+        let mut table: SparseTable<u8, u8, &str> = vec![((1, 1), "first"), ((1, 1), "last")].into_iter().collect();
+        assert_eq!(table.len(), 1);
+        assert_eq!(*table.get_or_compute(1, 1, |_, _| "computed"), "last");
+        assert_eq!(table.hits(), 1);
+    }
+
+    #[test]
+    fn largest_by_with_reversed_comparator_finds_the_smallest() {
+        assert_eq!(largest_by(&[3, 1, 2], |a, b| b.cmp(a)), Some(&1));
+        assert_eq!(largest_by::<i32, _>(&[], |a, b| a.cmp(b)), None);
+    }
+
+    #[test]
+    fn largest_by_keeps_the_first_of_equal_elements() {
+        let points = [Point { x: 1, y: 5 }, Point { x: 2, y: 5 }, Point { x: 0, y: 1 }];
+        let highest = largest_by(&points, |a, b| a.y.cmp(&b.y)).unwrap();
+        assert_eq!(highest.x, 1);
+    }
+
+    #[test]
+    fn borrowing_registry_formats_with_local_state() {
+        let prefix = String::from("area");
+        let mut registry = FormatterRegistry::new();
+        registry.register(|r| format!("{}:{}", prefix, r.area())); // Borrows `prefix`
+        registry.register(|r| format!("w{}", r.width));
+        assert_eq!(registry.format_all(&Rectangle { width: 2, height: 3 }), ["area:6", "w2"]);
+    }
+
+    #[test]
+    fn owned_registry_outlives_its_constructor() {
+        let registry = owned_registry("px");
+        assert_eq!(registry.format_all(&Rectangle { width: 2, height: 3 }), ["2x3 px"]);
+    }
+
+    #[test]
+    fn feed_digest_round_trips_tabs_and_newlines() {
+        let mut feed = Feed::new();
+        feed.push(Tweet::new("a\tb", "x\ty\\z\nw", false, false).unwrap());
+        feed.push(Rectangle::square(2));
+
+        let digest = feed.to_digest();
+        assert_eq!(digest.lines().count(), 2); // One line per item despite the embedded newline
+        let parsed = Feed::from_digest(&digest).unwrap();
+        assert_eq!(parsed[0], (String::from("@a\tb"), String::from("@a\tb: x\ty\\z\nw")));
+        assert_eq!(parsed[1].1, Rectangle::square(2).summarize());
+    }
+
+    #[test]
+    fn feed_from_digest_reports_the_bad_line() {
+        assert_eq!(Feed::from_digest("a\tb\nnope"), Err(DigestError::MissingTab { line: 2 }));
+        assert_eq!(Feed::from_digest("a\\q\tb"), Err(DigestError::BadEscape { line: 1 }));
+    }
+
+    #[test]
+    fn percent_clamps_to_range() {
+        assert_eq!(Percent::new(150.0), Percent(100.0));
+        assert_eq!(Percent::new(-3.0), Percent(0.0));
+        assert_eq!(Percent::new(f64::NAN), Percent(0.0));
+    }
+
+    #[test]
+    fn percent_arithmetic_clamps() {
+        assert_eq!(Percent::new(90.0) + Percent::new(20.0), Percent(100.0));
+        assert_eq!(Percent::new(10.0) - Percent::new(20.0), Percent(0.0));
+    }
+
+    #[test]
+    fn percent_fraction_and_display() {
+        assert_eq!(Percent::new(25.0).as_fraction(), 0.25);
+        assert_eq!(Percent::new(42.0).to_string(), "42.0%");
+    }
+
+    #[test]
+    fn parse_age_trims_whitespace() {
+        use validation::{parse_age, AgeError};
+        assert_eq!(parse_age("  30\n"), Ok(30));
+        assert_eq!(parse_age(""), Err(AgeError::Empty));
+        assert_eq!(parse_age("   "), Err(AgeError::Empty));
+    }
+
+    #[test]
+    fn parse_age_rejects_prefixes_and_negatives() {
+        use validation::{parse_age, AgeError};
+        assert_eq!(parse_age("0x10"), Err(AgeError::NotANumber { original: String::from("0x10") }));
+        assert_eq!(parse_age("-1"), Err(AgeError::Negative));
+    }
+
+    #[test]
+    fn parse_age_range_boundaries() {
+        use validation::{parse_age, AgeError};
+        assert_eq!(parse_age("150"), Ok(150));
+        assert_eq!(parse_age("151"), Err(AgeError::OutOfRange { value: 151, max: 150 }));
+        assert_eq!(parse_age("255"), Err(AgeError::OutOfRange { value: 255, max: 150 }));
+        // Too big for i64 is still "out of range", not "not a number"
+        assert_eq!(parse_age("99999999999999999999"), Err(AgeError::OutOfRange { value: i64::MAX, max: 150 }));
+    }
+
+    #[test]
+    fn parse_rgb_decimal_form() {
+        use validation::{parse_rgb, RgbError};
+        assert_eq!(parse_rgb(" 255, 128 ,0 "), Ok(Color(255, 128, 0)));
+        assert_eq!(parse_rgb("255,256,0"), Err(RgbError::OutOfRange { value: 256 }));
+        assert_eq!(parse_rgb("0x1,0,0"), Err(RgbError::NotANumber { original: String::from("0x1") }));
+        assert_eq!(parse_rgb("0x123456"), Err(RgbError::ComponentCount { found: 1 }));
+    }
+
+    #[test]
+    fn parse_rgb_hex_form_accepts_any_case() {
+        use validation::{parse_rgb, RgbError};
+        assert_eq!(parse_rgb("#ff80aB"), Ok(Color(255, 128, 171)));
+        assert_eq!(parse_rgb("#+f8000"), Err(RgbError::InvalidHex { original: String::from("#+f8000") }));
+    }
+
+    const LOG_SAMPLE: [&str; 7] = ["INFO a", "DEBUG b", "bad", "ERROR c", "INFO d", "NOPE x", "WARN"];
+
+    #[test]
+    fn pipeline_counts_levels_and_skips_malformed_lines() {
+        use pipeline::{process_iter, Level};
+        let stats = process_iter(&LOG_SAMPLE);
+        assert_eq!(stats.skipped, 3);
+        assert_eq!(stats.counts[&Level::Info], 2);
+        assert!(!stats.counts.contains_key(&Level::Debug)); // Filtered out by stage 2
+    }
+
+    #[test]
+    fn threaded_pipeline_matches_iterator_pipeline() {
+        use pipeline::{process_iter, process_threaded};
+        let owned = LOG_SAMPLE.iter().map(|line| line.to_string()).collect();
+        assert_eq!(process_iter(&LOG_SAMPLE), process_threaded(owned));
+        assert_eq!(process_iter(&[]), process_threaded(vec![]));
+    }
+
+    #[test]
+    fn level_parsing_is_case_sensitive() {
+        assert_eq!("WARN".parse::<pipeline::Level>(), Ok(pipeline::Level::Warn));
+        assert!("warn".parse::<pipeline::Level>().is_err());
+    }
+
+    #[test]
+    fn factorial_rejects_inputs_above_the_limit() {
+        assert_eq!(factorial(MAX_FACTORIAL_INPUT + 1), None);
+        assert_eq!(factorial(u64::MAX), None); // Rejected before recursing
+        assert_eq!(factorial_iter(MAX_FACTORIAL_INPUT + 1), None);
+    }
+
+    #[test]
+    fn recursive_and_iterative_factorial_agree() {
+        for n in 0..=MAX_FACTORIAL_INPUT {
+            assert_eq!(factorial(n), factorial_iter(n), "{}!", n);
+        }
+        assert_eq!(factorial(0), Some(1));
+        assert_eq!(factorial_iter(20), Some(2_432_902_008_176_640_000));
+    }
+
+    #[test]
+    fn last_char_of_first_line_only_looks_at_the_first_line() {
+        assert_eq!(last_char_of_first_line(""), None);
+        assert_eq!(last_char_of_first_line("\nsecond"), None);
+        assert_eq!(last_char_of_first_line("ab\ncd"), Some('b'));
+    }
+
+    #[test]
+    fn first_word_longer_than_borrows_from_the_input() {
+        assert_eq!(first_word_longer_than("", 0), None);
+        assert_eq!(first_word_longer_than("a bb ccc", 1), Some("bb"));
+        assert_eq!(first_word_longer_than("a bb ccc", 3), None);
+    }
+
+    #[test]
+    fn first_line_number_converts_none_into_an_error() {
+        assert_eq!(first_line_number("7"), Ok(7));
+        assert_eq!(first_line_number(""), Err(String::from("text is empty")));
+        assert!(first_line_number("\n5").is_err()); // The first line is blank
+    }
+
+    #[test]
+    fn group_by_domain_buckets_users_and_skips_malformed_emails() {
+        let users = vec![
+            user_with_email("a@x.com"),
+            user_with_email("b@y.org"),
+            user_with_email("c@x.com"),
+            user_with_email("bad"),
+        ];
+        let groups = group_by_domain(&users);
+        assert_eq!(groups.len(), 2);
+        let emails: Vec<&str> = groups["x.com"].iter().map(|u| u.email.as_str()).collect();
+        assert_eq!(emails, ["a@x.com", "c@x.com"]);
+        assert_eq!(groups["y.org"].len(), 1);
+        assert!(!groups.contains_key(""));
+    }
+
+    fn reply_tweet() -> Tweet {
+        Tweet { username: String::from("a"), content: String::from("c"), reply: true, retweet: false }
+    }
+
+    fn short_article() -> NewsArticle {
+        NewsArticle {
+            headline: String::from("h"),
+            location: String::from("l"),
+            author: String::from("au"),
+            content: String::from("x"),
+        }
+    }
+
+    #[test]
+    fn enum_and_dyn_feeds_summarize_identically() {
+        let dynamic: Vec<Box<dyn Summary>> = vec![Box::new(reply_tweet()), Box::new(short_article())];
+        let enumerated: Vec<SummaryItem> = vec![reply_tweet().into(), short_article().into()];
+        assert_eq!(summarize_dyn_feed(&dynamic), summarize_enum_feed(&enumerated));
+    }
+
+    #[test]
+    fn summary_item_from_preserves_fields() {
+        match SummaryItem::from(reply_tweet()) {
+            SummaryItem::Tweet(tweet) => assert!(tweet.username == "a" && tweet.reply),
+            _ => panic!("expected the Tweet variant"),
+        }
+        match SummaryItem::from(short_article()) {
+            SummaryItem::Article(article) => {
+                assert_eq!((article.headline.as_str(), article.author.as_str()), ("h", "au"));
+            }
+            _ => panic!("expected the Article variant"),
+        }
+    }
+
+    #[test]
+    fn dedup_colors_keeps_first_seen_order() {
+        let palette = [Color(1, 1, 1), Color(2, 2, 2), Color(1, 1, 1), Color(3, 3, 3), Color(2, 2, 2)];
+        assert_eq!(dedup_colors(&palette), [Color(1, 1, 1), Color(2, 2, 2), Color(3, 3, 3)]);
+        assert_eq!(dedup_colors(&[]), []);
+    }
+
+    #[test]
+    fn dedup_similar_collapses_near_duplicates() {
+        let palette = [Color(100, 100, 100), Color(103, 104, 100), Color(200, 0, 0), Color(106, 108, 100)];
+        // (103, 104, 100) is 5.0 away from the first kept color; (106, 108, 100) is 10.0 away
+        assert_eq!(dedup_similar(&palette, 5.0), [Color(100, 100, 100), Color(200, 0, 0), Color(106, 108, 100)]);
+        assert_eq!(dedup_similar(&palette, 0.0), palette);
+    }
+
+    fn coord(x: i32, y: i32) -> GridCoord {
+        GridCoord { x, y }
+    }
+
+    #[test]
+    fn occupied_neighbors_at_the_edge_skip_missing_cells() {
+        let mut world = WorldMap::new();
+        assert!(world.place(coord(i32::MAX, 0), Rectangle::square(1)).is_none());
+        world.place(coord(i32::MAX, 1), Rectangle::square(2));
+        world.place(coord(i32::MAX - 1, 0), Rectangle::square(3));
+        assert_eq!(world.occupied_neighbors(coord(i32::MAX, 0)), [coord(i32::MAX, 1), coord(i32::MAX - 1, 0)]);
+        assert!(world.occupied_neighbors(coord(5, 5)).is_empty());
+    }
+
+    #[test]
+    fn placing_over_a_cell_returns_the_old_rectangle() {
+        let mut world = WorldMap::new();
+        world.place(coord(0, 0), Rectangle::square(2));
+        let old = world.place(coord(0, 0), Rectangle::square(9)).unwrap();
+        assert_eq!(old.width, 2);
+    }
+
+    #[test]
+    fn color_names_look_up_by_freshly_built_keys() {
+        let names = color_names();
+        assert_eq!(names[&Color(0, 0, 255)], "blue");
+        assert!(!names.contains_key(&Color(1, 2, 3)));
+    }
+
+    fn article_with_content(content: &str) -> NewsArticle {
+        NewsArticle {
+            headline: String::from("h"),
+            location: String::from("l"),
+            author: String::from("a"),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn words_split_on_unicode_whitespace() {
+        let article = article_with_content(" The\u{3000}quick\tbrown\n fox ");
+        assert_eq!(article.words().collect::<Vec<_>>(), ["The", "quick", "brown", "fox"]);
+    }
+
+    #[test]
+    fn blank_content_has_no_words() {
+        assert_eq!(article_with_content(" \t\n").words().count(), 0);
+        assert_eq!(Rectangle::square(1).words().count(), 0); // No `full_text` at all
+    }
+
+    fn sample_company() -> Company {
+        let mut company = Company::new();
+        company.add("Eng", "Zoe");
+        company.add("Eng", "Al");
+        company.add("Eng", "Zoe"); // Duplicate adds are ignored
+        company.add("eng", "al"); // Names and departments are case-sensitive
+        company
+    }
+
+    #[test]
+    fn company_sorts_and_dedupes_employees() {
+        let company = sample_company();
+        assert_eq!(company.employees_in("Eng"), ["Al", "Zoe"]);
+        assert_eq!(company.employees_in("eng"), ["al"]);
+        assert!(company.employees_in("Ops").is_empty());
+    }
+
+    #[test]
+    fn company_remove_reports_whether_anyone_left() {
+        let mut company = sample_company();
+        assert!(!company.remove("Eng", "Nobody"));
+        assert!(!company.remove("Ops", "Al"));
+        assert!(!company.remove("Eng", "al"));
+        assert!(company.remove("eng", "al"));
+        assert_eq!(company.all_by_department(), [("Eng", vec!["Al", "Zoe"])]); // Empty departments disappear
+    }
+
+    #[test]
+    fn parse_command_accepts_multi_word_names() {
+        assert_eq!(
+            parse_command("Add Sally Ride to Research and Dev"),
+            Some(Command::Add { employee: String::from("Sally Ride"), department: String::from("Research and Dev") })
+        );
+        assert_eq!(parse_command("List all"), Some(Command::ListAll));
+    }
+
+    #[test]
+    fn parse_command_rejects_malformed_lines() {
+        assert_eq!(parse_command("add Sally to X"), None);
+        assert_eq!(parse_command("Add Sally"), None);
+        assert_eq!(parse_command("List "), None);
+    }
+
+    #[test]
+    fn find_subslice_returns_the_first_match() {
+        assert_eq!(find_subslice(&[1, 2, 3, 2, 3], &[2, 3]), Some(1));
+        assert_eq!(find_subslice(&[1, 2, 3], &[3, 2]), None);
+    }
+
+    #[test]
+    fn find_subslice_edge_cases() {
+        assert_eq!(find_subslice::<i32>(&[], &[]), Some(0));
+        assert_eq!(find_subslice(&[1], &[]), Some(0));
+        assert_eq!(find_subslice(&[1, 2], &[1, 2, 3]), None); // Needle longer than haystack
+    }
+
+    fn user_at(name: &str, domain: &str, active: bool) -> User {
+        User { username: name.to_string(), email: format!("{}@{}", name, domain), sign_in_count: 0, active }
+    }
+
+    fn names(users: &[User]) -> Vec<&str> {
+        users.iter().map(|u| u.username.as_str()).collect()
+    }
+
+    #[test]
+    fn deactivate_and_collect_keeps_order_on_both_sides() {
+        let mut users = vec![
+            user_at("a", "x", true),
+            user_at("b", "x", false),
+            user_at("c", "x", true),
+            user_at("d", "x", false),
+        ];
+        let inactive = deactivate_and_collect(&mut users);
+        assert_eq!(names(&users), ["a", "c"]);
+        assert_eq!(names(&inactive), ["b", "d"]);
+    }
+
+    #[test]
+    fn retain_active_drops_exactly_the_inactive_users() {
+        let mut users = vec![user_at("a", "x", false), user_at("b", "x", true), user_at("c", "x", false)];
+        assert_eq!(retain_active(&mut users), 2);
+        assert_eq!(names(&users), ["b"]);
+    }
+
+    #[test]
+    fn take_first_n_drains_at_most_the_whole_vec() {
+        let mut queue = vec![1, 2, 3];
+        assert_eq!(take_first_n(&mut queue, 2), [1, 2]);
+        assert_eq!(queue, [3]);
+
+        let mut queue = vec![1, 2, 3];
+        assert_eq!(take_first_n(&mut queue, 9), [1, 2, 3]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn swap_remove_moves_the_last_element_into_the_gap() {
+        assert_eq!(compare_removals(&[1, 2, 3, 4], 0), (vec![2, 3, 4], vec![4, 2, 3]));
+    }
+
+    #[test]
+    fn split_tail_past_the_end_is_empty() {
+        let mut head = vec![1, 2, 3];
+        assert_eq!(split_tail(&mut head, 1), [2, 3]);
+        assert_eq!(head, [1]);
+
+        let mut head = vec![1, 2];
+        assert!(split_tail(&mut head, 5).is_empty());
+        assert_eq!(head, [1, 2]);
+    }
+
+    #[test]
+    fn collapse_domain_runs_only_merges_adjacent_users() {
+        let mut users = vec![
+            user_at("a", "x", true),
+            user_at("b", "x", true),
+            user_at("c", "y", true),
+            user_at("d", "x", true),
+        ];
+        collapse_domain_runs(&mut users);
+        assert_eq!(names(&users), ["a", "c", "d"]);
+    }
+
+    #[test]
+    fn tweet_builder_defaults_flags_to_false() {
+        let tweet = TweetBuilder::new().username("u").content("c").build().unwrap();
+        assert_eq!((tweet.username.as_str(), tweet.content.as_str()), ("u", "c"));
+        assert!(!tweet.reply && !tweet.retweet);
+
+        let tweet = TweetBuilder::new().reply(true).retweet(true).build().unwrap();
+        assert!(tweet.reply && tweet.retweet);
+    }
+
+    #[test]
+    fn tweet_builder_enforces_the_length_limit() {
+        let result = TweetBuilder::new().content(&"x".repeat(281)).build();
+        assert_eq!(result.err(), Some(TweetError::TooLong { chars: 281 }));
+    }
+
+    #[test]
+    fn article_builder_defaults_location_to_empty() {
+        let article = NewsArticleBuilder::new().headline("h").author("a").content("c").build();
+        assert_eq!((article.headline.as_str(), article.author.as_str(), article.content.as_str()), ("h", "a", "c"));
+        assert_eq!(article.location, "");
+    }
+
+    #[test]
+    fn missing_profile_chain_ends_at_the_io_error() {
+        let err = load_profile("/definitely/missing/file").unwrap_err();
+        let chain = error_chain(&err);
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[0], "could not read profile /definitely/missing/file");
+        assert!(err.source().unwrap().source().is_none());
+    }
+
+    #[test]
+    fn bad_field_chain_has_four_levels() {
+        let parse_error = parse_user("username=a\nemail=b\nsign_in_count=x\nactive=true").unwrap_err();
+        let err = ProfileError::Parse { path: String::from("p"), source: parse_error };
+        assert_eq!(
+            error_chain(&err),
+            [
+                "could not parse profile p",
+                "invalid field `sign_in_count`",
+                "expected a whole number",
+                "invalid digit found in string",
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_field_is_the_root_cause() {
+        let err = parse_user("username=a").unwrap_err();
+        assert_eq!(error_chain(&err), ["invalid field `sign_in_count`", "field is missing"]);
+        assert!(FieldError::Missing.source().is_none());
+    }
+
+    #[test]
+    fn parse_user_reads_every_field() {
+        let user = parse_user("username=a\nemail=b@c\nsign_in_count=3\nactive=false").unwrap();
+        assert_eq!((user.username.as_str(), user.email.as_str()), ("a", "b@c"));
+        assert_eq!((user.sign_in_count, user.active), (3, false));
+    }
+
+    #[test]
+    fn first_where_steps_until_the_predicate_holds() {
+        assert_eq!(first_where(1, |n| n + 1, |n| n % 10 == 0), 10);
+        assert_eq!(first_where(20, |n| n + 1, |n| n % 10 == 0), 20); // The start value is checked first
+    }
+
+    #[test]
+    fn first_where_capped_gives_up_after_the_cap() {
+        assert_eq!(first_where_capped(1, |n| n + 1, |n| n % 10 == 0, 9), Some(10));
+        assert_eq!(first_where_capped(1, |n| n + 1, |n| n % 10 == 0, 8), None);
+        assert_eq!(first_where_capped(1u32, |n| n * 2, |n| n % 3 == 0, 20), None); // Powers of two never qualify
+    }
+
+    #[test]
+    fn interning_twice_returns_the_same_symbol() {
+        let mut interner = Interner::new();
+        let hello = interner.intern("hello");
+        let world = interner.intern("world");
+        assert_eq!(interner.intern("hello"), hello);
+        assert_ne!(hello, world);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.resolve(world), "world");
+    }
+
+    #[test]
+    fn empty_string_is_interned_like_any_other() {
+        let mut interner = Interner::new();
+        let empty = interner.intern("");
+        assert_eq!(interner.resolve(empty), "");
+        assert_eq!(interner.intern(""), empty);
+        assert_eq!(interner.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn resolving_a_foreign_symbol_panics() {
+        let mut big = Interner::new();
+        big.intern("a");
+        let foreign = big.intern("b");
+        let mut small = Interner::new();
+        small.intern("x");
+        small.resolve(foreign);
+    }
+
+    #[test]
+    fn checked_area_detects_small_integer_overflow() {
+        assert_eq!(checked_area(16u8, 16u8), None);
+        assert_eq!(checked_area(15u8, 17u8), Some(255));
+        assert_eq!(Rectangle { width: u32::MAX, height: 2 }.checked_area(), None);
+        assert_eq!(Rectangle { width: 3, height: 2 }.checked_area(), Some(6));
+    }
+
+    #[test]
+    fn checked_area_for_floats_never_fails() {
+        assert_eq!(checked_area(2.0, 3.0), Some(6.0));
+        assert_eq!(checked_area(1e200f64, 1e200), Some(f64::INFINITY));
+    }
+
+    #[test]
+    fn rotate_option_takes_the_value_out() {
+        let mut empty: Option<String> = None;
+        assert_eq!(rotate_option(&mut empty), None);
+        assert_eq!(empty, None);
+
+        let mut slot = Some(String::from("x"));
+        assert_eq!(rotate_option(&mut slot).as_deref(), Some("x"));
+        assert!(slot.is_none());
+    }
+
+    #[test]
+    fn traffic_light_cycle_keeps_the_intersection() {
+        let intersection = || String::from("A");
+        let mut light = TrafficLight::Red { intersection: intersection() };
+        advance_state(&mut light);
+        assert_eq!(light, TrafficLight::Green { intersection: intersection() });
+        advance_state(&mut light);
+        assert_eq!(light, TrafficLight::Yellow { intersection: intersection() });
+        advance_state(&mut light);
+        assert_eq!(light, TrafficLight::Red { intersection: intersection() });
+    }
+
+    #[test]
+    fn swap_ends_handles_short_vecs() {
+        let mut empty: Vec<i32> = vec![];
+        swap_ends(&mut empty);
+        assert!(empty.is_empty());
+
+        let mut single = vec![7];
+        swap_ends(&mut single);
+        assert_eq!(single, [7]);
+
+        let mut three = vec![1, 2, 3];
+        swap_ends(&mut three);
+        assert_eq!(three, [3, 2, 1]);
+    }
+
+    #[test]
+    fn cursor_script_round_trips_mid_session() {
+        let mut cursor = Cursor::new();
+        cursor.apply(Message::Move { x: -3, y: 4 });
+        cursor.apply(Message::Write(String::from("a b\tc\nd\\ and x\r")));
+        cursor.apply(Message::ChangeColor(1, 2, 3));
+        cursor.apply(Message::Write(String::from("\r\n")));
+
+        let resumed = Cursor::from_script(&cursor.to_script()).unwrap();
+        assert_eq!((resumed.x, resumed.y, resumed.color, resumed.quit), (-3, 4, Color(1, 2, 3), false));
+        assert_eq!(resumed.text, cursor.text);
+        assert_eq!(logged_messages(&resumed), logged_messages(&cursor));
+        assert_eq!(resumed, cursor);
+    }
+
+    #[test]
+    fn cursor_script_keeps_entry_offsets() {
+        let mut cursor = Cursor::new();
+        cursor.apply(Message::Move { x: 1, y: 2 });
+        std::thread::sleep(Duration::from_millis(2));
+        cursor.apply(Message::Quit);
+        assert_eq!(cursor.history()[0].at, Duration::ZERO);
+
+        let mut resumed = Cursor::from_script(&cursor.to_script()).unwrap();
+        let offsets: Vec<Duration> = resumed.history().iter().map(|entry| entry.at).collect();
+        assert_eq!(offsets, [Duration::ZERO, cursor.history()[1].at]);
+        // Messages applied after resuming count from the same origin
+        resumed.apply(Message::Write(String::from("more")));
+        assert!(resumed.history()[2].at >= resumed.history()[1].at);
+    }
+
+    #[test]
+    fn script_offsets_need_nine_digits_of_nanoseconds() {
+        assert_eq!(parse_offset("1.000000500s"), Some(Duration::new(1, 500)));
+        assert_eq!(parse_offset("0.5s"), None);
+        assert_eq!(parse_offset("0.000000500"), None);
+        assert_eq!(parse_offset("x.000000500s"), None);
+    }
+
+    #[test]
+    fn cursor_script_rejects_bad_lines() {
+        let expected = ScriptError { line: 2, text: String::from("0.000000000s color 1 2 300") };
+        assert_eq!(Cursor::from_script("0.000000000s quit\n0.000000000s color 1 2 300").err(), Some(expected));
+        assert!(Cursor::from_script("0.000000000s jump 1 2").is_err());
+        assert!(Cursor::from_script("0.000000000s quit now").is_err());
+        assert!(Cursor::from_script("0.000000000s write bad\\escape").is_err());
+        assert!(Cursor::from_script("move 1 2").is_err());
+        assert!(Cursor::from_script("").unwrap().history().is_empty());
+    }
+
+    #[test]
+    fn digest_escaping_round_trips_control_characters() {
+        let field = "tab\there, cr\r, lf\n, slash\\";
+        assert!(!escape_digest(field).contains(['\t', '\r', '\n']));
+        assert_eq!(unescape_digest(&escape_digest(field)).as_deref(), Some(field));
+        assert_eq!(unescape_digest("dangling\\"), None);
+    }
+
+    #[test]
+    fn percentage_accepts_exact_bounds() {
+        assert_eq!(Percentage::new(0), Ok(Percentage(0)));
+        assert_eq!(Percentage::new(100), Ok(Percentage(100)));
+        assert!(Percentage::new(101).is_err());
+    }
+
+    #[test]
+    fn probability_rejects_nan_and_out_of_range() {
+        assert_eq!(Probability::new(0.0), Ok(Probability(0.0)));
+        assert_eq!(Probability::new(1.0), Ok(Probability(1.0)));
+        assert!(Probability::new(f64::NAN).is_err());
+        assert!(Probability::new(1.0001).is_err());
+        assert!(Probability::new(-0.1).is_err());
+    }
+
+    #[test]
+    fn bounded_arithmetic_saturates() {
+        assert_eq!(Percentage(90).saturating_add(Percentage(20)), Percentage(100));
+        assert_eq!(Percentage(100).saturating_add(Percentage(100)), Percentage(100));
+        assert_eq!(Percentage(10).saturating_sub(Percentage(20)), Percentage(0));
+        assert_eq!(CERTAIN.saturating_add(Probability(0.3)), Probability(1.0));
+    }
+
+    #[test]
+    fn fifty_percent_round_trips_through_probability() {
+        let half = Percentage::new(50).unwrap().as_probability();
+        assert_eq!(half, Probability(0.5));
+        assert_eq!(half.as_percentage(), Percentage(50));
+    }
+
+    #[test]
+    fn bounded_ranges_display() {
+        assert_eq!(Percentage(42).to_string(), "42%");
+        assert_eq!(Probability(0.42).to_string(), "0.420");
+    }
+
+    #[test]
+    fn default_thread_count_is_at_least_one() {
+        assert!(default_thread_count() >= 1);
+    }
+
+    #[test]
+    fn thread_count_falls_back_to_one_when_unknown() {
+        assert_eq!(thread_count_or_fallback(Err(io::Error::other("unsupported"))), 1);
+        assert_eq!(thread_count_or_fallback(Ok(NonZeroUsize::new(6).unwrap())), 6);
+    }
+
+    #[test]
+    fn zero_threads_means_auto() {
+        let numbers: Vec<i32> = (0..100).collect();
+        let doubled: Vec<i32> = numbers.iter().map(|n| n * 2).collect();
+        assert_eq!(par_map(&numbers, 0, |n| n * 2), doubled);
+    }
+
+    #[test]
+    fn rotate_left_wraps_around() {
+        let mut by_two = [1, 2, 3, 4, 5];
+        rotate_left(&mut by_two, 2);
+        assert_eq!(by_two, [3, 4, 5, 1, 2]);
+
+        let mut by_seven = [1, 2, 3, 4, 5];
+        rotate_left(&mut by_seven, 7); // Same as rotating by 2
+        assert_eq!(by_seven, [3, 4, 5, 1, 2]);
+
+        let mut full_turn = [1, 2, 3];
+        rotate_left(&mut full_turn, 3);
+        assert_eq!(full_turn, [1, 2, 3]);
+    }
+
+    #[test]
+    fn rotate_left_on_empty_and_non_copy_slices() {
+        let mut empty: [i32; 0] = [];
+        rotate_left(&mut empty, 4);
+        assert_eq!(empty, []);
+
+        let mut words = vec![String::from("a"), String::from("b")];
+        rotate_left(&mut words, 1);
+        assert_eq!(words, ["b", "a"]);
+    }
+
+    /// Multiples of 7 take much longer, so workers finish out of order.
+    fn uneven_cost(n: u64) -> u64 {
+        if n.is_multiple_of(7) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        n * 10
+    }
+
+    #[test]
+    fn parallel_map_preserves_input_order() {
+        let numbers: Vec<u64> = (0..50).collect();
+        let expected: Vec<u64> = numbers.iter().map(|n| n * 10).collect();
+        assert_eq!(parallel_map(numbers.clone(), 4, uneven_cost), expected);
+        assert_eq!(parallel_map(numbers, 1, uneven_cost), expected);
+    }
+
+    #[test]
+    fn parallel_map_with_more_threads_than_items() {
+        assert_eq!(parallel_map(vec![1u64, 2], 16, uneven_cost), [10, 20]);
+        assert_eq!(parallel_map(Vec::<u64>::new(), 4, uneven_cost), []);
+    }
+
+    #[test]
+    fn parallel_map_borrows_without_static() {
+        let suffix = String::from("!"); // `thread::scope` lets `f` borrow this
+        let shouted = parallel_map(vec!["a", "b"], 0, |s: &str| s.to_uppercase() + &suffix);
+        assert_eq!(shouted, ["A!", "B!"]);
+    }
+
+    fn sample_board() -> Leaderboard {
+        let mut board = Leaderboard::new();
+        board.record("a", 10);
+        board.record("b", 20);
+        board.record("c", 10);
+        board.record("d", 5);
+        board
+    }
+
+    #[test]
+    fn leaderboard_breaks_ties_by_recording_order() {
+        assert_eq!(sample_board().top_n(3), [("b", 20), ("a", 10), ("c", 10)]);
+    }
+
+    #[test]
+    fn leaderboard_rerecording_same_score_keeps_place() {
+        let mut board = Leaderboard::new();
+        board.record("A", 10);
+        board.record("B", 10);
+        board.record("A", 10);
+        assert_eq!(board.top_n(2), [("A", 10), ("B", 10)]);
+    }
+
+    #[test]
+    fn leaderboard_new_score_replaces_old() {
+        let mut board = sample_board();
+        board.record("a", 30);
+        assert_eq!(board.rank_of("a"), Some(1));
+        assert_eq!(board.top_n(99).len(), 4);
+    }
+
+    #[test]
+    fn leaderboard_top_n_beyond_len_returns_everyone() {
+        assert_eq!(sample_board().top_n(99).len(), 4);
+        assert!(Leaderboard::new().top_n(3).is_empty());
+    }
+
+    #[test]
+    fn leaderboard_ranges() {
+        let board = sample_board();
+        assert_eq!(board.scores_between(5, 10), [("a", 10), ("c", 10), ("d", 5)]);
+        assert!(board.scores_between(11, 19).is_empty());
+        assert!(board.scores_between(30, 1).is_empty());
+    }
+
+    #[test]
+    fn leaderboard_rank_of_missing_name_is_none() {
+        let board = sample_board();
+        assert_eq!(board.rank_of("c"), Some(3));
+        assert_eq!(board.rank_of("zz"), None);
+    }
+
+    #[test]
+    fn identical_colors_are_zero_apart() {
+        for color in [Color(0, 0, 0), Color(12, 200, 99), Color(255, 255, 255)] {
+            assert_eq!(color.distance_rgb(&color), 0.0);
+            assert_eq!(color.distance_lab(&color), 0.0);
+        }
+    }
+
+    #[test]
+    fn lab_distance_weights_green_more_than_red() {
+        let (black, red, green) = (Color(0, 0, 0), Color(40, 0, 0), Color(0, 40, 0));
+        assert_eq!(black.distance_rgb(&red), black.distance_rgb(&green));
+        assert!(black.distance_lab(&green) > 1.5 * black.distance_lab(&red));
+    }
+
+    #[test]
+    fn lab_matches_reference_values() {
+        let delta = Color(255, 0, 0).distance_lab(&Color(0, 255, 0));
+        assert!((delta - 170.6).abs() < 1.0, "{}", delta);
+        let white = Color(255, 255, 255).to_lab();
+        assert!((white[0] - 100.0).abs() < 0.01 && white[1].abs() < 0.05 && white[2].abs() < 0.05, "{:?}", white);
+    }
+
+    #[test]
+    fn debug_borrows_classifies_each_kind() {
+        let mut r = SectionReporter::new();
+        let mut text = String::from("x");
+        let number = 5u8;
+        let literal: &str = "lit";
+        assert_eq!(debug_borrows!(r, text.clone()), "owned");
+        assert_eq!(debug_borrows!(r, number), "owned");
+        assert_eq!(debug_borrows!(r, &text), "&");
+        assert_eq!(debug_borrows!(r, &number), "&");
+        assert_eq!(debug_borrows!(r, literal), "&");
+        assert_eq!(debug_borrows!(r, &mut text), "&mut");
+        let lines = &r.finish().sections[0].lines;
+        assert_eq!(lines[0], "text.clone(): String (owned)");
+        assert_eq!(lines[5], "&mut text: &mut String (&mut)");
+    }
+
+    #[test]
+    fn short_type_name_drops_paths() {
+        assert_eq!(short_type_name("&mut alloc::string::String"), "&mut String");
+        assert_eq!(short_type_name("alloc::vec::Vec<core::option::Option<i32>>"), "Vec<Option<i32>>");
+        assert_eq!(short_type_name("u8"), "u8");
+    }
+
+    #[test]
+    fn disjoint_field_borrows_rebuilds_email_from_username() {
+        let mut user = User {
+            username: String::from("pat"),
+            email: String::from("old@example.com"),
+            sign_in_count: 0,
+            active: true,
+        };
+        disjoint_field_borrows(&mut user);
+        assert_eq!(user.email, "pat@users.example.com");
+        assert_eq!(user.username, "pat");
+    }
+
+    #[test]
+    fn nll_and_split_at_mut_examples() {
+        let mut scores = vec![1, 2];
+        assert_eq!(nll_example(&mut scores), 2);
+        assert_eq!(scores, [1, 2, 2]);
+        let mut halves = [1, 2, 9, 3, 4];
+        add_front_into_back(&mut halves);
+        assert_eq!(halves, [1, 2, 9, 4, 6]);
+    }
+
+    fn final_state(messages: &[Message]) -> (i32, i32, Color, String, bool) {
+        let mut cursor = Cursor::new();
+        for message in messages {
+            cursor.apply(message.clone());
+        }
+        (cursor.x, cursor.y, cursor.color, cursor.text, cursor.quit)
+    }
+
+    #[test]
+    fn coalesce_collapses_a_run_of_moves() {
+        let moves = vec![Message::Move { x: 1, y: 1 }, Message::Move { x: 2, y: 2 }, Message::Move { x: 3, y: -3 }];
+        assert_eq!(coalesce(moves.clone()), [Message::Move { x: 3, y: -3 }]);
+        assert_eq!(final_state(&coalesce(moves.clone())), final_state(&moves));
+        assert!(coalesce(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn coalesce_keeps_writes_and_quits_in_place() {
+        let messages = vec![
+            Message::Move { x: 1, y: 1 },
+            Message::Write(String::from("a")),
+            Message::Move { x: 2, y: 2 },
+            Message::ChangeColor(1, 1, 1),
+            Message::Move { x: 4, y: 4 },
+            Message::ChangeColor(2, 2, 2),
+            Message::ChangeColor(3, 3, 3),
+            Message::Write(String::from("b")),
+            Message::Quit,
+            Message::Quit,
+        ];
+        let coalesced = coalesce(messages.clone());
+        assert_eq!(
+            coalesced,
+            [
+                Message::Move { x: 1, y: 1 },
+                Message::Write(String::from("a")),
+                Message::Move { x: 2, y: 2 },
+                Message::ChangeColor(1, 1, 1),
+                Message::Move { x: 4, y: 4 },
+                Message::ChangeColor(3, 3, 3),
+                Message::Write(String::from("b")),
+                Message::Quit,
+                Message::Quit,
+            ]
+        );
+        assert_eq!(final_state(&coalesced), final_state(&messages));
+    }
+
+    #[test]
+    fn range_counter_reverses() {
+        assert_eq!(RangeCounter::new(1, 5).rev().collect::<Vec<_>>(), [4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn range_counter_len_tracks_both_ends() {
+        let mut counter = RangeCounter::new(0, 10);
+        assert_eq!(counter.len(), 10);
+        assert_eq!(counter.size_hint(), (10, Some(10)));
+
+        counter.next();
+        counter.next_back();
+        counter.next_back();
+        assert_eq!(counter.len(), 7);
+        assert_eq!(counter.size_hint(), (7, Some(7)));
+        assert_eq!(counter.collect::<Vec<_>>(), [1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn empty_range_counter_yields_nothing() {
+        let mut empty = RangeCounter::new(3, 3);
+        assert_eq!(empty.len(), 0);
+        assert_eq!(empty.next(), None);
+        assert_eq!(empty.next_back(), None);
+        assert_eq!(RangeCounter::new(5, 2).len(), 0); // Backwards bounds are empty, not an underflow
+    }
+
+    #[test]
+    fn sum_from_both_ends_gives_the_middle_to_the_front() {
+        assert_eq!(sum_from_both_ends(RangeCounter::new(1, 6)), (1 + 2 + 3, 5 + 4));
+        assert_eq!(sum_from_both_ends(RangeCounter::new(1, 5)), (1 + 2, 4 + 3));
+        assert_eq!(sum_from_both_ends(vec![7u32].into_iter()), (7, 0));
+        assert_eq!(sum_from_both_ends(RangeCounter::new(0, 0)), (0, 0));
+    }
+
+    #[test]
+    fn email_parse_splits_at_the_at_sign() {
+        let email = Email::parse("john@example.com").unwrap();
+        assert_eq!((email.local(), email.domain()), ("john", "example.com"));
+        assert_eq!(email.to_string(), "john@example.com");
+    }
+
+    #[test]
+    fn email_parse_rejects_malformed_addresses() {
+        assert_eq!(Email::parse("no-at-sign"), Err(EmailError::MissingAt));
+        assert_eq!(Email::parse("a@b@c"), Err(EmailError::MultipleAt));
+        assert_eq!(Email::parse("@example.com"), Err(EmailError::EmptyLocal));
+        assert_eq!(Email::parse("john@"), Err(EmailError::EmptyDomain));
+    }
+
+    #[test]
+    fn user_domain_accessors_follow_email_rules() {
+        let user = user_with_email("john@example.com");
+        assert_eq!(user.domain(), Some("example.com"));
+        assert_eq!(user.email_typed().map(|email| email.local().to_string()), Ok(String::from("john")));
+        for bad in ["no-at-sign", "a@b@c", "john@"] {
+            let user = user_with_email(bad);
+            assert_eq!(user.domain(), None, "{}", bad);
+            assert!(user.email_typed().is_err(), "{}", bad);
+        }
+        // The plain `?` helper only needs an '@'; it does not validate the address
+        assert_eq!(user_email_domain(&user_with_email("a@b@c")), Some("b@c"));
+        assert_eq!(user_email_domain(&user_with_email("no-at-sign")), None);
+    }
+
+    fn template_vars() -> HashMap<&'static str, String> {
+        HashMap::from([("a", String::from("1")), ("b", String::from("2")), ("braces", String::from("{a}}"))])
+    }
+
+    #[test]
+    fn render_substitutes_adjacent_and_trailing_placeholders() {
+        let vars = template_vars();
+        assert_eq!(template::render("{a}{b}", &vars), Ok(String::from("12")));
+        assert_eq!(template::render("x={a}", &vars), Ok(String::from("x=1")));
+        assert_eq!(template::render("", &vars), Ok(String::new()));
+    }
+
+    #[test]
+    fn render_unescapes_doubled_braces() {
+        let vars = template_vars();
+        assert_eq!(template::render("{{a}} }}{{", &vars), Ok(String::from("{a} }{")));
+        assert_eq!(template::render("{{{a}}}", &vars), Ok(String::from("{1}")));
+    }
+
+    #[test]
+    fn render_does_not_re_expand_values() {
+        assert_eq!(template::render("<{braces}>", &template_vars()), Ok(String::from("<{a}}>")));
+    }
+
+    #[test]
+    fn render_errors_carry_byte_offsets() {
+        use template::{render, TemplateError};
+        let vars = template_vars();
+        // "é" is two bytes, so the placeholder starts at byte 2
+        assert_eq!(render("é{zz}", &vars), Err(TemplateError::UnknownVariable { name: String::from("zz"), offset: 2 }));
+        assert_eq!(render("{}", &vars), Err(TemplateError::UnknownVariable { name: String::new(), offset: 0 }));
+        assert_eq!(render("ab{a", &vars), Err(TemplateError::Unterminated { offset: 2 }));
+        assert_eq!(render("a}b", &vars), Err(TemplateError::UnmatchedClose { offset: 1 }));
+    }
+
+    #[test]
+    fn render_lenient_keeps_unknown_placeholders() {
+        let vars = template_vars();
+        assert_eq!(
+            template::render_lenient("{a} {x} {y}{b} {", &vars),
+            (String::from("1 {x} {y}2 {"), vec![String::from("x"), String::from("y")])
+        );
+        assert_eq!(template::render_lenient("}}a}", &vars), (String::from("}a}"), vec![]));
+    }
+
+    #[test]
+    fn sample_variants_cover_every_variant_in_order() {
+        let samples = Message::sample_variants();
+        assert_eq!(samples.len(), Message::variant_names().len());
+        for (message, name) in samples.iter().zip(Message::variant_names()) {
+            assert_eq!(message.variant_name(), *name);
+        }
+    }
+
+    #[test]
+    fn sample_variants_round_trip_through_cursor_scripts() {
+        for message in Message::sample_variants() {
+            let mut cursor = Cursor::new();
+            cursor.apply(message.clone());
+            let replayed = Cursor::from_script(&cursor.to_script()).unwrap();
+            assert_eq!(replayed.history()[0].message, message);
+        }
+    }
+
+    fn ranked_user(username: &str, sign_in_count: u64, active: bool) -> User {
+        User { username: username.to_string(), email: String::from("e@x.com"), sign_in_count, active }
+    }
+
+    fn ranking_sample() -> Vec<User> {
+        vec![
+            ranked_user("b", 5, true),
+            ranked_user("c", 1, false),
+            ranked_user("a", 5, true),
+            ranked_user("d", 7, true),
+            ranked_user("e", 9, false),
+        ]
+    }
+
+    fn usernames<'a>(users: impl IntoIterator<Item = &'a User>) -> Vec<&'a str> {
+        users.into_iter().map(|user| user.username.as_str()).collect()
+    }
+
+    #[test]
+    fn sort_users_orders_by_active_then_count_then_name() {
+        let mut users = ranking_sample();
+        sort_users(&mut users);
+        // "a" and "b" tie on active and count, so the username breaks the tie
+        assert_eq!(usernames(&users), ["d", "a", "b", "e", "c"]);
+    }
+
+    #[test]
+    fn top_users_takes_the_first_n_in_order() {
+        let users = ranking_sample();
+        assert_eq!(usernames(top_users(&users, 2)), ["d", "a"]);
+        assert_eq!(usernames(top_users(&users, 3)), ["d", "a", "b"]);
+    }
+
+    #[test]
+    fn top_users_with_zero_or_too_many() {
+        let users = ranking_sample();
+        assert!(top_users(&users, 0).is_empty());
+        assert_eq!(usernames(top_users(&users, 99)), ["d", "a", "b", "e", "c"]);
+        assert!(top_users(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn sort_rects_by_area_desc_puts_largest_first() {
+        let mut rects = [Rectangle::square(1), Rectangle::square(3), Rectangle { width: 2, height: 2 }];
+        sort_rects_by_area_desc(&mut rects);
+        assert_eq!(rects.iter().map(Rectangle::area).collect::<Vec<_>>(), [9, 4, 1]);
+    }
+
+    fn rects_overlap(a: (&Rectangle, &Point<u32>), b: (&Rectangle, &Point<u32>)) -> bool {
+        let ((ra, pa), (rb, pb)) = (a, b);
+        pa.x < pb.x + rb.width && pb.x < pa.x + ra.width && pa.y < pb.y + rb.height && pb.y < pa.y + ra.height
+    }
+
+    #[test]
+    fn pack_places_fitting_rectangles_without_overlap() {
+        let bin = Rectangle { width: 10, height: 10 };
+        let rects = vec![
+            Rectangle { width: 4, height: 3 },
+            Rectangle { width: 5, height: 2 },
+            Rectangle { width: 3, height: 4 },
+            Rectangle::square(6),
+            Rectangle { width: 11, height: 1 }, // Wider than the bin
+            Rectangle { width: 2, height: 2 },
+            Rectangle::square(4),
+        ];
+        let placements = pack(bin.clone(), &rects);
+        assert_eq!(placements.len(), rects.len());
+        assert!(placements[..3].iter().all(Option::is_some));
+        assert!(placements[4].is_none());
+
+        let placed: Vec<_> = rects.iter().zip(&placements).filter_map(|(r, p)| p.as_ref().map(|p| (r, p))).collect();
+        for (i, &(rect, origin)) in placed.iter().enumerate() {
+            assert!(origin.x + rect.width <= bin.width && origin.y + rect.height <= bin.height);
+            for &other in &placed[i + 1..] {
+                assert!(!rects_overlap((rect, origin), other));
+            }
+        }
+    }
+
+    #[test]
+    fn pack_of_nothing_is_empty() {
+        assert!(pack(Rectangle::square(1), &[]).is_empty());
+    }
+
+    #[test]
+    fn pool_exhausts_and_refills_on_drop() {
+        let pool = Pool::new(vec![String::from("a"), String::from("bb")]);
+        let first = pool.checkout().unwrap();
+        let second = pool.checkout().unwrap();
+        assert!(pool.checkout().is_none());
+        assert_eq!(pool.available(), 0);
+        assert_eq!(first.len() + second.len(), 3); // `Deref` reaches `String::len`
+
+        drop(first);
+        assert_eq!(pool.available(), 1);
+    }
+
+    #[test]
+    fn checkout_timeout_gives_up_when_nothing_returns() {
+        let pool = Pool::new(vec![1]);
+        let _held = pool.checkout().unwrap();
+        let start = Instant::now();
+        assert!(pool.checkout_timeout(Duration::from_millis(20)).is_none());
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn checkout_timeout_wakes_when_an_item_returns() {
+        let pool = Pool::new(vec![7]);
+        let held = pool.checkout().unwrap();
+        thread::scope(|s| {
+            s.spawn(move || {
+                thread::sleep(Duration::from_millis(10));
+                drop(held);
+            });
+            let item = pool.checkout_timeout(Duration::from_secs(5));
+            assert_eq!(item.as_deref(), Some(&7));
+        });
+    }
+
+    #[test]
+    fn local_pool_follows_the_same_pattern() {
+        let pool = LocalPool::new(vec![5]);
+        let item = pool.checkout().unwrap();
+        assert_eq!(*item + 1, 6);
+        assert!(pool.checkout().is_none());
+        drop(item);
+        assert_eq!(pool.available(), 1);
+    }
+
+    #[test]
+    fn sum_adds_up_and_defaults_when_empty() {
+        assert_eq!(sum(&[1, 2, 3, 4]), 10);
+        assert_eq!(sum::<i64>(&[]), 0);
+        assert_eq!(sum::<f64>(&[]), 0.0);
+    }
+
+    #[test]
+    fn mean_of_empty_is_none() {
+        assert_eq!(mean(&[1.0, 2.0, 4.5]), Some(2.5));
+        assert_eq!(mean(&[]), None);
+    }
+
+    #[test]
+    fn with_thousands_groups_digits() {
+        use humanize::with_thousands;
+        assert_eq!(with_thousands(0), "0");
+        assert_eq!(with_thousands(999), "999");
+        assert_eq!(with_thousands(1000), "1,000");
+        assert_eq!(with_thousands(-1_234_567), "-1,234,567");
+        assert_eq!(with_thousands(i64::MIN), "-9,223,372,036,854,775,808"); // No overflow negating
+    }
+
+    #[test]
+    fn human_bytes_switches_units_at_1024() {
+        use humanize::human_bytes;
+        assert_eq!(human_bytes(0), "0 B");
+        assert_eq!(human_bytes(1023), "1023 B");
+        assert_eq!(human_bytes(1024), "1.0 KiB");
+        assert_eq!(human_bytes(1536), "1.5 KiB");
+        assert_eq!(human_bytes(1_048_575), "1.0 MiB"); // Rounds up into the next unit
+        assert_eq!(human_bytes(3 * 1024 * 1024 + 210_000), "3.2 MiB");
+    }
+
+    #[test]
+    fn human_bytes_tops_out_at_pib() {
+        assert_eq!(humanize::human_bytes(1 << 50), "1.0 PiB");
+        assert_eq!(humanize::human_bytes(u64::MAX), "16384.0 PiB");
+    }
+
+    #[test]
+    fn ordinal_handles_the_teens() {
+        let ordinals: Vec<String> = [0, 1, 2, 3, 4, 11, 12, 13, 21, 22, 23, 101, 111, 112]
+            .iter()
+            .map(|&n| humanize::ordinal(n))
+            .collect();
+        assert_eq!(
+            ordinals,
+            ["0th", "1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "23rd", "101st", "111th", "112th"]
+        );
+    }
+
+    #[test]
+    fn process_sqrt_divide_succeeds() {
+        assert_eq!(process_sqrt_divide(18.0, 2.0), Ok(3.0));
+    }
+
+    #[test]
+    fn process_sqrt_divide_reports_divide_by_zero() {
+        let err = process_sqrt_divide(1.0, 0.0).unwrap_err();
+        assert_eq!(err, MathError::Division(DivideError::DivideByZero));
+        assert_eq!(err.to_string(), "division failed: cannot divide by zero");
+    }
+
+    #[test]
+    fn process_sqrt_divide_reports_negative_sqrt() {
+        assert_eq!(process_sqrt_divide(-8.0, 2.0), Err(MathError::NegativeSqrt(-4.0)));
+    }
+
+    #[test]
+    fn empty_stack_has_nothing_to_peek_or_pop() {
+        let mut stack: Stack<i32> = Stack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.peek(), None);
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn stack_pops_in_lifo_order() {
+        let mut stack = Stack::new();
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert_eq!(stack.len(), 3);
+        assert_eq!(stack.peek(), Some(&3));
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        stack.push(9);
+        assert_eq!(stack.into_iter().collect::<Vec<_>>(), [9, 1]);
+    }
+
+    #[test]
+    fn collecting_into_a_stack_pushes_in_order() {
+        let stack: Stack<char> = "abc".chars().collect();
+        assert_eq!(stack.peek(), Some(&'c'));
+        assert_eq!(stack.into_iter().collect::<String>(), "cba");
+    }
+
+    fn buffer_state(buffer: &TextBuffer) -> (String, usize) {
+        (buffer.as_string(), buffer.cursor_position())
+    }
+
+    #[test]
+    fn text_buffer_scripted_edits() {
+        let mut buffer = TextBuffer::new();
+        assert_eq!(buffer.delete_backward(), None);
+        assert!(!buffer.move_left());
+        assert!(!buffer.move_right());
+        assert_eq!(buffer_state(&buffer), (String::new(), 0));
+
+        for c in "héllo".chars() {
+            buffer.insert_char(c);
+        }
+        assert_eq!(buffer_state(&buffer), ("héllo".to_string(), 5));
+        buffer.move_to(2);
+        assert_eq!(buffer_state(&buffer), ("héllo".to_string(), 2));
+        assert_eq!(buffer.delete_backward(), Some('é'));
+        assert_eq!(buffer_state(&buffer), ("hllo".to_string(), 1));
+        buffer.insert_char('🐧');
+        assert_eq!(buffer_state(&buffer), ("h🐧llo".to_string(), 2));
+        buffer.move_to(100); // Clamped to the end
+        assert_eq!(buffer_state(&buffer), ("h🐧llo".to_string(), 5));
+        assert!(!buffer.move_right());
+        buffer.move_to(0);
+        assert_eq!(buffer.delete_backward(), None);
+        assert_eq!(buffer_state(&buffer), ("h🐧llo".to_string(), 0));
+        buffer.insert_char('>');
+        assert_eq!(buffer_state(&buffer), (">h🐧llo".to_string(), 1));
+        assert!(buffer.move_right());
+        assert_eq!(buffer_state(&buffer), (">h🐧llo".to_string(), 2));
+    }
+
+    #[test]
+    fn text_buffer_matches_string_model_on_random_edits() {
+        // xorshift64: deterministic, so a failure reproduces
+        let mut seed: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut random = |n: usize| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % n as u64) as usize
+        };
+        let alphabet: Vec<char> = "abé🐧 z".chars().collect();
+        let (mut buffer, mut model, mut cursor) = (TextBuffer::new(), String::new(), 0);
+        // The model indexes by char; `String` wants byte offsets
+        let byte_offset = |text: &str, chars: usize| text.char_indices().nth(chars).map_or(text.len(), |(i, _)| i);
+
+        for _ in 0..5000 {
+            let chars = model.chars().count();
+            match random(5) {
+                0 | 1 => {
+                    let c = alphabet[random(alphabet.len())];
+                    buffer.insert_char(c);
+                    model.insert(byte_offset(&model, cursor), c);
+                    cursor += 1;
+                }
+                2 => {
+                    let deleted = buffer.delete_backward();
+                    if cursor > 0 {
+                        cursor -= 1;
+                        assert_eq!(deleted, Some(model.remove(byte_offset(&model, cursor))));
+                    } else {
+                        assert_eq!(deleted, None);
+                    }
+                }
+                3 => {
+                    let target = random(chars + 3); // Sometimes past the end
+                    buffer.move_to(target);
+                    cursor = target.min(chars);
+                }
+                _ if random(2) == 0 => {
+                    assert_eq!(buffer.move_left(), cursor > 0);
+                    cursor = cursor.saturating_sub(1);
+                }
+                _ => {
+                    assert_eq!(buffer.move_right(), cursor < chars);
+                    cursor = (cursor + 1).min(chars);
+                }
+            }
+            assert_eq!(buffer_state(&buffer), (model.clone(), cursor));
+        }
+    }
+
+    #[test]
+    fn render_bar_at_zero_and_full() {
+        assert_eq!(progress::render_bar(0, 10, 10), "[..........] 0%");
+        assert_eq!(progress::render_bar(10, 10, 10), "[##########] 100%");
+        // An empty job is already done
+        assert_eq!(progress::render_bar(0, 0, 4), "[####] 100%");
+    }
+
+    #[test]
+    fn render_bar_rounds_down() {
+        assert_eq!(progress::render_bar(52, 100, 10), "[#####.....] 52%");
+        assert_eq!(progress::render_bar(1, 3, 10), "[###.......] 33%");
+    }
+
+    #[test]
+    fn render_bar_with_one_cell() {
+        assert_eq!(progress::render_bar(2, 3, 1), "[.] 66%");
+        assert_eq!(progress::render_bar(3, 3, 1), "[#] 100%");
+    }
+
+    #[test]
+    fn run_with_progress_visits_every_index_once() {
+        let visits: Vec<AtomicU64> = (0..1001).map(|_| AtomicU64::new(0)).collect();
+        progress::run_with_progress(1001, 7, |index| {
+            visits[index].fetch_add(1, AtomicOrdering::Relaxed);
+        });
+        assert!(visits.iter().all(|count| count.load(AtomicOrdering::Relaxed) == 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "item failed")]
+    fn run_with_progress_reraises_worker_panic() {
+        progress::run_with_progress(100, 4, |index| {
+            if index == 10 {
+                panic!("item failed");
+            }
+        });
+    }
+
+    fn clamped(rect: &Rectangle, x: i32, y: i32) -> (i32, i32) {
+        let p = rect.clamp_point(&Point { x, y });
+        (p.x, p.y)
+    }
+
+    #[test]
+    fn clamp_point_snaps_outside_points_to_corners() {
+        let rect = Rectangle { width: 10, height: 20 };
+        assert_eq!(clamped(&rect, -3, -7), (0, 0));
+        assert_eq!(clamped(&rect, 15, 25), (10, 20));
+        assert_eq!(clamped(&rect, 4, 9), (4, 9)); // Already inside
+    }
+
+    #[test]
+    fn clamp_point_on_a_huge_rectangle_does_not_overflow() {
+        let huge = Rectangle { width: u32::MAX, height: 1 };
+        assert_eq!(clamped(&huge, i32::MAX, 3), (i32::MAX, 1));
+    }
+
+    #[test]
+    fn nearest_edge_just_outside_each_side() {
+        let rect = Rectangle { width: 10, height: 20 };
+        assert_eq!(rect.nearest_edge(&Point { x: 5, y: -1 }), Edge::Top);
+        assert_eq!(rect.nearest_edge(&Point { x: 5, y: 21 }), Edge::Bottom);
+        assert_eq!(rect.nearest_edge(&Point { x: -1, y: 10 }), Edge::Left);
+        assert_eq!(rect.nearest_edge(&Point { x: 11, y: 10 }), Edge::Right);
+        assert_eq!(rect.nearest_edge(&Point { x: 11, y: 1 }), Edge::Right);
+        assert_eq!(rect.nearest_edge(&Point { x: 2, y: 10 }), Edge::Left); // Inside, closest to the left
+    }
+
+    #[test]
+    fn rectangle_converts_into_pixel_rect() {
+        let rect: units::Rect<units::Pixels> = Rectangle { width: 30, height: 50 }.into();
+        assert_eq!((rect.width, rect.height), (30, 50));
+    }
+
+    #[test]
+    fn unit_rect_can_hold_keeps_strict_semantics() {
+        let rect: units::Rect<units::Pixels> = units::Rect::new(30, 50);
+        assert!(!rect.can_hold(&units::Rect::new(30, 50)));
+        assert!(rect.can_hold(&units::Rect::new(29, 49)));
+    }
+
+    #[test]
+    fn unit_conversion_round_trips_within_rounding() {
+        use units::{Millimeters, Pixels, Rect};
+        for dpi in [25, 72, 96, 300, 1200] {
+            // A pixel round trip can be off by up to one millimeter's worth of pixels
+            let one_mm_in_px = (f64::from(dpi) / 25.4).ceil() as u32;
+            for v in [0u32, 1, 7, 99, 1000, 12345] {
+                let mm: Rect<Millimeters> = Rect::new(v, v);
+                let back: Rect<Millimeters> = mm.convert::<Pixels>(dpi).convert(dpi);
+                assert!(back.width.abs_diff(v) <= 1, "{} mm at {} dpi", v, dpi);
+
+                let px: Rect<Pixels> = Rect::new(v, v);
+                let back: Rect<Pixels> = px.convert::<Millimeters>(dpi).convert(dpi);
+                assert!(back.width.abs_diff(v) <= one_mm_in_px.max(1), "{} px at {} dpi", v, dpi);
+            }
+        }
+    }
+
+    #[test]
+    fn a4_at_300_dpi() {
+        let a4: units::Rect<units::Pixels> = units::Rect::<units::Millimeters>::new(210, 297).convert(300);
+        assert_eq!((a4.width, a4.height), (2480, 3508));
+    }
+
+    #[test]
+    fn multiply_with_white_and_screen_with_black_are_identity() {
+        let (color, white, black) = (Color(12, 130, 250), Color(255, 255, 255), Color(0, 0, 0));
+        assert_eq!(white.blend(&color, BlendMode::Multiply), color);
+        assert_eq!(color.blend(&white, BlendMode::Multiply), color);
+        assert_eq!(black.blend(&color, BlendMode::Screen), color);
+        assert_eq!(color.blend(&black, BlendMode::Screen), color);
+        assert_eq!(color.blend(&black, BlendMode::Normal), black);
+    }
+
+    #[test]
+    fn blend_modes_match_known_channel_values() {
+        let (base, top) = (Color(128, 200, 0), Color(128, 100, 255));
+        assert_eq!(base.blend(&top, BlendMode::Multiply), Color(64, 78, 0));
+        assert_eq!(base.blend(&top, BlendMode::Screen), Color(192, 222, 255));
+        assert_eq!(Color(200, 100, 0).blend(&Color(100, 200, 3), BlendMode::Add), Color(255, 255, 3));
+        assert_eq!(Color(64, 192, 0).blend(&Color(128, 128, 255), BlendMode::Overlay), Color(64, 192, 0));
+    }
+
+    // Multiplies by 10, but panics on 3 after scribbling -1 over it
+    fn fail_on_three(n: &mut i32) {
+        if *n == 3 {
+            *n = -1;
+            panic!("boom");
+        }
+        *n *= 10;
+    }
+
+    #[test]
+    fn apply_all_leaves_partial_update_on_panic() {
+        let mut stack: Stack<i32> = vec![1, 2, 3, 4].into_iter().collect();
+        assert_eq!(catch_panic(|| stack.apply_all(fail_on_three)), Err("boom".to_string()));
+        // Earlier items updated, the failing one half-written, later ones untouched
+        assert_eq!(stack.items, vec![10, 20, -1, 4]);
+    }
+
+    #[test]
+    fn apply_all_transactional_is_untouched_on_panic() {
+        let mut stack: Stack<i32> = vec![1, 2, 3, 4].into_iter().collect();
+        assert_eq!(catch_panic(|| stack.apply_all_transactional(fail_on_three)), Err("boom".to_string()));
+        assert_eq!(stack.items, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn apply_all_variants_agree_on_success() {
+        let mut in_place: Stack<i32> = (5..9).collect();
+        let mut transactional: Stack<i32> = (5..9).collect();
+        in_place.apply_all(|n| *n += 1);
+        transactional.apply_all_transactional(|n| *n += 1);
+        assert_eq!(in_place.items, vec![6, 7, 8, 9]);
+        assert_eq!(transactional.items, in_place.items);
+    }
+
+    #[test]
+    fn catch_panic_reads_formatted_messages() {
+        assert_eq!(catch_panic(|| panic!("n={}", 3)), Err::<(), _>("n=3".to_string()));
+        assert_eq!(catch_panic(|| 7), Ok(7));
+    }
+
+    #[test]
+    fn windows_mean_over_a_known_sequence() {
+        assert_eq!(windows_mean(&[1.0, 2.0, 3.0, 4.0, 8.0], 3), [2.0, 3.0, 5.0]);
+        assert_eq!(windows_mean(&[1.0, 2.0], 2), [1.5]);
+        assert_eq!(windows_mean(&[1.0, 5.0], 1), [1.0, 5.0]);
+    }
+
+    #[test]
+    fn windows_mean_with_oversized_or_zero_window_is_empty() {
+        assert!(windows_mean(&[1.0, 2.0], 3).is_empty());
+        assert!(windows_mean(&[1.0, 2.0], 0).is_empty());
+        assert!(windows_mean(&[], 1).is_empty());
+    }
+
+    static RAN_SKIPPED: AtomicBool = AtomicBool::new(false);
+    static RAN_SELECTED: AtomicBool = AtomicBool::new(false);
+
+    fn test_showcase() -> Showcase {
+        Showcase { config: config::ShowcaseConfig::default(), data: showcase_data() }
+    }
+
+    #[test]
+    fn find_section_ignores_case() {
+        assert_eq!(find_section("generics").map(|section| section.name), Some("Generics"));
+        let unwind = find_section("UNWIND SAFETY").map(|section| section.description);
+        assert_eq!(unwind, Some("unwind safety and `catch_unwind`"));
+        assert!(find_section("nope").is_none());
+    }
+
+    #[test]
+    fn registry_keeps_showcase_order() {
+        let names: Vec<&str> = registry().iter().map(|section| section.name).collect();
+        assert_eq!(names.len(), 52);
+        assert_eq!(names[..3], ["Basic Syntax", "Control Flow", "Functions"]);
+        assert_eq!(names[8], "Generics");
+        assert_eq!(names[51], "Generational Arenas");
+        // `--verify` covers every section except the printed ones, which it lists as skipped
+        let printed: Vec<&str> = registry()
+            .iter()
+            .filter(|section| matches!(section.output, Output::Printed(_)))
+            .map(|section| section.name)
+            .collect();
+        let unverified: Vec<&str> = UNVERIFIED.iter().map(|&(name, _)| name).collect();
+        assert_eq!(printed, unverified);
+        assert_eq!(SNAPSHOTS.len() + UNVERIFIED.len(), registry().len());
+    }
+
+    #[test]
+    fn duplicate_names_are_detected() {
+        assert!(has_unique_names(registry()));
+        let sections = [Section::new("Twice", "", |_| {}), Section::new("twice", "", |_| {})];
+        assert!(!has_unique_names(&sections));
+    }
+
+    #[test]
+    fn filtered_run_skips_other_sections() {
+        let sections = [
+            Section::new("Skipped", "", |_| RAN_SKIPPED.store(true, AtomicOrdering::SeqCst)),
+            Section::new("Selected", "", |_| RAN_SELECTED.store(true, AtomicOrdering::SeqCst)),
+        ];
+        let ran = run_section_list(&sections, &mut test_showcase(), &|section| section.name == "Selected");
+        assert_eq!(ran, 1);
+        assert!(RAN_SELECTED.load(AtomicOrdering::SeqCst));
+        assert!(!RAN_SKIPPED.load(AtomicOrdering::SeqCst));
+    }
+
+    const DUMP_SAMPLE: [Color; 3] = [Color(255, 0, 0), Color(0, 128, 10), Color(1, 2, 255)];
+
+    #[test]
+    fn hex_dump_wraps_after_the_given_count() {
+        assert_eq!(hex_dump(&DUMP_SAMPLE, 2), "#ff0000, #00800a,\n#0102ff");
+    }
+
+    #[test]
+    fn hex_dump_without_wrapping_is_one_line() {
+        assert_eq!(hex_dump(&DUMP_SAMPLE, 0), "#ff0000, #00800a, #0102ff"); // 0 means never wrap
+        assert_eq!(hex_dump(&DUMP_SAMPLE, 5), "#ff0000, #00800a, #0102ff");
+    }
+
+    #[test]
+    fn hex_dump_of_nothing_is_empty() {
+        assert_eq!(hex_dump(&[], 2), "");
+        assert_eq!(hex_dump(&[], 0), "");
+    }
+
+    fn not_found() -> Result<(), io::Error> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "gone"))
+    }
+
+    #[test]
+    fn stacked_contexts_read_outermost_first() {
+        let err = not_found().context("inner step").with_context(|| format!("outer step {}", 2)).unwrap_err();
+        assert_eq!(err.to_string(), "outer step 2: inner step: gone");
+        assert_eq!(err.source().unwrap().to_string(), "inner step: gone");
+        assert_eq!(error_chain(&err), ["outer step 2: inner step: gone", "inner step: gone", "gone"]);
+    }
+
+    #[test]
+    fn context_source_downcasts_to_original_error() {
+        let err = not_found().context("reading").unwrap_err();
+        let io_err = err.source().unwrap().downcast_ref::<io::Error>().unwrap();
+        assert_eq!(io_err.kind(), io::ErrorKind::NotFound);
+
+        let err = load_users("/definitely/not/here").unwrap_err();
+        let io_err = err.source().unwrap().downcast_ref::<io::Error>().unwrap();
+        assert_eq!(io_err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn with_context_is_lazy_on_ok() {
+        let ok: Result<u8, io::Error> = Ok(7);
+        assert_eq!(ok.with_context(|| panic!("context built for an Ok")).unwrap(), 7);
+    }
+
+    #[test]
+    fn condensed_chain_skips_repeated_causes() {
+        let err = not_found().context("inner step").context("outer step").unwrap_err();
+        assert_eq!(condensed_error_chain(&err), ["outer step: inner step: gone"]);
+        // Causes that add information are kept
+        let err = parse_user("username=a\nemail=b\nsign_in_count=x\nactive=true").unwrap_err();
+        assert_eq!(condensed_error_chain(&err), error_chain(&err));
+    }
+
+    #[test]
+    fn load_users_tolerates_crlf_and_extra_blank_lines() {
+        let path = std::env::temp_dir().join(format!("rust_showcase_users_test_{}.txt", std::process::id()));
+        let text = "username=a\r\nemail=a@x.com\r\nsign_in_count=1\r\nactive=true\r\n\r\n\r\n\
+                    username=b\r\nemail=b@x.com\r\nsign_in_count=2\r\nactive=false\r\n\r\n";
+        fs::write(&path, text).unwrap();
+        let users = load_users(&path.to_string_lossy());
+        fs::remove_file(&path).unwrap();
+        let names: Vec<String> = users.unwrap().into_iter().map(|user| user.username).collect();
+        assert_eq!(names, ["a", "b"]);
+    }
+
+    #[test]
+    fn cloned_feed_summarizes_like_the_original() {
+        let mut feed = Feed::new();
+        feed.push(Tweet::new("pat", "hello there", false, false).unwrap());
+        feed.push(Rectangle::square(2));
+        let mut copy = feed.clone();
+        assert_eq!(copy.summarize_all(), feed.summarize_all());
+        assert_eq!(copy.to_digest(), feed.to_digest());
+
+        // The clone owns its items: pushing to it leaves the original alone
+        copy.push(Rectangle::square(1));
+        assert_eq!((feed.summarize_all().len(), copy.summarize_all().len()), (2, 3));
+    }
+
+    #[test]
+    fn arena_reuse_bumps_generation() {
+        let mut arena = Arena::new();
+        let first = arena.insert("first");
+        assert_eq!(arena.remove(first), Some("first"));
+        let second = arena.insert("second");
+        assert_eq!(second.index, first.index);
+        assert_eq!(second.generation, first.generation + 1);
+    }
+
+    #[test]
+    fn arena_stale_handle_finds_nothing() {
+        let mut arena = Arena::new();
+        let stale = arena.insert(1);
+        arena.remove(stale);
+        let fresh = arena.insert(2);
+        assert_eq!(arena.get(stale), None);
+        assert_eq!(arena.get_mut(stale), None);
+        assert_eq!(arena.remove(stale), None);
+        assert_eq!(arena.get(fresh), Some(&2));
+    }
+
+    #[test]
+    fn arena_iter_skips_removed_slots() {
+        let mut arena = Arena::new();
+        let handles: Vec<Handle> = ["a", "b", "c"].into_iter().map(|name| arena.insert(name)).collect();
+        arena.remove(handles[1]);
+        let live: Vec<(Handle, &str)> = arena.iter().map(|(handle, &name)| (handle, name)).collect();
+        assert_eq!(live, vec![(handles[0], "a"), (handles[2], "c")]);
+        assert_eq!(arena.len(), 2);
+    }
+
+    #[test]
+    fn remove_subtree_takes_children_and_unlinks_from_parent() {
+        let mut scene = Arena::new();
+        let root = scene.insert(SceneNode::new("world"));
+        let player = add_child(&mut scene, root, "player").unwrap();
+        let sword = add_child(&mut scene, player, "sword").unwrap();
+        let camera = add_child(&mut scene, root, "camera").unwrap();
+
+        // Removing a node with children removes the children too
+        assert_eq!(remove_subtree(&mut scene, Some(root), player), 2);
+        assert_eq!(scene.get(sword).map(|node| node.name.as_str()), None);
+        assert_eq!(scene.get(root).unwrap().children, vec![camera]);
+        assert_eq!(remove_subtree(&mut scene, Some(root), player), 0);
+
+        let mut names = Vec::new();
+        visit(&scene, root, 0, &mut |node, depth| names.push((node.name.clone(), depth)));
+        assert_eq!(names, vec![("world".to_string(), 0), ("camera".to_string(), 1)]);
     }
 }
 